@@ -0,0 +1,244 @@
+use std::sync::Arc;
+use std::sync::mpsc::{Sender, channel};
+use std::time::Instant;
+
+use ratatui::crossterm::event::KeyCode;
+
+use super::config::profile_section;
+use super::external::{ExternalCommand, ExternalInput};
+use super::{App, SearchMsg};
+use crate::aws::roles::{MfaPrompt, base_profile};
+use crate::aws::{AuthErrorKind, AuthFailure, invalidate_client};
+
+/// An auth failure shown above the panes until it's fixed or dismissed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuthBanner {
+    pub failure: AuthFailure,
+    /// `aws sso login --profile X` when the profile (a role's base profile)
+    /// signs in through SSO.
+    pub login_command: Option<String>,
+}
+
+/// `'prod'`, safe to paste into `sh -c`.
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+impl AuthBanner {
+    /// `sso_profile` is the SSO profile that renews the failed one's
+    /// credentials, if any.
+    pub fn new(failure: AuthFailure, sso_profile: Option<String>) -> AuthBanner {
+        let login_command = sso_profile
+            .filter(|_| failure.kind == AuthErrorKind::Expired)
+            .map(|profile| format!("aws sso login --profile {}", shell_quote(&profile)));
+        AuthBanner {
+            failure,
+            login_command,
+        }
+    }
+
+    /// One line: what went wrong and what to press.
+    pub fn text(&self) -> String {
+        let profile = profile_section(&self.failure.profile);
+        match (self.failure.kind, &self.login_command) {
+            (AuthErrorKind::Expired, Some(_)) => {
+                format!("SSO session for '{profile}' has expired.  A Log in and retry  Esc Dismiss")
+            }
+            (AuthErrorKind::Expired, None) => format!(
+                "Credentials for '{profile}' expired or were rejected; refresh them, then A Retry  Esc Dismiss"
+            ),
+            (AuthErrorKind::MissingProfile, _) => {
+                format!("Profile '{profile}' is not defined.  P Pick another profile  Esc Dismiss")
+            }
+            (AuthErrorKind::AccessDenied, _) => format!(
+                "'{profile}' may not read CloudWatch Logs in {}; check its IAM permissions.  Esc Dismiss",
+                self.failure.region
+            ),
+        }
+    }
+}
+
+/// The MFA code popup, open while a role is being assumed.
+#[derive(Debug)]
+pub struct MfaCodePrompt {
+    pub role: String,
+    pub serial: String,
+    pub code: String,
+    /// Where the code goes; dropping it unanswered cancels.
+    pub reply: Sender<Option<String>>,
+}
+
+/// MFA prompt for the TUI: ask the UI loop for the code and wait for it.
+/// A closed app counts as no code.
+pub fn tui_mfa_prompt(tx: Sender<SearchMsg>) -> Arc<MfaPrompt> {
+    Arc::new(move |role: &str, serial: &str| {
+        let (reply, answer) = channel();
+        tx.send(SearchMsg::MfaPrompt(MfaCodePrompt {
+            role: role.to_string(),
+            serial: serial.to_string(),
+            code: String::new(),
+            reply,
+        }))
+        .ok()?;
+        answer.recv().ok().flatten()
+    })
+}
+
+impl App {
+    /// Enter sends the code, Esc gives up on the role (its fetch fails).
+    pub fn handle_mfa_prompt_key(&mut self, code: KeyCode) {
+        let Some(prompt) = &mut self.state.mfa_prompt else {
+            return;
+        };
+        match code {
+            KeyCode::Enter if !prompt.code.is_empty() => {
+                if let Some(prompt) = self.state.mfa_prompt.take() {
+                    let _ = prompt.reply.send(Some(prompt.code));
+                }
+            }
+            KeyCode::Esc => {
+                if let Some(prompt) = self.state.mfa_prompt.take() {
+                    let _ = prompt.reply.send(None);
+                }
+            }
+            KeyCode::Backspace => {
+                prompt.code.pop();
+            }
+            KeyCode::Char(c) if c.is_ascii_digit() && prompt.code.len() < 6 => {
+                prompt.code.push(c);
+            }
+            _ => {}
+        }
+    }
+
+    pub fn show_auth_failure(&mut self, failure: AuthFailure) {
+        // A role's credentials come from its base profile; that's the one
+        // to log in with
+        let base = profile_section(&base_profile(&failure.profile));
+        let sso_profile = (self.state.sso_check)(&base).then_some(base);
+        self.state.auth_banner = Some(AuthBanner::new(failure, sso_profile));
+    }
+
+    /// `A` with the banner up: log in through SSO (TUI suspended), or just
+    /// retry when the credentials were refreshed some other way.
+    pub fn reauthenticate(&mut self) {
+        let Some(banner) = &self.state.auth_banner else {
+            return;
+        };
+        if banner.failure.kind != AuthErrorKind::Expired {
+            return;
+        }
+        match banner.login_command.clone() {
+            Some(command) => {
+                self.state.auth_retry = true;
+                self.state.pending_external = Some(ExternalCommand {
+                    command,
+                    content: String::new(),
+                    input: ExternalInput::Terminal,
+                    extension: "txt",
+                    wait_for_enter: false,
+                });
+            }
+            None => self.retry_after_login(),
+        }
+    }
+
+    /// Called once the login command has run.
+    pub fn finish_login(&mut self, success: bool) {
+        if !std::mem::take(&mut self.state.auth_retry) {
+            return;
+        }
+        if success {
+            self.retry_after_login();
+        } else {
+            self.state.status_message = Some("Login failed; press A to try again".to_string());
+            self.state.status_set_at = Some(Instant::now());
+        }
+    }
+
+    /// Fresh credentials: rebuild the client and redo whatever failed, the
+    /// group list or else the last search.
+    fn retry_after_login(&mut self) {
+        let Some(banner) = self.state.auth_banner.take() else {
+            return;
+        };
+        invalidate_client(&banner.failure.region, &banner.failure.profile);
+        if self
+            .state
+            .all_groups
+            .iter()
+            .any(|g| g.starts_with("(error"))
+        {
+            self.reload_groups();
+        } else if !self.state.search_group.is_empty() {
+            self.start_search();
+        }
+        self.state.status_message = Some("Retrying with fresh credentials...".to_string());
+        self.state.status_set_at = Some(Instant::now());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::state::AppState;
+
+    fn failure(kind: AuthErrorKind, profile: &str) -> AuthFailure {
+        AuthFailure {
+            kind,
+            profile: profile.to_string(),
+            region: "eu-west-1".to_string(),
+            message: "boom".to_string(),
+        }
+    }
+
+    #[test]
+    fn login_is_offered_for_expired_sso_profiles_only() {
+        let banner = AuthBanner::new(
+            failure(AuthErrorKind::Expired, "team's prod"),
+            Some("team's prod".to_string()),
+        );
+        assert_eq!(
+            banner.login_command.as_deref(),
+            Some(r"aws sso login --profile 'team'\''s prod'")
+        );
+        assert!(banner.text().contains("A Log in and retry"));
+
+        let banner = AuthBanner::new(failure(AuthErrorKind::Expired, "keys"), None);
+        assert!(banner.login_command.is_none());
+        assert!(banner.text().contains("A Retry"));
+
+        let banner = AuthBanner::new(
+            failure(AuthErrorKind::AccessDenied, "prod"),
+            Some("prod".to_string()),
+        );
+        assert!(banner.login_command.is_none());
+        assert!(banner.text().contains("IAM permissions"));
+        assert!(
+            AuthBanner::new(failure(AuthErrorKind::MissingProfile, "gone"), None)
+                .text()
+                .contains("P Pick another profile")
+        );
+    }
+
+    #[test]
+    fn banner_asks_the_sso_check_about_the_failed_profile() {
+        let mut app = App::for_test(AppState {
+            sso_check: |profile| profile == "sso-hub",
+            ..AppState::for_test()
+        });
+        app.show_auth_failure(failure(AuthErrorKind::Expired, "sso-hub"));
+        assert_eq!(
+            app.state
+                .auth_banner
+                .take()
+                .unwrap()
+                .login_command
+                .as_deref(),
+            Some("aws sso login --profile 'sso-hub'")
+        );
+
+        app.show_auth_failure(failure(AuthErrorKind::Expired, "static-keys"));
+        assert!(app.state.auth_banner.unwrap().login_command.is_none());
+    }
+}
@@ -1,92 +1,278 @@
 use crate::app::App;
+use crate::aws::console::log_events_url;
+use crate::aws::{LogEvent, normalize_filter_pattern};
 use arboard::Clipboard;
+use ratatui::crossterm::event::KeyCode;
+use std::io::Write;
 use std::time::Instant;
 
+/// Largest OSC 52 payload (after base64) we send. Many terminals drop longer
+/// sequences silently (xterm, hterm and tmux all cap around 100 KB), so we
+/// refuse and say so instead of pretending the copy worked.
+pub const OSC52_MAX_ENCODED_BYTES: usize = 100_000;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ClipboardMode {
+    /// System clipboard via arboard, falling back to OSC 52 when no display
+    /// is reachable (SSH, containers, bare tmux).
+    Auto,
+    /// System clipboard only.
+    System,
+    /// Always use the OSC 52 terminal escape.
+    Osc52,
+}
+
+impl ClipboardMode {
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label.trim().to_ascii_lowercase().as_str() {
+            "auto" | "" => Some(ClipboardMode::Auto),
+            "system" | "arboard" => Some(ClipboardMode::System),
+            "osc52" | "osc" | "terminal" => Some(ClipboardMode::Osc52),
+            _ => None,
+        }
+    }
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub fn base64_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        out.push(BASE64_ALPHABET[(n >> 18) as usize & 63] as char);
+        out.push(BASE64_ALPHABET[(n >> 12) as usize & 63] as char);
+        out.push(if chunk.len() > 1 {
+            BASE64_ALPHABET[(n >> 6) as usize & 63] as char
+        } else {
+            '='
+        });
+        out.push(if chunk.len() > 2 {
+            BASE64_ALPHABET[n as usize & 63] as char
+        } else {
+            '='
+        });
+    }
+    out
+}
+
+/// OSC 52 "set clipboard" escape for `text`. Inside tmux the sequence is
+/// wrapped in a DCS passthrough (needs `set -g allow-passthrough on`), with
+/// the inner ESC doubled as tmux requires.
+pub fn osc52_sequence(text: &str, tmux: bool) -> Result<String, String> {
+    let encoded = base64_encode(text.as_bytes());
+    if encoded.len() > OSC52_MAX_ENCODED_BYTES {
+        return Err(format!(
+            "too large for OSC 52 ({} KB, limit {} KB); use e to export instead",
+            encoded.len() / 1024,
+            OSC52_MAX_ENCODED_BYTES / 1024
+        ));
+    }
+
+    let seq = format!("\x1b]52;c;{encoded}\x07");
+    if tmux {
+        Ok(format!(
+            "\x1bPtmux;{}\x1b\\",
+            seq.replace('\x1b', "\x1b\x1b")
+        ))
+    } else {
+        Ok(seq)
+    }
+}
+
+fn copy_with_osc52(text: &str) -> Result<(), String> {
+    let tmux = std::env::var_os("TMUX").is_some();
+    let seq = osc52_sequence(text, tmux)?;
+    let mut out = std::io::stdout();
+    out.write_all(seq.as_bytes())
+        .and_then(|_| out.flush())
+        .map_err(|e| format!("write OSC 52: {e}"))
+}
+
+fn copy_with_arboard(text: &str) -> Result<(), String> {
+    Clipboard::new()
+        .and_then(|mut clipboard| clipboard.set_text(text.to_string()))
+        .map_err(|e| e.to_string())
+}
+
+/// Put `text` on the clipboard using `mode`. Returns the backend used.
+pub fn copy_text(text: &str, mode: ClipboardMode) -> Result<&'static str, String> {
+    match mode {
+        ClipboardMode::System => copy_with_arboard(text).map(|_| "system"),
+        ClipboardMode::Osc52 => copy_with_osc52(text).map(|_| "OSC 52"),
+        ClipboardMode::Auto => match copy_with_arboard(text) {
+            Ok(()) => Ok("system"),
+            Err(_) => copy_with_osc52(text).map(|_| "OSC 52"),
+        },
+    }
+}
+
+/// Entries of the `Y` copy menu for the event under the cursor.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CopyFormat {
+    Formatted,
+    RawMessage,
+    JsonCompact,
+    JsonPretty,
+    IdAndStream,
+    ConsoleLink,
+}
+
+impl CopyFormat {
+    pub const ALL: [CopyFormat; 6] = [
+        CopyFormat::Formatted,
+        CopyFormat::RawMessage,
+        CopyFormat::JsonCompact,
+        CopyFormat::JsonPretty,
+        CopyFormat::IdAndStream,
+        CopyFormat::ConsoleLink,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CopyFormat::Formatted => "Formatted entry",
+            CopyFormat::RawMessage => "Raw message",
+            CopyFormat::JsonCompact => "JSON (compact)",
+            CopyFormat::JsonPretty => "JSON (pretty)",
+            CopyFormat::IdAndStream => "Event ID and stream",
+            CopyFormat::ConsoleLink => "Console link",
+        }
+    }
+}
+
 impl App {
+    /// Text for `format` of the event under the cursor; Err explains why
+    /// there is nothing to copy.
+    pub fn event_copy_text(&self, ev: &LogEvent, format: CopyFormat) -> Result<String, String> {
+        match format {
+            CopyFormat::Formatted => Ok(ev.formatted()),
+            CopyFormat::RawMessage => Ok(ev.message.trim_end().to_string()),
+            CopyFormat::JsonCompact => ev
+                .json_payload()
+                .map(|json| json.to_string())
+                .ok_or_else(|| "Event has no JSON payload".to_string()),
+            CopyFormat::JsonPretty => ev
+                .json_payload()
+                .and_then(|json| serde_json::to_string_pretty(&json).ok())
+                .ok_or_else(|| "Event has no JSON payload".to_string()),
+            CopyFormat::IdAndStream => Ok(format!("{} {}", ev.event_id, ev.log_stream_name)),
+            CopyFormat::ConsoleLink => {
+                if self.state.search_group.is_empty() {
+                    return Err("No log group for this event".to_string());
+                }
+                let (_, region, group) = self.group_target(&self.state.search_group);
+                // A minute either side so the console shows some context.
+                Ok(log_events_url(
+                    &region,
+                    &group,
+                    Some(&ev.log_stream_name),
+                    ev.timestamp_ms - 60_000,
+                    ev.timestamp_ms + 60_000,
+                    &normalize_filter_pattern(&self.state.search_query),
+                    Some(&ev.event_id),
+                ))
+            }
+        }
+    }
+
+    pub fn open_copy_menu(&mut self) {
+        if self.current_event().is_none() {
+            self.state.status_message = Some("No event under the cursor".to_string());
+            self.state.status_set_at = Some(Instant::now());
+            return;
+        }
+        self.state.copy_menu_selected = 0;
+        self.state.copy_menu_open = true;
+    }
+
+    pub fn handle_copy_menu_key(&mut self, code: KeyCode) {
+        let last = CopyFormat::ALL.len() - 1;
+        let chosen = match code {
+            KeyCode::Esc => {
+                self.state.copy_menu_open = false;
+                None
+            }
+            KeyCode::Up => {
+                self.state.copy_menu_selected = self.state.copy_menu_selected.saturating_sub(1);
+                None
+            }
+            KeyCode::Down => {
+                self.state.copy_menu_selected = (self.state.copy_menu_selected + 1).min(last);
+                None
+            }
+            KeyCode::Enter => Some(self.state.copy_menu_selected),
+            // 1-6 pick an entry directly
+            KeyCode::Char(c) => c
+                .to_digit(10)
+                .and_then(|d| (d as usize).checked_sub(1))
+                .filter(|&idx| idx <= last),
+            _ => None,
+        };
+
+        let Some(idx) = chosen else {
+            return;
+        };
+        self.state.copy_menu_open = false;
+
+        let format = CopyFormat::ALL[idx];
+        let Some(ev) = self.current_event() else {
+            return;
+        };
+        match self.event_copy_text(ev, format) {
+            Ok(text) => {
+                let what = format.label().to_lowercase();
+                self.copy_to_clipboard(&text, &what);
+            }
+            Err(e) => {
+                self.state.status_message = Some(e);
+                self.state.status_set_at = Some(Instant::now());
+            }
+        }
+    }
+
     pub fn results_text(&self) -> String {
         self.state.lines.join("\n")
     }
 
+    /// Copy `text` and report `what` in the status bar, e.g. "12 lines".
+    pub fn copy_to_clipboard(&mut self, text: &str, what: &str) {
+        self.state.status_message = Some(match copy_text(text, self.state.clipboard_mode) {
+            Ok("system") => format!("Copied {what} to clipboard"),
+            Ok(backend) => format!("Copied {what} to clipboard ({backend})"),
+            Err(e) => format!("Clipboard error: {e}"),
+        });
+        self.state.status_set_at = Some(Instant::now());
+    }
+
     pub fn copy_results_to_clipboard(&mut self) {
         let text = self.results_text();
         if text.trim().is_empty() {
             return;
         }
 
-        if let Ok(mut clipboard) = Clipboard::new() {
-            if clipboard.set_text(text.clone()).is_ok() {
-                self.state.status_message = Some(format!(
-                    "Copied {} lines to clipboard",
-                    self.state.lines.len()
-                ));
-                self.state.status_set_at = Some(Instant::now());
-            }
-        }
+        let what = format!("{} lines", self.state.lines.len());
+        self.copy_to_clipboard(&text, &what);
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::app::state::AppState;
-    use crate::app::{App, FilterField, Focus};
-    use crate::ui::styles::Theme;
-    use std::sync::atomic::AtomicBool;
-    use std::sync::{Arc, mpsc};
-    use std::time::Instant as StdInstant;
+    use crate::app::{App, Focus};
 
     fn app_with_results(lines: Vec<&str>) -> App {
-        let (tx, rx) = mpsc::channel();
-
         let state = AppState {
-            app_title: "Test".to_string(),
-            theme: Theme::default_dark(),
-            theme_name: "dark".to_string(),
             lines: lines.into_iter().map(|s| s.to_string()).collect(),
-            filter_cursor_pos: 0,
-
-            all_groups: Vec::new(),
-            groups: Vec::new(),
-            selected_group: 0,
-            groups_scroll: 0,
-
-            profile: "test-profile".to_string(),
-            region: "eu-west-1".to_string(),
             focus: Focus::Results,
-
-            filter_start: String::new(),
-            filter_end: String::new(),
-            filter_query: String::new(),
-            filter_field: FilterField::Query,
-            editing: false,
-            cursor_on: true,
-            last_blink: StdInstant::now(),
-
-            group_search_active: false,
-            group_search_input: String::new(),
-
-            searching: false,
-            dots: 0,
-            last_dots: StdInstant::now(),
-            results_scroll: 0,
-            tail_mode: false,
-
-            status_message: None,
-            status_set_at: None,
-
-            saved_filters: Vec::new(),
-            save_filter_popup_open: false,
-            save_filter_name: String::new(),
-            load_filter_popup_open: false,
-            load_filter_selected: 0,
+            ..AppState::for_test()
         };
 
-        App {
-            state,
-            exit: false,
-            search_tx: tx,
-            search_rx: rx,
-            tail_stop: Arc::new(AtomicBool::new(false)),
-        }
+        App::for_test(state)
     }
 
     #[test]
@@ -111,4 +297,115 @@ mod tests {
         app.copy_results_to_clipboard();
         assert!(app.state.status_message.is_none());
     }
+
+    #[test]
+    fn base64_matches_rfc4648_vectors() {
+        use super::base64_encode;
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foo"), "Zm9v");
+        assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[test]
+    fn osc52_sequence_plain_and_tmux() {
+        use super::osc52_sequence;
+        assert_eq!(osc52_sequence("hi", false).unwrap(), "\x1b]52;c;aGk=\x07");
+        assert_eq!(
+            osc52_sequence("hi", true).unwrap(),
+            "\x1bPtmux;\x1b\x1b]52;c;aGk=\x07\x1b\\"
+        );
+    }
+
+    #[test]
+    fn osc52_refuses_payloads_over_the_limit() {
+        use super::{OSC52_MAX_ENCODED_BYTES, osc52_sequence};
+        let big = "x".repeat(OSC52_MAX_ENCODED_BYTES);
+        let err = osc52_sequence(&big, false).unwrap_err();
+        assert!(err.contains("too large"), "error was: {err}");
+    }
+
+    #[test]
+    fn clipboard_mode_labels() {
+        use super::ClipboardMode;
+        assert_eq!(
+            ClipboardMode::from_label("OSC52"),
+            Some(ClipboardMode::Osc52)
+        );
+        assert_eq!(
+            ClipboardMode::from_label("system"),
+            Some(ClipboardMode::System)
+        );
+        assert_eq!(ClipboardMode::from_label(""), Some(ClipboardMode::Auto));
+        assert_eq!(ClipboardMode::from_label("nope"), None);
+    }
+
+    fn sample_event(message: &str) -> crate::aws::LogEvent {
+        crate::aws::LogEvent {
+            timestamp_ms: 120_000,
+            message: message.to_string(),
+            log_stream_name: "stream-1".to_string(),
+            event_id: "evt-1".to_string(),
+        }
+    }
+
+    #[test]
+    fn event_copy_text_variants() {
+        use super::CopyFormat;
+        let app = app_with_results(Vec::new());
+        let ev = sample_event("INFO {\"a\": 1}\n");
+
+        assert_eq!(
+            app.event_copy_text(&ev, CopyFormat::RawMessage).unwrap(),
+            "INFO {\"a\": 1}"
+        );
+        assert_eq!(
+            app.event_copy_text(&ev, CopyFormat::JsonCompact).unwrap(),
+            "{\"a\":1}"
+        );
+        assert_eq!(
+            app.event_copy_text(&ev, CopyFormat::JsonPretty).unwrap(),
+            "{\n  \"a\": 1\n}"
+        );
+        assert_eq!(
+            app.event_copy_text(&ev, CopyFormat::IdAndStream).unwrap(),
+            "evt-1 stream-1"
+        );
+        assert!(
+            app.event_copy_text(&sample_event("plain"), CopyFormat::JsonCompact)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn console_link_points_at_the_event() {
+        use super::CopyFormat;
+        let mut app = app_with_results(Vec::new());
+        let ev = sample_event("boom");
+
+        // Nothing searched yet: no group to link to
+        assert!(app.event_copy_text(&ev, CopyFormat::ConsoleLink).is_err());
+
+        app.state.search_group = "/aws/lambda/api".to_string();
+        let url = app.event_copy_text(&ev, CopyFormat::ConsoleLink).unwrap();
+        assert!(url.starts_with("https://eu-west-1.console.aws.amazon.com/"));
+        assert!(url.contains("log-group/$252Faws$252Flambda$252Fapi/log-events/stream-1"));
+        assert!(url.contains("start$3D60000$26end$3D180000"));
+        assert!(url.ends_with("refEventId$3Devt-1"));
+    }
+
+    #[test]
+    fn copy_menu_number_keys_close_menu() {
+        use ratatui::crossterm::event::KeyCode;
+        let mut app = app_with_results(Vec::new());
+        app.state.copy_menu_open = true;
+        app.handle_copy_menu_key(KeyCode::Down);
+        assert_eq!(app.state.copy_menu_selected, 1);
+        // Out-of-range digits are ignored
+        app.handle_copy_menu_key(KeyCode::Char('9'));
+        assert!(app.state.copy_menu_open);
+        app.handle_copy_menu_key(KeyCode::Esc);
+        assert!(!app.state.copy_menu_open);
+    }
 }
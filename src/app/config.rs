@@ -0,0 +1,422 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use serde::Deserialize;
+
+use super::App;
+use super::clipboard::ClipboardMode;
+use super::filters::{config_file_path, expand_home};
+use crate::aws::roles::{RoleSpec, validate_roles};
+use crate::aws::{FALLBACK_REGION, check_endpoint_url, resolve_time_window};
+use crate::ui::styles::{BUILTIN_THEMES, NO_COLOR_THEME, Theme, ThemeSpec, theme_by_name};
+
+pub const CONFIG_FILE: &str = "config.json";
+pub const THEMES_FILE: &str = "themes.json";
+
+/// Settings as they appear in the file. Every key is optional; a profile
+/// section only needs the keys it changes.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct RawSettings {
+    /// Window searched when Start is empty, e.g. "15m", "1h", "2d".
+    pub default_window: Option<String>,
+    /// Region used when neither --region nor the AWS config names one.
+    pub fallback_region: Option<String>,
+    pub theme: Option<String>,
+    pub tail_interval_secs: Option<u64>,
+    /// Results kept before the oldest quarter is dropped.
+    pub max_lines: Option<usize>,
+    pub status_timeout_secs: Option<u64>,
+    /// auto, system or osc52.
+    pub clipboard: Option<String>,
+    /// Extra read-only saved-filter files, e.g. a team's shared library.
+    pub filter_files: Option<Vec<String>>,
+    /// `profile/region` pairs whose groups are all listed in the Groups pane.
+    pub contexts: Option<Vec<String>>,
+    /// CloudWatch Logs endpoint, e.g. LocalStack's "http://localhost:4566".
+    pub endpoint_url: Option<String>,
+}
+
+impl RawSettings {
+    /// Keys set in `other` win.
+    fn merge(&mut self, other: &RawSettings) {
+        let other = other.clone();
+        self.default_window = other.default_window.or(self.default_window.take());
+        self.fallback_region = other.fallback_region.or(self.fallback_region.take());
+        self.theme = other.theme.or(self.theme.take());
+        self.tail_interval_secs = other.tail_interval_secs.or(self.tail_interval_secs);
+        self.max_lines = other.max_lines.or(self.max_lines);
+        self.status_timeout_secs = other.status_timeout_secs.or(self.status_timeout_secs);
+        self.clipboard = other.clipboard.or(self.clipboard.take());
+        self.filter_files = other.filter_files.or(self.filter_files.take());
+        self.contexts = other.contexts.or(self.contexts.take());
+        self.endpoint_url = other.endpoint_url.or(self.endpoint_url.take());
+    }
+}
+
+/// `config.json`: `defaults` apply everywhere, `profiles.<name>` on top of
+/// them for that AWS profile. `roles` are assumed from another profile and
+/// can then be used as profile names themselves.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigFile {
+    pub defaults: RawSettings,
+    pub profiles: BTreeMap<String, RawSettings>,
+    pub roles: BTreeMap<String, RoleSpec>,
+}
+
+/// Resolved, validated settings for one profile.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Settings {
+    /// Relative start used for an empty Start field, e.g. "-15m".
+    pub default_start: String,
+    pub fallback_region: String,
+    pub theme: String,
+    pub tail_interval: Duration,
+    pub max_lines: usize,
+    pub status_timeout: Duration,
+    pub clipboard: ClipboardMode,
+    pub filter_files: Vec<PathBuf>,
+    /// `(profile, region)` pairs listed together; empty means just the
+    /// current one.
+    pub contexts: Vec<(String, String)>,
+    /// Every role in the file, whichever profile is active.
+    pub roles: BTreeMap<String, RoleSpec>,
+    /// None means the real AWS endpoint.
+    pub endpoint_url: Option<String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            default_start: "-15m".to_string(),
+            fallback_region: FALLBACK_REGION.to_string(),
+            theme: "dark".to_string(),
+            tail_interval: Duration::from_secs(3),
+            max_lines: 2000,
+            status_timeout: Duration::from_secs(2),
+            clipboard: ClipboardMode::Auto,
+            filter_files: Vec::new(),
+            contexts: Vec::new(),
+            roles: BTreeMap::new(),
+            endpoint_url: None,
+        }
+    }
+}
+
+impl Settings {
+    /// Built-in defaults, then `defaults`, then the `profile` section.
+    pub fn resolve(file: &ConfigFile, profile: &str) -> Result<Settings, String> {
+        let mut raw = file.defaults.clone();
+        if let Some(section) = file.profiles.get(profile) {
+            raw.merge(section);
+        }
+
+        let mut settings = Settings::default();
+        if let Some(window) = raw.default_window {
+            let start = format!("-{}", window.trim().trim_start_matches('-'));
+            resolve_time_window(&start, "", Utc::now().timestamp_millis())
+                .map_err(|_| format!("default_window: '{window}' is not like 15m, 1h or 2d"))?;
+            settings.default_start = start;
+        }
+        if let Some(region) = raw.fallback_region {
+            if region.trim().is_empty() {
+                return Err("fallback_region: must not be empty".to_string());
+            }
+            settings.fallback_region = region;
+        }
+        // Checked at startup, once the user themes are loaded too
+        if let Some(theme) = raw.theme {
+            settings.theme = theme;
+        }
+        if let Some(secs) = raw.tail_interval_secs {
+            if secs == 0 {
+                return Err("tail_interval_secs: must be at least 1".to_string());
+            }
+            settings.tail_interval = Duration::from_secs(secs);
+        }
+        if let Some(lines) = raw.max_lines {
+            if lines < 100 {
+                return Err("max_lines: must be at least 100".to_string());
+            }
+            settings.max_lines = lines;
+        }
+        if let Some(secs) = raw.status_timeout_secs {
+            settings.status_timeout = Duration::from_secs(secs.max(1));
+        }
+        if let Some(mode) = raw.clipboard {
+            settings.clipboard = ClipboardMode::from_label(&mode).ok_or_else(|| {
+                format!("clipboard: unknown mode '{mode}' (expected auto, system or osc52)")
+            })?;
+        }
+        if let Some(files) = raw.filter_files {
+            settings.filter_files = files.iter().map(|f| expand_home(f)).collect();
+        }
+        if let Some(contexts) = raw.contexts {
+            settings.contexts = contexts
+                .iter()
+                .map(|c| match c.rsplit_once('/') {
+                    Some((profile, region))
+                        if !profile.trim().is_empty() && !region.trim().is_empty() =>
+                    {
+                        Ok((profile.trim().to_string(), region.trim().to_string()))
+                    }
+                    _ => Err(format!("contexts: '{c}' is not like prod/eu-west-1")),
+                })
+                .collect::<Result<_, _>>()?;
+        }
+        if let Some(url) = raw.endpoint_url {
+            check_endpoint_url(&url).map_err(|e| format!("endpoint_url: {e}"))?;
+            settings.endpoint_url = Some(url);
+        }
+        validate_roles(&file.roles)?;
+        settings.roles = file.roles.clone();
+        Ok(settings)
+    }
+}
+
+/// Parse a config file's contents; errors carry line/column from serde.
+pub fn parse_config(data: &str) -> Result<ConfigFile, String> {
+    serde_json::from_str(data).map_err(|e| e.to_string())
+}
+
+pub fn load_config_from(path: &Path, profile: &str) -> Result<Settings, String> {
+    if !path.exists() {
+        return Ok(Settings::default());
+    }
+    let data = std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    parse_config(&data)
+        .and_then(|file| Settings::resolve(&file, profile))
+        .map_err(|e| format!("{}: {e}", path.display()))
+}
+
+pub fn config_path() -> Result<PathBuf, String> {
+    config_file_path(CONFIG_FILE)
+}
+
+/// Settings for `profile` from `~/.config/lumberjack/config.json`; built-in
+/// defaults when the file doesn't exist.
+pub fn load_settings(profile: &str) -> Result<Settings, String> {
+    load_config_from(&config_path()?, profile)
+}
+
+/// Build the themes in a `themes.json` array, in file order.
+pub fn parse_themes(data: &str) -> Result<Vec<(String, Theme)>, String> {
+    let specs: Vec<ThemeSpec> = serde_json::from_str(data).map_err(|e| e.to_string())?;
+    let mut themes: Vec<(String, Theme)> = Vec::new();
+    for spec in specs {
+        if BUILTIN_THEMES.contains(&spec.name.as_str()) || spec.name == NO_COLOR_THEME {
+            return Err(format!(
+                "theme '{}': name is taken by a built-in theme",
+                spec.name
+            ));
+        }
+        if themes.iter().any(|(name, _)| *name == spec.name) {
+            return Err(format!("theme '{}' is defined twice", spec.name));
+        }
+        themes.push((spec.name.clone(), spec.build()?));
+    }
+    Ok(themes)
+}
+
+/// User themes from `~/.config/lumberjack/themes.json`; none when the file
+/// doesn't exist.
+pub fn load_user_themes() -> Result<Vec<(String, Theme)>, String> {
+    let path = config_file_path(THEMES_FILE)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let data = std::fs::read_to_string(&path).map_err(|e| format!("{}: {e}", path.display()))?;
+    parse_themes(&data).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+/// True when `NO_COLOR` is set to anything non-empty (see no-color.org).
+pub fn no_color_requested() -> bool {
+    std::env::var("NO_COLOR").is_ok_and(|v| !v.is_empty())
+}
+
+impl App {
+    /// `T`: built-in themes, then the user's, then back to the first.
+    pub fn cycle_theme(&mut self) {
+        if self.state.theme_name == NO_COLOR_THEME {
+            self.state.status_message = Some("Colors are off (NO_COLOR is set)".to_string());
+            self.state.status_set_at = Some(Instant::now());
+            return;
+        }
+
+        let names: Vec<&str> = BUILTIN_THEMES
+            .iter()
+            .copied()
+            .chain(self.state.user_themes.iter().map(|(name, _)| name.as_str()))
+            .collect();
+        let next = names
+            .iter()
+            .position(|name| *name == self.state.theme_name)
+            .map_or(0, |idx| (idx + 1) % names.len());
+        let name = names[next].to_string();
+
+        if let Some(theme) = theme_by_name(&name, &self.state.user_themes) {
+            self.state.theme = theme;
+            self.state.theme_name = name;
+        }
+    }
+}
+
+/// Section name used for a profile: the explicit one, else `AWS_PROFILE`,
+/// else "default" (what the credential chain would pick).
+pub fn profile_section(profile: &str) -> String {
+    if !profile.is_empty() {
+        return profile.to_string();
+    }
+    std::env::var("AWS_PROFILE")
+        .ok()
+        .filter(|p| !p.trim().is_empty())
+        .unwrap_or_else(|| "default".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_keys_keep_built_in_defaults() {
+        let file = parse_config("{}").unwrap();
+        assert_eq!(Settings::resolve(&file, "prod"), Ok(Settings::default()));
+    }
+
+    #[test]
+    fn profile_section_overrides_defaults() {
+        let file = parse_config(
+            r#"{
+                "defaults": { "default_window": "1h", "theme": "light", "max_lines": 5000 },
+                "profiles": {
+                    "prod": {
+                        "theme": "green", "tail_interval_secs": 10, "clipboard": "osc52",
+                        "contexts": ["prod/eu-west-1", "prod/us-east-1"]
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let prod = Settings::resolve(&file, "prod").unwrap();
+        assert_eq!(prod.default_start, "-1h");
+        assert_eq!(prod.theme, "green");
+        assert_eq!(prod.max_lines, 5000);
+        assert_eq!(prod.tail_interval, Duration::from_secs(10));
+        assert_eq!(prod.clipboard, ClipboardMode::Osc52);
+        assert!(prod.filter_files.is_empty());
+        assert_eq!(
+            prod.contexts,
+            vec![
+                ("prod".to_string(), "eu-west-1".to_string()),
+                ("prod".to_string(), "us-east-1".to_string())
+            ]
+        );
+
+        let dev = Settings::resolve(&file, "dev").unwrap();
+        assert_eq!(dev.theme, "light");
+        assert_eq!(dev.tail_interval, Duration::from_secs(3));
+        assert_eq!(dev.fallback_region, FALLBACK_REGION);
+    }
+
+    #[test]
+    fn reports_bad_values_and_unknown_keys() {
+        let err = parse_config(r#"{ "defaults": { "tail_interval": 3 } }"#).unwrap_err();
+        assert!(err.contains("unknown field `tail_interval`"), "{err}");
+        assert!(err.contains("line 1"), "{err}");
+
+        let resolve = |json: &str| Settings::resolve(&parse_config(json).unwrap(), "default");
+        assert!(
+            resolve(r#"{ "defaults": { "default_window": "soon" } }"#)
+                .unwrap_err()
+                .starts_with("default_window:")
+        );
+        assert!(
+            resolve(r#"{ "defaults": { "clipboard": "pigeon" } }"#)
+                .unwrap_err()
+                .starts_with("clipboard:")
+        );
+        assert!(resolve(r#"{ "defaults": { "tail_interval_secs": 0 } }"#).is_err());
+        assert!(resolve(r#"{ "defaults": { "max_lines": 10 } }"#).is_err());
+        assert!(
+            resolve(r#"{ "defaults": { "contexts": ["eu-west-1"] } }"#)
+                .unwrap_err()
+                .starts_with("contexts:")
+        );
+        assert!(
+            resolve(r#"{ "defaults": { "endpoint_url": "localhost:4566" } }"#)
+                .unwrap_err()
+                .starts_with("endpoint_url:")
+        );
+        assert_eq!(
+            resolve(r#"{ "defaults": { "endpoint_url": "http://localhost:4566" } }"#)
+                .unwrap()
+                .endpoint_url
+                .as_deref(),
+            Some("http://localhost:4566")
+        );
+    }
+
+    #[test]
+    fn roles_apply_to_every_profile() {
+        let file = parse_config(
+            r#"{
+                "defaults": { "contexts": ["app-prod/eu-west-1"] },
+                "roles": {
+                    "app-prod": {
+                        "source_profile": "hub",
+                        "role_arn": "arn:aws:iam::111111111111:role/log-reader",
+                        "external_id": "lumberjack",
+                        "mfa_serial": "arn:aws:iam::222222222222:mfa/me"
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+        let settings = Settings::resolve(&file, "hub").unwrap();
+        let role = &settings.roles["app-prod"];
+        assert_eq!(role.source_profile, "hub");
+        assert_eq!(role.external_id.as_deref(), Some("lumberjack"));
+        assert_eq!(role.session_name, None);
+        assert_eq!(
+            Settings::resolve(&file, "dev").unwrap().roles,
+            settings.roles
+        );
+
+        let err = Settings::resolve(
+            &parse_config(r#"{ "roles": { "a": { "role_arn": "log-reader" } } }"#).unwrap(),
+            "default",
+        )
+        .unwrap_err();
+        assert!(err.starts_with("roles.a:"), "{err}");
+        assert!(parse_config(r#"{ "roles": { "a": { "arn": "x" } } }"#).is_err());
+    }
+
+    #[test]
+    fn window_accepts_leading_dash() {
+        let file = parse_config(r#"{ "defaults": { "default_window": "-30m" } }"#).unwrap();
+        assert_eq!(
+            Settings::resolve(&file, "default").unwrap().default_start,
+            "-30m"
+        );
+    }
+
+    #[test]
+    fn user_themes_keep_file_order_and_unique_names() {
+        let themes = parse_themes(
+            r##"[
+                { "name": "nord", "styles": { "header": { "bg": "#2e3440" } } },
+                { "name": "paper", "base": "light" }
+            ]"##,
+        )
+        .unwrap();
+        let names: Vec<&str> = themes.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(names, vec!["nord", "paper"]);
+
+        assert!(parse_themes(r#"[{ "name": "dark" }]"#).is_err());
+        assert!(parse_themes(r#"[{ "name": "a" }, { "name": "a" }]"#).is_err());
+    }
+}
@@ -0,0 +1,495 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::Ordering;
+use std::time::Instant;
+
+use ratatui::crossterm::event::KeyCode;
+
+use super::config::{load_config_from, profile_section};
+use super::{App, SavedFilter};
+use crate::aws::profiles::{CLOUDWATCH_REGIONS, list_profiles};
+use crate::aws::roles::{role_names, set_roles};
+use crate::aws::{AwsLogError, GroupTarget, set_config_endpoint_url};
+
+/// A saved filter on its way into the Filter pane, with its placeholder
+/// values already chosen.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FilterLoad {
+    /// Index into `saved_filters`.
+    pub filter: usize,
+    pub values: BTreeMap<String, String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ContextPickerKind {
+    Profile,
+    Region,
+}
+
+/// The `P` / `R` popup: a type-to-filter list of profiles or regions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContextPicker {
+    pub kind: ContextPickerKind,
+    pub items: Vec<String>,
+    pub input: String,
+    pub selected: usize,
+}
+
+/// `*` matches any run of characters (including none); everything else
+/// must match exactly.
+pub fn group_matches(pattern: &str, name: &str) -> bool {
+    let Some((first, rest)) = pattern.split_once('*') else {
+        return pattern == name;
+    };
+    let Some(mut remaining) = name.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = rest.split('*').collect();
+    let last = parts.pop().unwrap_or_default();
+    for part in parts {
+        match remaining.find(part) {
+            Some(pos) => remaining = &remaining[pos + part.len()..],
+            None => return false,
+        }
+    }
+    remaining.len() >= last.len() && remaining.ends_with(last)
+}
+
+/// Split `patterns` into the groups they name in `available` (in list
+/// order, no repeats) and the patterns that matched nothing.
+pub fn resolve_groups(patterns: &[String], available: &[String]) -> (Vec<String>, Vec<String>) {
+    let mut found: Vec<String> = Vec::new();
+    let mut missing = Vec::new();
+    for pattern in patterns {
+        let matches: Vec<&String> = available
+            .iter()
+            .filter(|g| group_matches(pattern, g))
+            .collect();
+        if matches.is_empty() {
+            missing.push(pattern.clone());
+        }
+        for g in matches {
+            if !found.contains(g) {
+                found.push(g.clone());
+            }
+        }
+    }
+    (found, missing)
+}
+
+impl SavedFilter {
+    /// `group` followed by `groups`, skipping blanks.
+    pub fn group_patterns(&self) -> Vec<String> {
+        std::iter::once(&self.group)
+            .chain(&self.groups)
+            .filter(|g| !g.trim().is_empty())
+            .cloned()
+            .collect()
+    }
+}
+
+/// A Groups pane entry when several contexts are listed together, e.g.
+/// `[prod/eu-west-1] /aws/lambda/api`.
+pub fn tag_group(profile: &str, region: &str, group: &str) -> String {
+    format!("[{profile}/{region}] {group}")
+}
+
+/// `(profile, region, group)` from an entry made by `tag_group`.
+pub fn split_context_tag(entry: &str) -> Option<(&str, &str, &str)> {
+    let (context, group) = entry.strip_prefix('[')?.split_once("] ")?;
+    let (profile, region) = context.rsplit_once('/')?;
+    Some((profile, region, group))
+}
+
+/// One list from several contexts' groups, each tagged with its context; a
+/// context that failed shows up as an error row instead.
+pub fn tagged_groups(
+    contexts: &[(String, String)],
+    results: Vec<Result<Vec<String>, AwsLogError>>,
+) -> Vec<String> {
+    let mut out = Vec::new();
+    for ((profile, region), res) in contexts.iter().zip(results) {
+        match res {
+            Ok(groups) => out.extend(groups.iter().map(|g| tag_group(profile, region, g))),
+            Err(e) => out.push(format!(
+                "(error fetching log groups for {profile}/{region}: {e})"
+            )),
+        }
+    }
+    out
+}
+
+/// `profile / region` as shown in prompts; an empty profile is the default
+/// credential chain.
+pub fn context_label(profile: &str, region: &str) -> String {
+    let profile = if profile.is_empty() {
+        "default"
+    } else {
+        profile
+    };
+    format!("{profile} / {region}")
+}
+
+impl App {
+    /// The profile/region `f` was saved in; empty fields mean the current one.
+    pub fn saved_context(&self, f: &SavedFilter) -> (String, String) {
+        let profile = if f.profile.is_empty() {
+            &self.state.profile
+        } else {
+            &f.profile
+        };
+        let region = if f.region.is_empty() {
+            &self.state.region
+        } else {
+            &f.region
+        };
+        (profile.clone(), region.clone())
+    }
+
+    /// The profile/region `f` was saved in, when that isn't the current one
+    /// and its groups aren't in the Groups pane either.
+    pub fn foreign_context(&self, f: &SavedFilter) -> Option<(String, String)> {
+        let (profile, region) = self.saved_context(f);
+        let current = profile == self.state.profile && region == self.state.region;
+        (!current && !self.lists_context(&profile, &region)).then_some((profile, region))
+    }
+
+    /// Whether the Groups pane lists the configured `contexts`, each entry
+    /// tagged with its profile/region.
+    pub fn multi_context(&self) -> bool {
+        !self.state.single_context && !self.state.settings.contexts.is_empty()
+    }
+
+    /// Whether `profile`/`region`'s groups are in the Groups pane.
+    pub fn lists_context(&self, profile: &str, region: &str) -> bool {
+        if self.multi_context() {
+            self.state
+                .settings
+                .contexts
+                .iter()
+                .any(|(p, r)| p == profile && r == region)
+        } else {
+            profile == self.state.profile && region == self.state.region
+        }
+    }
+
+    /// The Groups pane entry for `group` in `profile`/`region`.
+    pub fn pane_entry(&self, profile: &str, region: &str, group: &str) -> String {
+        if self.multi_context() {
+            tag_group(profile, region, group)
+        } else {
+            group.to_string()
+        }
+    }
+
+    /// Plain names of the Groups pane entries from `profile`/`region`.
+    pub fn groups_in_context(&self, profile: &str, region: &str) -> Vec<String> {
+        self.state
+            .all_groups
+            .iter()
+            .map(|entry| self.group_target(entry))
+            .filter(|(p, r, _)| p == profile && r == region)
+            .map(|(_, _, group)| group)
+            .collect()
+    }
+
+    /// Select `group` of `profile`/`region` in the Groups pane.
+    pub fn select_group_in(&mut self, profile: &str, region: &str, group: &str) -> bool {
+        let entry = self.pane_entry(profile, region, group);
+        self.select_group_by_name(&entry)
+    }
+
+    /// Profile, region and plain group name for a Groups pane entry; untagged
+    /// entries belong to the current context.
+    pub fn group_target(&self, entry: &str) -> (String, String, String) {
+        match split_context_tag(entry) {
+            Some((profile, region, group)) => {
+                (profile.to_string(), region.to_string(), group.to_string())
+            }
+            None => (
+                self.state.profile.clone(),
+                self.state.region.clone(),
+                entry.to_string(),
+            ),
+        }
+    }
+
+    /// `entries` grouped by context, keeping first-seen order, so each
+    /// context is searched with its own client.
+    pub fn search_targets(&self, entries: &[String]) -> Vec<GroupTarget> {
+        let mut targets: Vec<GroupTarget> = Vec::new();
+        for entry in entries {
+            let (profile, region, group) = self.group_target(entry);
+            match targets
+                .iter_mut()
+                .find(|t| t.profile == profile && t.region == region)
+            {
+                Some(target) => target.groups.push(group),
+                None => targets.push(GroupTarget {
+                    profile,
+                    region,
+                    groups: vec![group],
+                }),
+            }
+        }
+        targets
+    }
+
+    /// Point the app at another profile/region and fetch its groups; from
+    /// then on the Groups pane lists just that context, even with `contexts`
+    /// configured. The filter fields stay, the selected group is picked
+    /// again if it exists there, and a running tail stops (it polls the old
+    /// context).
+    pub fn switch_context(&mut self, profile: &str, region: &str) {
+        if self.state.pending_group_select.is_none() {
+            self.state.pending_group_select = self
+                .state
+                .groups
+                .get(self.state.selected_group)
+                .filter(|g| !g.starts_with('('))
+                .map(|entry| self.group_target(entry).2);
+        }
+        self.state.single_context = true;
+        if profile != self.state.profile {
+            self.reload_settings(profile);
+        }
+        self.tail_stop.store(true, Ordering::Relaxed);
+        self.state.profile = profile.to_string();
+        self.state.region = region.to_string();
+        self.clear_group_binding();
+        self.reload_groups();
+    }
+
+    /// Settings are per profile; a broken config keeps the current ones.
+    fn reload_settings(&mut self, profile: &str) {
+        let Some(path) = &self.state.config_path else {
+            return;
+        };
+        match load_config_from(path, &profile_section(profile)) {
+            Ok(settings) => {
+                set_roles(settings.roles.clone());
+                set_config_endpoint_url(settings.endpoint_url.clone());
+                self.state.settings = settings;
+            }
+            Err(e) => {
+                self.state.status_message = Some(format!("Config not reloaded: {e}"));
+                self.state.status_set_at = Some(Instant::now());
+            }
+        }
+    }
+
+    /// `P`: pick a profile from the AWS config and credentials files.
+    pub fn open_profile_picker(&mut self) {
+        let mut items = list_profiles();
+        items.extend(role_names());
+        items.sort();
+        items.dedup();
+        if !self.state.profile.is_empty() && !items.contains(&self.state.profile) {
+            items.insert(0, self.state.profile.clone());
+        }
+        if items.is_empty() {
+            self.state.status_message =
+                Some("No profiles in ~/.aws/config or ~/.aws/credentials".to_string());
+            self.state.status_set_at = Some(Instant::now());
+            return;
+        }
+        self.open_context_picker(ContextPickerKind::Profile, items);
+    }
+
+    /// `R`: pick one of the regions CloudWatch Logs runs in.
+    pub fn open_region_picker(&mut self) {
+        let mut items: Vec<String> = CLOUDWATCH_REGIONS.iter().map(|r| r.to_string()).collect();
+        if !items.contains(&self.state.region) {
+            items.insert(0, self.state.region.clone());
+        }
+        self.open_context_picker(ContextPickerKind::Region, items);
+    }
+
+    fn open_context_picker(&mut self, kind: ContextPickerKind, items: Vec<String>) {
+        let current = match kind {
+            ContextPickerKind::Profile => &self.state.profile,
+            ContextPickerKind::Region => &self.state.region,
+        };
+        let selected = items.iter().position(|i| i == current).unwrap_or(0);
+        self.state.context_picker = Some(ContextPicker {
+            kind,
+            items,
+            input: String::new(),
+            selected,
+        });
+    }
+
+    /// Picker rows matching what's been typed.
+    pub fn context_picker_entries(&self) -> Vec<&str> {
+        let Some(picker) = &self.state.context_picker else {
+            return Vec::new();
+        };
+        picker
+            .items
+            .iter()
+            .filter(|i| Self::fuzzy_match(i, &picker.input))
+            .map(String::as_str)
+            .collect()
+    }
+
+    pub fn handle_context_picker_key(&mut self, code: KeyCode) {
+        let count = self.context_picker_entries().len();
+        let choice = self
+            .context_picker_entries()
+            .get(self.state.context_picker.as_ref().map_or(0, |p| p.selected))
+            .map(|s| s.to_string());
+        let Some(picker) = self.state.context_picker.as_mut() else {
+            return;
+        };
+        match code {
+            KeyCode::Esc => self.state.context_picker = None,
+            KeyCode::Up => picker.selected = picker.selected.saturating_sub(1),
+            KeyCode::Down if picker.selected + 1 < count => picker.selected += 1,
+            KeyCode::Backspace => {
+                picker.input.pop();
+                picker.selected = 0;
+            }
+            KeyCode::Char(c) if !c.is_control() => {
+                picker.input.push(c);
+                picker.selected = 0;
+            }
+            KeyCode::Enter => {
+                let kind = picker.kind;
+                self.state.context_picker = None;
+                let Some(choice) = choice else {
+                    return;
+                };
+                let (profile, region) = match kind {
+                    ContextPickerKind::Profile => (choice, self.state.region.clone()),
+                    ContextPickerKind::Region => (self.state.profile.clone(), choice),
+                };
+                if profile == self.state.profile && region == self.state.region {
+                    return;
+                }
+                self.switch_context(&profile, &region);
+                self.state.status_message = Some(format!(
+                    "Switched to {}, loading groups...",
+                    context_label(&profile, &region)
+                ));
+                self.state.status_set_at = Some(Instant::now());
+            }
+            _ => {}
+        }
+    }
+
+    /// Forget the groups/streams a loaded filter bound.
+    pub fn clear_group_binding(&mut self) {
+        self.state.filter_groups.clear();
+        self.state.filter_streams.clear();
+    }
+
+    /// `y` switches and loads once the groups are in, `n` loads here anyway,
+    /// Esc drops the load.
+    pub fn handle_context_switch_key(&mut self, code: KeyCode) {
+        let Some(load) = self.state.context_switch_prompt.take() else {
+            return;
+        };
+        match code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => {
+                let Some((profile, region)) = self
+                    .state
+                    .saved_filters
+                    .get(load.filter)
+                    .and_then(|f| self.foreign_context(f))
+                else {
+                    return;
+                };
+                self.state.pending_filter_load = Some(load);
+                self.switch_context(&profile, &region);
+                self.state.status_message = Some(format!(
+                    "Switched to {}, loading groups...",
+                    context_label(&profile, &region)
+                ));
+                self.state.status_set_at = Some(Instant::now());
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') => self.finish_filter_load(load),
+            KeyCode::Esc => {}
+            _ => self.state.context_switch_prompt = Some(load),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::config::Settings;
+    use crate::app::filters::config_file_path;
+    use crate::app::state::AppState;
+
+    #[test]
+    fn wildcard_group_patterns() {
+        assert!(group_matches("/aws/lambda/orders", "/aws/lambda/orders"));
+        assert!(!group_matches(
+            "/aws/lambda/orders",
+            "/aws/lambda/orders-v2"
+        ));
+        assert!(group_matches(
+            "/aws/lambda/orders-*",
+            "/aws/lambda/orders-v2"
+        ));
+        assert!(group_matches("*orders*", "/aws/lambda/orders-v2"));
+        assert!(group_matches("/aws/*/api-*-prod", "/aws/ecs/api-eu-prod"));
+        assert!(!group_matches(
+            "/aws/*/api-*-prod",
+            "/aws/ecs/api-eu-staging"
+        ));
+        // The tail can't reuse characters the middle already consumed
+        assert!(!group_matches("a*ab", "ab"));
+    }
+
+    #[test]
+    fn context_tags_round_trip() {
+        let entry = tag_group("team/prod", "eu-west-1", "/aws/lambda/api");
+        assert_eq!(entry, "[team/prod/eu-west-1] /aws/lambda/api");
+        assert_eq!(
+            split_context_tag(&entry),
+            Some(("team/prod", "eu-west-1", "/aws/lambda/api"))
+        );
+        assert_eq!(split_context_tag("/aws/lambda/api"), None);
+        assert_eq!(split_context_tag("(loading log groups...)"), None);
+    }
+
+    #[test]
+    fn resolves_groups_and_reports_missing_patterns() {
+        let available: Vec<String> = ["/app/api", "/app/worker", "/db"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let patterns: Vec<String> = ["/app/*", "/app/api", "/cache", "/queue-*"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        let (found, missing) = resolve_groups(&patterns, &available);
+        assert_eq!(found, vec!["/app/api", "/app/worker"]);
+        assert_eq!(missing, vec!["/cache", "/queue-*"]);
+    }
+
+    #[test]
+    fn profile_switch_reloads_that_profiles_settings() {
+        let path = config_file_path("config-switch-test.json").unwrap();
+        std::fs::write(
+            &path,
+            r#"{ "defaults": { "max_lines": 500 }, "profiles": { "prod": { "max_lines": 800 } } }"#,
+        )
+        .unwrap();
+        let mut app = App::for_test(AppState {
+            config_path: Some(path.clone()),
+            ..AppState::for_test()
+        });
+
+        app.switch_context("prod", "eu-west-1");
+        assert_eq!(app.state.settings.max_lines, 800);
+        // Only a profile change re-reads the file
+        std::fs::write(&path, "{}").unwrap();
+        app.switch_context("prod", "us-east-1");
+        assert_eq!(app.state.settings.max_lines, 800);
+        app.switch_context("dev", "us-east-1");
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(app.state.settings, Settings::default());
+    }
+}
@@ -0,0 +1,432 @@
+use std::path::Path;
+use std::time::Instant;
+
+use chrono::{DateTime, Utc};
+use ratatui::crossterm::event::KeyCode;
+use serde_json::{Value, json};
+
+use super::App;
+use super::fields::flatten_json;
+use crate::aws::LogEvent;
+
+pub const DEFAULT_EXPORT_STEM: &str = "lumberjack-export";
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// One JSON object per event (timestamp, stream, id, message, parsed JSON).
+    Ndjson,
+    /// Timestamp plus the chosen JSON fields as columns.
+    Csv,
+    /// Exactly what the Results pane shows.
+    Text,
+    /// The original messages, one per line, without timestamps.
+    Raw,
+}
+
+impl ExportFormat {
+    pub fn next(self) -> Self {
+        match self {
+            ExportFormat::Ndjson => ExportFormat::Csv,
+            ExportFormat::Csv => ExportFormat::Text,
+            ExportFormat::Text => ExportFormat::Raw,
+            ExportFormat::Raw => ExportFormat::Ndjson,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ExportFormat::Ndjson => "ndjson",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Text => "text",
+            ExportFormat::Raw => "raw",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Ndjson => "ndjson",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Text | ExportFormat::Raw => "txt",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label {
+            "ndjson" | "json" => Some(ExportFormat::Ndjson),
+            "csv" => Some(ExportFormat::Csv),
+            "text" => Some(ExportFormat::Text),
+            "raw" => Some(ExportFormat::Raw),
+            _ => None,
+        }
+    }
+}
+
+fn rfc3339(ms: i64) -> String {
+    DateTime::<Utc>::from_timestamp_millis(ms)
+        .map(|dt| dt.to_rfc3339())
+        .unwrap_or_else(|| ms.to_string())
+}
+
+/// Structured form of an event used for NDJSON output.
+pub fn event_to_json(ev: &LogEvent) -> Value {
+    json!({
+        "timestamp": ev.timestamp_ms,
+        "time": rfc3339(ev.timestamp_ms),
+        "log_stream": ev.log_stream_name,
+        "event_id": ev.event_id,
+        "message": ev.message,
+        "json": ev.json_payload(),
+    })
+}
+
+pub fn render_ndjson(events: &[&LogEvent]) -> String {
+    events
+        .iter()
+        .map(|ev| event_to_json(ev).to_string() + "\n")
+        .collect()
+}
+
+pub fn render_raw(events: &[&LogEvent]) -> String {
+    events
+        .iter()
+        .map(|ev| ev.message.trim_end().to_string() + "\n")
+        .collect()
+}
+
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// CSV with a `timestamp` column followed by one column per JSON field path.
+/// Without any columns the raw message is exported instead.
+pub fn render_csv(events: &[&LogEvent], columns: &[String]) -> String {
+    let mut header = vec!["timestamp".to_string()];
+    if columns.is_empty() {
+        header.push("message".to_string());
+    } else {
+        header.extend(columns.iter().cloned());
+    }
+
+    let mut out = header
+        .iter()
+        .map(|h| csv_escape(h))
+        .collect::<Vec<_>>()
+        .join(",");
+    out.push('\n');
+
+    for ev in events {
+        let mut row = vec![rfc3339(ev.timestamp_ms)];
+        if columns.is_empty() {
+            row.push(ev.message.trim_end().to_string());
+        } else {
+            let flat = ev
+                .json_payload()
+                .map(|json| flatten_json(&json))
+                .unwrap_or_default();
+            for col in columns {
+                let cell = match flat.iter().find(|(name, _)| name == col) {
+                    Some((_, Value::String(s))) => s.clone(),
+                    Some((_, other)) => other.to_string(),
+                    None => String::new(),
+                };
+                row.push(cell);
+            }
+        }
+        out.push_str(
+            &row.iter()
+                .map(|c| csv_escape(c))
+                .collect::<Vec<_>>()
+                .join(","),
+        );
+        out.push('\n');
+    }
+    out
+}
+
+/// Parse a comma/space separated list of field paths.
+pub fn parse_columns(input: &str) -> Vec<String> {
+    input
+        .split([',', ' '])
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+impl App {
+    /// Entry indices covered by the Results selection (anchor..=cursor), or
+    /// None when nothing is selected.
+    pub fn selected_entry_range(&self) -> Option<(usize, usize)> {
+        let anchor = self.state.selection_anchor?;
+        let cursor = self.current_entry_index().unwrap_or(anchor);
+        Some((anchor.min(cursor), anchor.max(cursor)))
+    }
+
+    pub fn toggle_selection(&mut self) {
+        self.state.selection_anchor = match self.state.selection_anchor {
+            Some(_) => None,
+            None => self.current_entry_index(),
+        };
+    }
+
+    /// Lines and events in scope for export/copy: the selection when there is
+    /// one, otherwise everything in Results.
+    pub fn export_scope(&self) -> (Vec<&String>, Vec<&LogEvent>) {
+        let (from, to) = self
+            .selected_entry_range()
+            .unwrap_or((0, self.state.lines.len().saturating_sub(1)));
+
+        let lines = self
+            .state
+            .lines
+            .iter()
+            .skip(from)
+            .take(to + 1 - from)
+            .collect();
+        let events = self
+            .state
+            .line_events
+            .iter()
+            .skip(from)
+            .take(to + 1 - from)
+            .flatten()
+            .collect();
+        (lines, events)
+    }
+
+    /// Render the in-scope results in `format`; returns the text and the
+    /// number of records written.
+    pub fn export_content(&self, format: ExportFormat, columns: &[String]) -> (String, usize) {
+        let (lines, events) = self.export_scope();
+        match format {
+            ExportFormat::Ndjson => (render_ndjson(&events), events.len()),
+            ExportFormat::Csv => (render_csv(&events, columns), events.len()),
+            ExportFormat::Raw => (render_raw(&events), events.len()),
+            ExportFormat::Text => {
+                let mut text = lines
+                    .iter()
+                    .map(|l| l.as_str())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                text.push('\n');
+                (text, lines.len())
+            }
+        }
+    }
+
+    pub fn open_export_popup(&mut self) {
+        if self.state.lines.is_empty() {
+            self.state.status_message = Some("Nothing to export".to_string());
+            self.state.status_set_at = Some(Instant::now());
+            return;
+        }
+        if self.state.export_path.is_empty() {
+            self.state.export_path = format!(
+                "{DEFAULT_EXPORT_STEM}.{}",
+                self.state.export_format.extension()
+            );
+        }
+        self.state.export_editing_columns = false;
+        self.state.export_popup_open = true;
+    }
+
+    pub fn handle_export_popup_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Esc => {
+                self.state.export_popup_open = false;
+            }
+            KeyCode::Tab => {
+                let old_default = format!(
+                    "{DEFAULT_EXPORT_STEM}.{}",
+                    self.state.export_format.extension()
+                );
+                self.state.export_format = self.state.export_format.next();
+                // Keep the default file name's extension in step with the format
+                if self.state.export_path == old_default {
+                    self.state.export_path = format!(
+                        "{DEFAULT_EXPORT_STEM}.{}",
+                        self.state.export_format.extension()
+                    );
+                }
+            }
+            KeyCode::Up | KeyCode::Down => {
+                self.state.export_editing_columns = !self.state.export_editing_columns
+                    && self.state.export_format == ExportFormat::Csv;
+            }
+            KeyCode::Enter => {
+                self.export_to_file();
+                self.state.export_popup_open = false;
+            }
+            KeyCode::Backspace => {
+                if self.state.export_editing_columns {
+                    self.state.export_columns.pop();
+                } else {
+                    self.state.export_path.pop();
+                }
+            }
+            KeyCode::Char(c) if !c.is_control() => {
+                if self.state.export_editing_columns {
+                    self.state.export_columns.push(c);
+                } else {
+                    self.state.export_path.push(c);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn export_to_file(&mut self) {
+        let path = self.state.export_path.trim().to_string();
+        if path.is_empty() {
+            self.state.status_message = Some("Export path is empty".to_string());
+            self.state.status_set_at = Some(Instant::now());
+            return;
+        }
+
+        let format = self.state.export_format;
+        let columns = parse_columns(&self.state.export_columns);
+        let (content, count) = self.export_content(format, &columns);
+
+        self.state.status_message = Some(match std::fs::write(Path::new(&path), content) {
+            Ok(()) => format!("Exported {count} {} records to {path}", format.label()),
+            Err(e) => format!("Error exporting to {path}: {e}"),
+        });
+        self.state.status_set_at = Some(Instant::now());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::Focus;
+    use crate::app::state::AppState;
+
+    fn event(ts: i64, message: &str) -> LogEvent {
+        LogEvent {
+            timestamp_ms: ts,
+            message: message.to_string(),
+            log_stream_name: "2025/01/01/[$LATEST]abc".to_string(),
+            event_id: format!("id-{ts}"),
+        }
+    }
+
+    fn app_with_events(events: Vec<LogEvent>) -> App {
+        let mut lines = vec!["--- results ---".to_string()];
+        lines.extend(events.iter().map(|e| e.formatted()));
+        let mut line_events = vec![None];
+        line_events.extend(events.into_iter().map(Some));
+
+        let state = AppState {
+            lines,
+            focus: Focus::Results,
+            line_events,
+            ..AppState::for_test()
+        };
+
+        App::for_test(state)
+    }
+
+    #[test]
+    fn ndjson_has_one_structured_object_per_event() {
+        let a = event(0, r#"INFO {"level":"error"}"#);
+        let b = event(1_000, "plain");
+        let out = render_ndjson(&[&a, &b]);
+
+        let rows: Vec<Value> = out
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["event_id"], "id-0");
+        assert_eq!(rows[0]["json"]["level"], "error");
+        assert_eq!(rows[1]["message"], "plain");
+        assert!(rows[1]["json"].is_null());
+    }
+
+    #[test]
+    fn csv_uses_requested_fields_and_escapes() {
+        let a = event(0, r#"{"level":"error","msg":"a, \"b\"","n":3}"#);
+        let b = event(0, r#"{"level":"info"}"#);
+        let cols = parse_columns("level, msg n");
+        let out = render_csv(&[&a, &b], &cols);
+
+        let mut lines = out.lines();
+        assert_eq!(lines.next(), Some("timestamp,level,msg,n"));
+        assert_eq!(
+            lines.next(),
+            Some(r#"1970-01-01T00:00:00+00:00,error,"a, ""b""",3"#)
+        );
+        assert_eq!(lines.next(), Some("1970-01-01T00:00:00+00:00,info,,"));
+    }
+
+    #[test]
+    fn csv_without_columns_exports_message() {
+        let a = event(0, "hello");
+        let out = render_csv(&[&a], &[]);
+        assert_eq!(out, "timestamp,message\n1970-01-01T00:00:00+00:00,hello\n");
+    }
+
+    #[test]
+    fn raw_export_has_original_messages_only() {
+        let a = event(0, "INFO {\"a\":1}\n");
+        assert_eq!(render_raw(&[&a]), "INFO {\"a\":1}\n");
+    }
+
+    #[test]
+    fn export_scope_uses_selection_when_present() {
+        let mut app = app_with_events(vec![event(0, "one"), event(1, "two"), event(2, "three")]);
+
+        // No selection: everything, header line included for text
+        let (text, count) = app.export_content(ExportFormat::Text, &[]);
+        assert_eq!(count, 4);
+        assert!(text.contains("--- results ---"));
+
+        // Select from entry 2 ("two") down to entry 3 ("three")
+        app.state.results_scroll = 2;
+        app.toggle_selection();
+        app.state.results_scroll = 3;
+        assert_eq!(app.selected_entry_range(), Some((2, 3)));
+
+        let (raw, count) = app.export_content(ExportFormat::Raw, &[]);
+        assert_eq!(count, 2);
+        assert_eq!(raw, "two\nthree\n");
+
+        app.toggle_selection();
+        assert_eq!(app.selected_entry_range(), None);
+    }
+
+    #[test]
+    fn export_to_file_writes_and_reports_path() {
+        let mut app = app_with_events(vec![event(0, "one")]);
+        let path =
+            std::env::temp_dir().join(format!("lumberjack-export-{}.txt", std::process::id()));
+        app.state.export_path = path.display().to_string();
+        app.state.export_format = ExportFormat::Raw;
+
+        app.export_to_file();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "one\n");
+        let status = app.state.status_message.clone().unwrap();
+        assert!(
+            status.contains("Exported 1 raw records"),
+            "status was: {status}"
+        );
+        assert!(status.contains(&path.display().to_string()));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn tab_cycles_format_and_keeps_default_extension_in_step() {
+        let mut app = app_with_events(vec![event(0, "one")]);
+        app.open_export_popup();
+        assert_eq!(app.state.export_path, "lumberjack-export.ndjson");
+
+        app.handle_export_popup_key(KeyCode::Tab);
+        assert_eq!(app.state.export_format, ExportFormat::Csv);
+        assert_eq!(app.state.export_path, "lumberjack-export.csv");
+    }
+}
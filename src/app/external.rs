@@ -0,0 +1,502 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use ratatui::DefaultTerminal;
+use ratatui::crossterm::event::KeyCode;
+use serde::{Deserialize, Serialize};
+
+use super::App;
+use super::export::{ExportFormat, render_csv, render_ndjson};
+use super::filters::config_file_path;
+
+const DEFAULT_PAGER: &str = "less -R";
+const DEFAULT_EDITOR: &str = "vi";
+
+/// A named shell command results can be piped into, from
+/// `~/.config/lumberjack/pipes.json`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PipeCommand {
+    pub name: String,
+    pub command: String,
+    /// What to feed the command: "text" (default), "raw", "ndjson" or "csv".
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+impl PipeCommand {
+    pub fn input_format(&self) -> ExportFormat {
+        self.format
+            .as_deref()
+            .and_then(ExportFormat::from_label)
+            .unwrap_or(ExportFormat::Text)
+    }
+}
+
+/// What a pipe menu entry is, which decides how it is run.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PipeTarget {
+    /// `$PAGER`, fed on stdin.
+    Pager,
+    /// `$EDITOR`, given a temp file.
+    Editor,
+    /// A command from `pipes.json`, fed on stdin; its output stays on screen
+    /// until Enter.
+    Configured,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PipeMenuEntry {
+    pub target: PipeTarget,
+    pub command: PipeCommand,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ExternalInput {
+    /// Written to the command's stdin.
+    Stdin,
+    /// Written to a temp file whose path is passed as the last argument.
+    File,
+    /// Nothing is fed in; the command gets the terminal (e.g. a login that
+    /// asks questions).
+    Terminal,
+}
+
+/// A command to run with the TUI suspended; queued by key handlers and run
+/// from the main loop, which owns the terminal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExternalCommand {
+    pub command: String,
+    pub content: String,
+    pub input: ExternalInput,
+    /// Extension for the temp file (editors pick syntax highlighting from it).
+    pub extension: &'static str,
+    /// Keep the output on screen until Enter is pressed (for non-interactive
+    /// commands like `jq .`).
+    pub wait_for_enter: bool,
+}
+
+impl ExternalCommand {
+    pub fn run(&self) -> io::Result<ExitStatus> {
+        match self.input {
+            ExternalInput::Stdin => {
+                let mut child = Command::new("sh")
+                    .arg("-c")
+                    .arg(&self.command)
+                    .stdin(Stdio::piped())
+                    .spawn()?;
+
+                // Feed stdin from a separate thread: pagers only read as the
+                // user scrolls, and may exit before consuming everything.
+                let mut stdin = child.stdin.take().expect("stdin is piped");
+                let content = self.content.clone();
+                let writer = std::thread::spawn(move || {
+                    let _ = stdin.write_all(content.as_bytes());
+                });
+
+                let status = child.wait()?;
+                let _ = writer.join();
+                Ok(status)
+            }
+            ExternalInput::Terminal => Command::new("sh").arg("-c").arg(&self.command).status(),
+            ExternalInput::File => {
+                let (path, mut file) = create_temp_file(self.extension)?;
+                let written = file.write_all(self.content.as_bytes());
+                drop(file);
+                if let Err(e) = written {
+                    let _ = std::fs::remove_file(&path);
+                    return Err(e);
+                }
+                // Pass the path as "$1" so it never needs shell quoting.
+                let status = Command::new("sh")
+                    .arg("-c")
+                    .arg(format!("{} \"$1\"", self.command))
+                    .arg("lumberjack")
+                    .arg(&path)
+                    .status();
+                let _ = std::fs::remove_file(&path);
+                status
+            }
+        }
+    }
+}
+
+/// A new, private (0600) temp file for log data. The name is unique per
+/// call and `create_new` refuses to follow a planted file or symlink, so a
+/// clash just moves on to the next name.
+fn create_temp_file(extension: &str) -> io::Result<(PathBuf, File)> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.subsec_nanos());
+    loop {
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let path = std::env::temp_dir().join(format!(
+            "lumberjack-{}-{nanos:08x}-{n}.{extension}",
+            std::process::id()
+        ));
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        match options.open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// `$PAGER`/`$EDITOR`-style lookup: the variable when set and non-empty,
+/// otherwise `default`.
+pub fn command_from_env(var: Option<String>, default: &str) -> String {
+    var.filter(|v| !v.trim().is_empty())
+        .unwrap_or_else(|| default.to_string())
+}
+
+fn pager_command() -> String {
+    command_from_env(std::env::var("PAGER").ok(), DEFAULT_PAGER)
+}
+
+fn editor_command() -> String {
+    let visual = std::env::var("VISUAL")
+        .ok()
+        .filter(|v| !v.trim().is_empty());
+    command_from_env(visual.or(std::env::var("EDITOR").ok()), DEFAULT_EDITOR)
+}
+
+fn pipes_path() -> Result<PathBuf, String> {
+    config_file_path("pipes.json")
+}
+
+pub fn load_pipe_commands() -> Result<Vec<PipeCommand>, String> {
+    let path = pipes_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let data = std::fs::read_to_string(&path)
+        .map_err(|e| format!("read_to_string {}: {e}", path.display()))?;
+    serde_json::from_str(&data).map_err(|e| format!("decode {}: {e}", path.display()))
+}
+
+impl App {
+    /// Text to hand to an external command: the current event when
+    /// `event_only`, otherwise the results (or selection).
+    pub fn external_content(&self, format: ExportFormat, event_only: bool) -> Option<String> {
+        if !event_only {
+            let (content, count) = self.export_content(format, &[]);
+            return (count > 0).then_some(content);
+        }
+
+        let ev = self.current_event()?;
+        Some(match format {
+            ExportFormat::Text => ev.formatted() + "\n",
+            ExportFormat::Raw => ev.message.trim_end().to_string() + "\n",
+            ExportFormat::Ndjson => render_ndjson(&[ev]),
+            ExportFormat::Csv => render_csv(&[ev], &[]),
+        })
+    }
+
+    fn queue_external(&mut self, cmd: Option<ExternalCommand>) {
+        match cmd {
+            Some(cmd) => self.state.pending_external = Some(cmd),
+            None => {
+                self.state.status_message = Some("Nothing to open".to_string());
+                self.state.status_set_at = Some(Instant::now());
+            }
+        }
+    }
+
+    /// Page the results (or selection) through `$PAGER`.
+    pub fn open_in_pager(&mut self) {
+        let cmd = self
+            .external_content(ExportFormat::Text, false)
+            .map(|content| ExternalCommand {
+                command: pager_command(),
+                content,
+                input: ExternalInput::Stdin,
+                extension: "txt",
+                wait_for_enter: false,
+            });
+        self.queue_external(cmd);
+    }
+
+    /// Open the event under the cursor in `$EDITOR`: its JSON payload
+    /// pretty-printed when it has one, otherwise the raw message.
+    pub fn open_event_in_editor(&mut self) {
+        let cmd = self.current_event().map(|ev| {
+            let (content, extension) = match ev.json_payload() {
+                Some(json) => (
+                    serde_json::to_string_pretty(&json).unwrap_or_default() + "\n",
+                    "json",
+                ),
+                None => (ev.message.trim_end().to_string() + "\n", "txt"),
+            };
+            ExternalCommand {
+                command: editor_command(),
+                content,
+                input: ExternalInput::File,
+                extension,
+                wait_for_enter: false,
+            }
+        });
+        self.queue_external(cmd);
+    }
+
+    pub fn open_pipe_menu(&mut self) {
+        match load_pipe_commands() {
+            Ok(commands) => self.state.pipe_commands = commands,
+            Err(e) => {
+                self.state.status_message = Some(format!("Error loading pipe commands: {e}"));
+                self.state.status_set_at = Some(Instant::now());
+                return;
+            }
+        }
+        self.state.pipe_menu_selected = 0;
+        self.state.pipe_menu_open = true;
+    }
+
+    /// Entries of the pipe menu: `$PAGER` and `$EDITOR` first, then the
+    /// configured commands.
+    pub fn pipe_menu_entries(&self) -> Vec<PipeMenuEntry> {
+        let builtin = |target, name: &str, command| PipeMenuEntry {
+            target,
+            command: PipeCommand {
+                name: name.to_string(),
+                command,
+                format: None,
+            },
+        };
+        let mut entries = vec![
+            builtin(PipeTarget::Pager, "Pager", pager_command()),
+            builtin(PipeTarget::Editor, "Editor", editor_command()),
+        ];
+        entries.extend(self.state.pipe_commands.iter().map(|c| PipeMenuEntry {
+            target: PipeTarget::Configured,
+            command: c.clone(),
+        }));
+        entries
+    }
+
+    pub fn handle_pipe_menu_key(&mut self, code: KeyCode) {
+        let entries = self.pipe_menu_entries();
+        match code {
+            KeyCode::Esc => {
+                self.state.pipe_menu_open = false;
+            }
+            KeyCode::Tab => {
+                self.state.pipe_menu_event = !self.state.pipe_menu_event;
+            }
+            KeyCode::Up if self.state.pipe_menu_selected > 0 => {
+                self.state.pipe_menu_selected -= 1;
+            }
+            KeyCode::Down if self.state.pipe_menu_selected + 1 < entries.len() => {
+                self.state.pipe_menu_selected += 1;
+            }
+            KeyCode::Enter => {
+                self.state.pipe_menu_open = false;
+                let Some(entry) = entries.get(self.state.pipe_menu_selected) else {
+                    return;
+                };
+                let format = entry.command.input_format();
+                let cmd = self
+                    .external_content(format, self.state.pipe_menu_event)
+                    .map(|content| ExternalCommand {
+                        command: entry.command.command.clone(),
+                        content,
+                        input: match entry.target {
+                            PipeTarget::Editor => ExternalInput::File,
+                            PipeTarget::Pager | PipeTarget::Configured => ExternalInput::Stdin,
+                        },
+                        extension: format.extension(),
+                        wait_for_enter: entry.target == PipeTarget::Configured,
+                    });
+                self.queue_external(cmd);
+            }
+            _ => {}
+        }
+    }
+
+    /// Suspend the TUI, run `cmd` on the real terminal, then restore the TUI.
+    /// App state is untouched, so everything is as it was on return.
+    pub fn run_external(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        cmd: ExternalCommand,
+    ) -> io::Result<()> {
+        ratatui::restore();
+
+        let result = cmd.run();
+        if cmd.wait_for_enter {
+            print!("\n[lumberjack] Press Enter to return ");
+            let _ = io::stdout().flush();
+            let _ = io::stdin().lock().read_line(&mut String::new());
+        }
+
+        *terminal = ratatui::init();
+        terminal.clear()?;
+
+        let success = result.as_ref().is_ok_and(|status| status.success());
+        self.state.status_message = Some(match result {
+            Ok(status) if status.success() => format!("Returned from `{}`", cmd.command),
+            Ok(status) => format!("`{}` exited with {status}", cmd.command),
+            Err(e) => format!("Error running `{}`: {e}", cmd.command),
+        });
+        self.state.status_set_at = Some(Instant::now());
+        self.finish_login(success);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::Focus;
+    use crate::app::state::AppState;
+    use crate::aws::LogEvent;
+
+    fn event(ts: i64, message: &str) -> LogEvent {
+        LogEvent {
+            timestamp_ms: ts,
+            message: message.to_string(),
+            log_stream_name: "2025/01/01/[$LATEST]abc".to_string(),
+            event_id: format!("id-{ts}"),
+        }
+    }
+
+    fn app_with_events(events: Vec<LogEvent>) -> App {
+        let mut lines = vec!["--- results ---".to_string()];
+        lines.extend(events.iter().map(|e| e.formatted()));
+        let mut line_events = vec![None];
+        line_events.extend(events.into_iter().map(Some));
+
+        let state = AppState {
+            lines,
+            focus: Focus::Results,
+            line_events,
+            ..AppState::for_test()
+        };
+
+        App::for_test(state)
+    }
+
+    /// Somewhere for a test command to leave its output.
+    fn scratch_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("lumberjack-test-{}-{name}", std::process::id()))
+    }
+
+    #[test]
+    fn env_command_falls_back_to_default() {
+        assert_eq!(command_from_env(None, "less -R"), "less -R");
+        assert_eq!(command_from_env(Some("  ".to_string()), "vi"), "vi");
+        assert_eq!(command_from_env(Some("nvim".to_string()), "vi"), "nvim");
+    }
+
+    #[test]
+    fn pipe_commands_parse_with_optional_format() {
+        let commands: Vec<PipeCommand> = serde_json::from_str(
+            r#"[
+                {"name": "jq", "command": "jq . | less", "format": "raw"},
+                {"name": "count", "command": "wc -l"}
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(commands[0].input_format(), ExportFormat::Raw);
+        assert_eq!(commands[1].input_format(), ExportFormat::Text);
+    }
+
+    #[test]
+    fn stdin_command_receives_content() {
+        let out = scratch_path("stdin-test");
+        let cmd = ExternalCommand {
+            command: format!("cat > '{}'", out.display()),
+            content: "hello\nworld\n".to_string(),
+            input: ExternalInput::Stdin,
+            extension: "txt",
+            wait_for_enter: false,
+        };
+
+        assert!(cmd.run().unwrap().success());
+        assert_eq!(std::fs::read_to_string(&out).unwrap(), "hello\nworld\n");
+        let _ = std::fs::remove_file(&out);
+    }
+
+    #[test]
+    fn file_command_gets_private_temp_file_and_cleans_up() {
+        let out = scratch_path("file-test");
+        let seen = scratch_path("file-test-seen");
+        let cmd = ExternalCommand {
+            command: format!(
+                "cp -f -- \"$1\" '{}'; {{ echo \"$1\"; ls -l \"$1\"; }} > '{}'; true",
+                out.display(),
+                seen.display()
+            ),
+            content: "{\"a\":1}\n".to_string(),
+            input: ExternalInput::File,
+            extension: "json",
+            wait_for_enter: false,
+        };
+
+        assert!(cmd.run().unwrap().success());
+        assert_eq!(std::fs::read_to_string(&out).unwrap(), "{\"a\":1}\n");
+        let seen_text = std::fs::read_to_string(&seen).unwrap();
+        let mut seen_lines = seen_text.lines();
+        let path = PathBuf::from(seen_lines.next().unwrap());
+        assert!(path.extension().is_some_and(|e| e == "json"));
+        #[cfg(unix)]
+        assert!(seen_lines.next().unwrap().starts_with("-rw-------"));
+        assert!(!path.exists());
+
+        // Every run gets a new name
+        let (first, _) = create_temp_file("txt").unwrap();
+        let (second, _) = create_temp_file("txt").unwrap();
+        assert_ne!(first, second);
+        let _ = std::fs::remove_file(&first);
+        let _ = std::fs::remove_file(&second);
+        let _ = std::fs::remove_file(&out);
+        let _ = std::fs::remove_file(&seen);
+    }
+
+    #[test]
+    fn pipe_menu_queues_configured_command_with_its_format() {
+        let mut app = app_with_events(vec![event(0, r#"INFO {"a":1}"#), event(1, "two")]);
+        app.state.pipe_commands = vec![PipeCommand {
+            name: "jq".to_string(),
+            command: "jq .".to_string(),
+            format: Some("ndjson".to_string()),
+        }];
+        app.state.pipe_menu_open = true;
+        app.state.pipe_menu_event = true;
+        app.state.results_scroll = 1;
+
+        // Pager, Editor, then the configured command
+        app.handle_pipe_menu_key(KeyCode::Down);
+        app.handle_pipe_menu_key(KeyCode::Down);
+        app.handle_pipe_menu_key(KeyCode::Enter);
+
+        assert!(!app.state.pipe_menu_open);
+        let cmd = app.state.pending_external.clone().unwrap();
+        assert_eq!(cmd.command, "jq .");
+        assert_eq!(cmd.input, ExternalInput::Stdin);
+        assert!(cmd.wait_for_enter);
+        // Only the event under the cursor, as one NDJSON line
+        assert_eq!(cmd.content.lines().count(), 1);
+        assert!(cmd.content.contains("\"event_id\":\"id-0\""));
+    }
+
+    #[test]
+    fn editor_gets_pretty_json_payload_of_current_event() {
+        let mut app = app_with_events(vec![event(0, r#"INFO {"a":1}"#)]);
+        app.state.results_scroll = 1;
+        app.open_event_in_editor();
+
+        let cmd = app.state.pending_external.clone().unwrap();
+        assert_eq!(cmd.input, ExternalInput::File);
+        assert_eq!(cmd.extension, "json");
+        assert_eq!(cmd.content, "{\n  \"a\": 1\n}\n");
+    }
+}
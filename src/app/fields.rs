@@ -0,0 +1,462 @@
+use std::collections::HashMap;
+use std::time::Instant;
+
+use ratatui::crossterm::event::KeyCode;
+use serde_json::Value;
+
+use super::App;
+use crate::aws::LogEvent;
+
+/// How many distinct values to keep per field in the discovery panel.
+const TOP_VALUES: usize = 5;
+
+/// A JSON field seen in the fetched events, with its most common values.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldStats {
+    /// Dot-separated path, e.g. `request.method`.
+    pub name: String,
+    /// Number of events containing the field.
+    pub count: usize,
+    /// Values rendered in shorthand form (`"error"`, `500`, `true`) with counts.
+    pub top_values: Vec<(String, usize)>,
+}
+
+/// Flatten nested JSON into `(path, scalar)` pairs using the paths CloudWatch
+/// filter patterns understand: `request.method`, `tags[0]`.
+pub fn flatten_json(value: &Value) -> Vec<(String, Value)> {
+    fn walk(prefix: &str, value: &Value, out: &mut Vec<(String, Value)>) {
+        match value {
+            Value::Object(map) => {
+                for (k, v) in map {
+                    let path = if prefix.is_empty() {
+                        k.clone()
+                    } else {
+                        format!("{prefix}.{k}")
+                    };
+                    walk(&path, v, out);
+                }
+            }
+            Value::Array(items) => {
+                for (i, v) in items.iter().enumerate() {
+                    walk(&format!("{prefix}[{i}]"), v, out);
+                }
+            }
+            leaf if !prefix.is_empty() => out.push((prefix.to_string(), leaf.clone())),
+            _ => {}
+        }
+    }
+
+    let mut out = Vec::new();
+    walk("", value, &mut out);
+    out
+}
+
+/// Render a JSON value the way the Query shorthand expects it: strings are
+/// double-quoted (with escapes), everything else is written as-is.
+pub fn shorthand_value(value: &Value) -> String {
+    // serde_json's encoding of a string is already a valid quoted literal,
+    // and numbers/bools/null print bare.
+    value.to_string()
+}
+
+/// `field=value` or `field!=value`, ready to drop into the Query field.
+pub fn shorthand_term(field: &str, value: &str, negate: bool) -> String {
+    let op = if negate { "!=" } else { "=" };
+    format!("{field}{op}{value}")
+}
+
+/// Collect field names, occurrence counts and top values across events.
+/// Fields are ordered by how many events contain them, then by name.
+pub fn discover_fields<'a>(events: impl Iterator<Item = &'a LogEvent>) -> Vec<FieldStats> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    let mut values: HashMap<String, HashMap<String, usize>> = HashMap::new();
+
+    for ev in events {
+        let Some(json) = ev.json_payload() else {
+            continue;
+        };
+        for (name, leaf) in flatten_json(&json) {
+            *values
+                .entry(name.clone())
+                .or_default()
+                .entry(shorthand_value(&leaf))
+                .or_default() += 1;
+            *counts.entry(name).or_default() += 1;
+        }
+    }
+
+    let mut fields: Vec<FieldStats> = counts
+        .into_iter()
+        .map(|(name, count)| {
+            let mut top: Vec<(String, usize)> = values
+                .remove(&name)
+                .unwrap_or_default()
+                .into_iter()
+                .collect();
+            top.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            top.truncate(TOP_VALUES);
+            FieldStats {
+                name,
+                count,
+                top_values: top,
+            }
+        })
+        .collect();
+
+    fields.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    fields
+}
+
+impl App {
+    pub fn open_field_panel(&mut self) {
+        let fields = discover_fields(self.state.line_events.iter().flatten());
+        if fields.is_empty() {
+            self.state.status_message = Some("No JSON fields in results".to_string());
+            self.state.status_set_at = Some(Instant::now());
+            return;
+        }
+
+        self.state.field_panel_fields = fields;
+        self.state.field_panel_selected = 0;
+        self.state.field_panel_value_selected = None;
+        self.state.field_panel_open = true;
+    }
+
+    /// Append a shorthand term to the Query field, space-separated.
+    pub fn append_query_term(&mut self, term: &str) {
+        let query = self.state.filter_query.trim_end().to_string();
+        self.state.filter_query = if query.is_empty() {
+            term.to_string()
+        } else {
+            format!("{query} {term}")
+        };
+        self.state.filter_cursor_pos = self.state.filter_query.len();
+    }
+
+    pub fn handle_field_panel_key(&mut self, code: KeyCode) {
+        let field_count = self.state.field_panel_fields.len();
+        if field_count == 0 {
+            self.state.field_panel_open = false;
+            return;
+        }
+
+        let value_count = self
+            .state
+            .field_panel_fields
+            .get(self.state.field_panel_selected)
+            .map(|f| f.top_values.len())
+            .unwrap_or(0);
+
+        match code {
+            KeyCode::Esc | KeyCode::Char('f') => {
+                self.state.field_panel_open = false;
+            }
+            KeyCode::Char('a') => {
+                self.state.field_panel_auto_run = !self.state.field_panel_auto_run;
+            }
+            KeyCode::Up => match self.state.field_panel_value_selected {
+                Some(v) => self.state.field_panel_value_selected = Some(v.saturating_sub(1)),
+                None => {
+                    self.state.field_panel_selected =
+                        self.state.field_panel_selected.saturating_sub(1)
+                }
+            },
+            KeyCode::Down => match self.state.field_panel_value_selected {
+                Some(v) => {
+                    self.state.field_panel_value_selected =
+                        Some((v + 1).min(value_count.saturating_sub(1)))
+                }
+                None => {
+                    self.state.field_panel_selected =
+                        (self.state.field_panel_selected + 1).min(field_count - 1)
+                }
+            },
+            KeyCode::Right if value_count > 0 => {
+                self.state.field_panel_value_selected.get_or_insert(0);
+            }
+            KeyCode::Left => {
+                self.state.field_panel_value_selected = None;
+            }
+            KeyCode::Enter if self.state.field_panel_value_selected.is_none() => {
+                self.state.field_panel_value_selected = (value_count > 0).then_some(0);
+            }
+            KeyCode::Enter => self.apply_field_panel_value(false),
+            KeyCode::Char('!') if self.state.field_panel_value_selected.is_some() => {
+                self.apply_field_panel_value(true)
+            }
+            _ => {}
+        }
+    }
+
+    pub fn open_value_picker(&mut self) {
+        let fields: Vec<(String, String)> = self
+            .current_event()
+            .and_then(|ev| ev.json_payload())
+            .map(|json| {
+                flatten_json(&json)
+                    .into_iter()
+                    .map(|(name, leaf)| (name, shorthand_value(&leaf)))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        if fields.is_empty() {
+            self.state.status_message = Some("No JSON fields in this event".to_string());
+            self.state.status_set_at = Some(Instant::now());
+            return;
+        }
+
+        self.state.value_picker_fields = fields;
+        self.state.value_picker_selected = 0;
+        self.state.value_picker_open = true;
+    }
+
+    pub fn handle_value_picker_key(&mut self, code: KeyCode) {
+        if self.state.value_picker_fields.is_empty() {
+            self.state.value_picker_open = false;
+            return;
+        }
+
+        match code {
+            KeyCode::Esc => {
+                self.state.value_picker_open = false;
+            }
+            KeyCode::Up => {
+                self.state.value_picker_selected =
+                    self.state.value_picker_selected.saturating_sub(1);
+            }
+            KeyCode::Down => {
+                self.state.value_picker_selected = (self.state.value_picker_selected + 1)
+                    .min(self.state.value_picker_fields.len() - 1);
+            }
+            KeyCode::Enter | KeyCode::Char('=') => self.apply_picked_value(false, false),
+            KeyCode::Char('!') => self.apply_picked_value(true, false),
+            KeyCode::Char('r') => self.apply_picked_value(false, true),
+            _ => {}
+        }
+    }
+
+    /// Add (or exclude) the picked `field=value`; `replace` swaps out the
+    /// whole query instead of appending to it.
+    fn apply_picked_value(&mut self, negate: bool, replace: bool) {
+        let Some((field, value)) = self
+            .state
+            .value_picker_fields
+            .get(self.state.value_picker_selected)
+        else {
+            return;
+        };
+
+        let term = shorthand_term(field, value, negate);
+        if replace {
+            self.state.filter_query.clear();
+        }
+        self.append_query_term(&term);
+
+        self.state.value_picker_open = false;
+        self.state.status_message = Some(format!("Query: {}", self.state.filter_query));
+        self.state.status_set_at = Some(Instant::now());
+    }
+
+    fn apply_field_panel_value(&mut self, negate: bool) {
+        let Some(field) = self
+            .state
+            .field_panel_fields
+            .get(self.state.field_panel_selected)
+        else {
+            return;
+        };
+        let Some((value, _)) = self
+            .state
+            .field_panel_value_selected
+            .and_then(|v| field.top_values.get(v))
+        else {
+            return;
+        };
+
+        let term = shorthand_term(&field.name, value, negate);
+        self.append_query_term(&term);
+        self.state.field_panel_open = false;
+        self.state.status_message = Some(format!("Added {term} to query"));
+        self.state.status_set_at = Some(Instant::now());
+
+        if self.state.field_panel_auto_run {
+            self.start_search();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::Focus;
+    use crate::app::state::AppState;
+
+    use serde_json::json;
+
+    fn event(message: &str) -> LogEvent {
+        LogEvent {
+            timestamp_ms: 0,
+            message: message.to_string(),
+            log_stream_name: "stream".to_string(),
+            event_id: "id".to_string(),
+        }
+    }
+
+    fn app_with_events(messages: Vec<&str>) -> App {
+        let events: Vec<LogEvent> = messages.into_iter().map(event).collect();
+
+        let state = AppState {
+            lines: events.iter().map(|e| e.formatted()).collect(),
+            focus: Focus::Results,
+            line_events: events.into_iter().map(Some).collect(),
+            ..AppState::for_test()
+        };
+
+        App::for_test(state)
+    }
+
+    #[test]
+    fn flatten_json_uses_dot_paths_for_nested_objects() {
+        let flat = flatten_json(&json!({"a": 1, "req": {"method": "GET", "tags": [1, 2]}}));
+        let names: Vec<&str> = flat.iter().map(|(n, _)| n.as_str()).collect();
+        assert!(names.contains(&"a"));
+        assert!(names.contains(&"req.method"));
+        assert!(names.contains(&"req.tags[0]"));
+        assert!(names.contains(&"req.tags[1]"));
+        assert_eq!(flat.len(), 4);
+    }
+
+    #[test]
+    fn shorthand_value_quotes_strings_only() {
+        assert_eq!(shorthand_value(&json!("error")), "\"error\"");
+        assert_eq!(shorthand_value(&json!("say \"hi\"")), r#""say \"hi\"""#);
+        assert_eq!(shorthand_value(&json!(500)), "500");
+        assert_eq!(shorthand_value(&json!(true)), "true");
+        assert_eq!(shorthand_value(&json!(null)), "null");
+    }
+
+    #[test]
+    fn shorthand_term_supports_negation() {
+        assert_eq!(
+            shorthand_term("level", "\"error\"", false),
+            "level=\"error\""
+        );
+        assert_eq!(
+            shorthand_term("level", "\"error\"", true),
+            "level!=\"error\""
+        );
+    }
+
+    #[test]
+    fn discover_fields_counts_occurrences_and_top_values() {
+        let events = [
+            event(r#"INFO {"level":"error","status":500}"#),
+            event(r#"{"level":"error"}"#),
+            event(r#"{"level":"info","status":200}"#),
+            event("plain text line"),
+        ];
+
+        let fields = discover_fields(events.iter());
+        assert_eq!(fields[0].name, "level");
+        assert_eq!(fields[0].count, 3);
+        assert_eq!(fields[0].top_values[0], ("\"error\"".to_string(), 2));
+        assert_eq!(fields[1].name, "status");
+        assert_eq!(fields[1].count, 2);
+    }
+
+    #[test]
+    fn selecting_a_value_appends_term_to_query() {
+        let mut app = app_with_events(vec![r#"{"level":"error"}"#, r#"{"level":"warn"}"#]);
+        app.state.filter_query = "env=prod".to_string();
+
+        app.open_field_panel();
+        assert!(app.state.field_panel_open);
+
+        // Enter on a field opens its values; Enter again applies the top one.
+        app.handle_field_panel_key(KeyCode::Enter);
+        assert_eq!(app.state.field_panel_value_selected, Some(0));
+        app.handle_field_panel_key(KeyCode::Enter);
+
+        assert_eq!(app.state.filter_query, "env=prod level=\"error\"");
+        assert!(!app.state.field_panel_open);
+    }
+
+    #[test]
+    fn bang_appends_negated_term() {
+        let mut app = app_with_events(vec![r#"{"level":"error"}"#]);
+
+        app.open_field_panel();
+        app.handle_field_panel_key(KeyCode::Right);
+        app.handle_field_panel_key(KeyCode::Char('!'));
+
+        assert_eq!(app.state.filter_query, "level!=\"error\"");
+    }
+
+    #[test]
+    fn value_picker_lists_fields_of_event_under_cursor() {
+        let mut app = app_with_events(vec![r#"{"a":1}"#, r#"{"user":{"id":"u-1"},"ok":true}"#]);
+        // Second entry starts after the timestamp + 3-line pretty JSON of the first
+        app.state.results_scroll = 4;
+
+        app.open_value_picker();
+        assert!(app.state.value_picker_open);
+        assert_eq!(
+            app.state.value_picker_fields,
+            vec![
+                ("ok".to_string(), "true".to_string()),
+                ("user.id".to_string(), "\"u-1\"".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn value_picker_adds_excludes_and_replaces() {
+        let mut app = app_with_events(vec![r#"{"level":"error","task":"batch job"}"#]);
+        app.state.filter_query = "env=prod".to_string();
+
+        app.open_value_picker();
+        app.handle_value_picker_key(KeyCode::Enter);
+        assert_eq!(app.state.filter_query, "env=prod level=\"error\"");
+
+        app.open_value_picker();
+        app.handle_value_picker_key(KeyCode::Down);
+        app.handle_value_picker_key(KeyCode::Char('!'));
+        assert_eq!(
+            app.state.filter_query,
+            "env=prod level=\"error\" task!=\"batch job\""
+        );
+
+        app.open_value_picker();
+        app.handle_value_picker_key(KeyCode::Char('r'));
+        assert_eq!(app.state.filter_query, "level=\"error\"");
+        assert!(!app.state.value_picker_open);
+
+        // The result is something normalize_filter_pattern understands
+        assert_eq!(
+            crate::aws::normalize_filter_pattern("level=\"error\" task!=\"batch job\""),
+            "{ $.level = \"error\" && $.task != \"batch job\" }"
+        );
+    }
+
+    #[test]
+    fn value_picker_terms_with_dollar_values_stay_shorthand() {
+        let mut app = app_with_events(vec![r#"{"version":"$LATEST"}"#]);
+
+        app.open_value_picker();
+        app.handle_value_picker_key(KeyCode::Enter);
+        assert_eq!(app.state.filter_query, "version=\"$LATEST\"");
+        assert_eq!(
+            crate::aws::normalize_filter_pattern(&app.state.filter_query),
+            "{ $.version = \"$LATEST\" }"
+        );
+    }
+
+    #[test]
+    fn open_field_panel_without_json_sets_status() {
+        let mut app = app_with_events(vec!["plain"]);
+        app.open_field_panel();
+        assert!(!app.state.field_panel_open);
+        assert!(app.state.status_message.is_some());
+    }
+}
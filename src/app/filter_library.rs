@@ -0,0 +1,313 @@
+use std::path::{Path, PathBuf};
+
+use ratatui::crossterm::event::KeyCode;
+
+use super::filters::{FilterPopupMode, expand_home, load_saved_filters};
+use super::{App, SavedFilter};
+
+/// Shared filters picked up from the directory lumberjack is started in,
+/// so a repo can check in its own library.
+pub const PROJECT_FILTERS_FILE: &str = "lumberjack-filters.json";
+
+/// Where `x` exports to unless another path is typed; kept apart from
+/// `PROJECT_FILTERS_FILE` so an export never replaces the shared library.
+pub const EXPORT_FILTERS_FILE: &str = "lumberjack-filters-export.json";
+
+pub fn read_filter_file(path: &Path) -> Result<Vec<SavedFilter>, String> {
+    let data = std::fs::read_to_string(path)
+        .map_err(|e| format!("read_to_string {}: {e}", path.display()))?;
+    serde_json::from_str(&data).map_err(|e| format!("decode {}: {e}", path.display()))
+}
+
+/// Read-only filter files to merge in, with the label shown next to their
+/// filters: the project file (if there is one), then `filter_files` from
+/// the config.
+pub fn shared_filter_files(configured: &[PathBuf]) -> Vec<(String, PathBuf)> {
+    let mut files = Vec::new();
+    let project = PathBuf::from(PROJECT_FILTERS_FILE);
+    if project.exists() {
+        files.push(("project".to_string(), project));
+    }
+    for path in configured {
+        let label = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string());
+        files.push((label, path.clone()));
+    }
+    files
+}
+
+/// The user's own filters (from `own`) first, then each shared file's,
+/// labelled with their source. Shared files that can't be read come back as
+/// problems rather than failing the whole load.
+pub fn load_filter_library(
+    own: Option<&Path>,
+    configured: &[PathBuf],
+) -> Result<(Vec<SavedFilter>, Vec<String>), String> {
+    let mut filters = load_saved_filters(own)?;
+    let mut problems = Vec::new();
+
+    for (label, path) in shared_filter_files(configured) {
+        match read_filter_file(&path) {
+            Ok(shared) => filters.extend(shared.into_iter().map(|f| SavedFilter {
+                source: Some(label.clone()),
+                ..f
+            })),
+            Err(e) => problems.push(e),
+        }
+    }
+    Ok((filters, problems))
+}
+
+/// The filter without anyone's usage stats or where it was loaded from.
+fn without_usage(filter: &SavedFilter) -> SavedFilter {
+    SavedFilter {
+        last_used_ms: None,
+        use_count: 0,
+        source: None,
+        ..filter.clone()
+    }
+}
+
+/// What goes into an exported file: the filters without anyone's usage
+/// stats, so re-exporting doesn't churn a shared library.
+pub fn export_json(filters: &[SavedFilter]) -> Result<String, String> {
+    let clean: Vec<SavedFilter> = filters.iter().map(without_usage).collect();
+    serde_json::to_string_pretty(&clean).map_err(|e| format!("encode: {e}"))
+}
+
+/// Every saved field but the usage stats matches: importing it again
+/// changes nothing.
+fn same_content(a: &SavedFilter, b: &SavedFilter) -> bool {
+    without_usage(a) == without_usage(b)
+}
+
+impl App {
+    /// The user's `filters.json` plus every shared file. Shared files that
+    /// can't be read are skipped and reported in the error.
+    pub fn load_all_filters(&mut self) -> Result<(), String> {
+        let (filters, problems) = load_filter_library(
+            self.state.filters_path.as_deref(),
+            &self.state.settings.filter_files,
+        )?;
+        self.state.saved_filters = filters;
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("Skipped filter files: {}", problems.join("; ")))
+        }
+    }
+
+    /// Export/Import path prompts.
+    pub fn handle_filter_file_key(&mut self, code: KeyCode) {
+        let (FilterPopupMode::Export(input) | FilterPopupMode::Import(input)) =
+            &mut self.state.load_filter_mode
+        else {
+            return;
+        };
+        match code {
+            KeyCode::Esc => {
+                self.state.load_filter_mode = FilterPopupMode::List;
+            }
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) if !c.is_control() => {
+                input.push(c);
+            }
+            KeyCode::Enter => {
+                let path = expand_home(input.trim());
+                if path.as_os_str().is_empty() {
+                    return;
+                }
+                let exporting = matches!(self.state.load_filter_mode, FilterPopupMode::Export(_));
+                self.state.load_filter_mode = FilterPopupMode::List;
+                if exporting {
+                    self.export_filters(&path);
+                } else {
+                    self.import_filters(&path);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Write the marked filters (or the selected one) to `path`.
+    fn export_filters(&mut self, path: &Path) {
+        let chosen: Vec<SavedFilter> = if self.state.load_filter_marked.is_empty() {
+            self.selected_filter_index()
+                .map(|idx| vec![self.state.saved_filters[idx].clone()])
+                .unwrap_or_default()
+        } else {
+            self.state
+                .load_filter_marked
+                .iter()
+                .filter_map(|&idx| self.state.saved_filters.get(idx).cloned())
+                .collect()
+        };
+
+        let result = export_json(&chosen).and_then(|data| {
+            std::fs::write(path, data).map_err(|e| format!("write {}: {e}", path.display()))
+        });
+        self.state.status_message = Some(match result {
+            Ok(()) => {
+                self.state.load_filter_marked.clear();
+                format!("Exported {} filter(s) to {}", chosen.len(), path.display())
+            }
+            Err(e) => format!("Export failed: {e}"),
+        });
+        self.state.status_set_at = Some(std::time::Instant::now());
+    }
+
+    /// Add the filters in `path` to the user's own; name clashes are asked
+    /// about one at a time.
+    fn import_filters(&mut self, path: &Path) {
+        match read_filter_file(path) {
+            Ok(incoming) => {
+                let pending = incoming
+                    .into_iter()
+                    .map(|f| SavedFilter {
+                        last_used_ms: None,
+                        use_count: 0,
+                        source: None,
+                        ..f
+                    })
+                    .collect();
+                self.continue_import(pending, 0, 0);
+            }
+            Err(e) => {
+                self.state.status_message = Some(format!("Import failed: {e}"));
+                self.state.status_set_at = Some(std::time::Instant::now());
+            }
+        }
+    }
+
+    fn own_filter_named(&self, name: &str) -> Option<usize> {
+        self.state
+            .saved_filters
+            .iter()
+            .position(|f| f.source.is_none() && f.name == name)
+    }
+
+    /// Take filters from `pending` until one clashes with an existing name
+    /// (then ask), or all are in (then save).
+    fn continue_import(
+        &mut self,
+        mut pending: Vec<SavedFilter>,
+        mut added: usize,
+        mut skipped: usize,
+    ) {
+        while !pending.is_empty() {
+            match self.own_filter_named(&pending[0].name) {
+                None => {
+                    self.state.saved_filters.push(pending.remove(0));
+                    added += 1;
+                }
+                Some(idx) if same_content(&self.state.saved_filters[idx], &pending[0]) => {
+                    pending.remove(0);
+                    skipped += 1;
+                }
+                Some(_) => {
+                    self.state.load_filter_mode = FilterPopupMode::ImportConflict {
+                        pending,
+                        added,
+                        skipped,
+                    };
+                    return;
+                }
+            }
+        }
+
+        self.state.load_filter_mode = FilterPopupMode::List;
+        self.state.load_filter_marked.clear();
+        let message = if skipped > 0 {
+            format!("Imported {added} filter(s), {skipped} skipped")
+        } else {
+            format!("Imported {added} filter(s)")
+        };
+        if added > 0 {
+            self.store_filters(message);
+        } else {
+            self.state.status_message = Some(message);
+            self.state.status_set_at = Some(std::time::Instant::now());
+        }
+    }
+
+    /// `o` overwrite, `k` keep existing, `b` keep both (import renamed);
+    /// `O` / `K` do that for every remaining clash. Esc stops the import.
+    pub fn handle_import_conflict_key(&mut self, code: KeyCode) {
+        let FilterPopupMode::ImportConflict {
+            pending,
+            added,
+            skipped,
+        } = std::mem::take(&mut self.state.load_filter_mode)
+        else {
+            return;
+        };
+        let (mut pending, mut added, mut skipped) = (pending, added, skipped);
+
+        match code {
+            KeyCode::Char('o') => {
+                self.overwrite_with(pending.remove(0));
+                added += 1;
+            }
+            KeyCode::Char('O') => {
+                for f in std::mem::take(&mut pending) {
+                    if self.own_filter_named(&f.name).is_some() {
+                        self.overwrite_with(f);
+                    } else {
+                        self.state.saved_filters.push(f);
+                    }
+                    added += 1;
+                }
+            }
+            KeyCode::Char('k') => {
+                pending.remove(0);
+                skipped += 1;
+            }
+            KeyCode::Char('K') => {
+                let before = pending.len();
+                pending.retain(|f| self.own_filter_named(&f.name).is_none());
+                skipped += before - pending.len();
+            }
+            KeyCode::Char('b') => {
+                let mut f = pending.remove(0);
+                let base = format!("{} (imported)", f.name);
+                f.name = base.clone();
+                let mut n = 2;
+                while self.own_filter_named(&f.name).is_some() {
+                    f.name = format!("{base} {n}");
+                    n += 1;
+                }
+                self.state.saved_filters.push(f);
+                added += 1;
+            }
+            KeyCode::Esc => {
+                skipped += pending.len();
+                pending.clear();
+            }
+            _ => {
+                self.state.load_filter_mode = FilterPopupMode::ImportConflict {
+                    pending,
+                    added,
+                    skipped,
+                };
+                return;
+            }
+        }
+        self.continue_import(pending, added, skipped);
+    }
+
+    /// Replace the user's filter of the same name, keeping its usage stats.
+    fn overwrite_with(&mut self, incoming: SavedFilter) {
+        if let Some(idx) = self.own_filter_named(&incoming.name) {
+            let existing = &mut self.state.saved_filters[idx];
+            *existing = SavedFilter {
+                last_used_ms: existing.last_used_ms,
+                use_count: existing.use_count,
+                ..incoming
+            };
+        }
+    }
+}
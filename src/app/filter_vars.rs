@@ -0,0 +1,208 @@
+use std::collections::BTreeMap;
+
+use ratatui::crossterm::event::KeyCode;
+
+use super::{App, SavedFilter};
+
+/// Names of the `${name}` placeholders in `text`, in order of appearance.
+/// Names are letters, digits, `_` and `-`; anything else is left as text.
+pub fn placeholder_names(text: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut rest = text;
+    while let Some(pos) = rest.find("${") {
+        rest = &rest[pos + 2..];
+        if let Some(end) = rest.find('}') {
+            let name = &rest[..end];
+            if is_placeholder_name(name) {
+                if !names.iter().any(|n| n == name) {
+                    names.push(name.to_string());
+                }
+                rest = &rest[end + 1..];
+            }
+        }
+    }
+    names
+}
+
+fn is_placeholder_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Replace each `${name}` that has a value; unknown ones stay as written.
+pub fn substitute(text: &str, values: &BTreeMap<String, String>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(pos) = rest.find("${") {
+        out.push_str(&rest[..pos]);
+        let after = &rest[pos + 2..];
+        match after.find('}') {
+            Some(end) if is_placeholder_name(&after[..end]) => {
+                match values.get(&after[..end]) {
+                    Some(value) => out.push_str(value),
+                    None => out.push_str(&rest[pos..pos + 2 + end + 1]),
+                }
+                rest = &after[end + 1..];
+            }
+            _ => {
+                out.push_str("${");
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+impl SavedFilter {
+    /// Placeholders across start, end and query, without repeats.
+    pub fn placeholders(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        for text in [&self.start, &self.end, &self.query] {
+            for name in placeholder_names(text) {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+        names
+    }
+}
+
+/// Values being typed for a saved filter's placeholders.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FilterVarsPrompt {
+    /// Index into `saved_filters`.
+    pub filter: usize,
+    pub names: Vec<String>,
+    pub values: Vec<String>,
+    /// The placeholder being typed into.
+    pub field: usize,
+}
+
+impl App {
+    /// Ask for the placeholders of saved filter `idx`, pre-filled with the
+    /// values used last time.
+    pub fn open_filter_vars_prompt(&mut self, idx: usize) {
+        let Some(f) = self.state.saved_filters.get(idx) else {
+            return;
+        };
+        let names = f.placeholders();
+        let values = names
+            .iter()
+            .map(|name| f.defaults.get(name).cloned().unwrap_or_default())
+            .collect();
+        self.state.filter_vars_prompt = Some(FilterVarsPrompt {
+            filter: idx,
+            names,
+            values,
+            field: 0,
+        });
+    }
+
+    pub fn handle_filter_vars_key(&mut self, code: KeyCode) {
+        let Some(prompt) = self.state.filter_vars_prompt.as_mut() else {
+            return;
+        };
+        let last = prompt.names.len().saturating_sub(1);
+
+        match code {
+            KeyCode::Esc => {
+                self.state.filter_vars_prompt = None;
+            }
+            KeyCode::Tab | KeyCode::Down => {
+                prompt.field = (prompt.field + 1).min(last);
+            }
+            KeyCode::BackTab | KeyCode::Up => {
+                prompt.field = prompt.field.saturating_sub(1);
+            }
+            KeyCode::Backspace => {
+                prompt.values[prompt.field].pop();
+            }
+            KeyCode::Char(c) if !c.is_control() => {
+                prompt.values[prompt.field].push(c);
+            }
+            // Enter moves through the variables and loads after the last one
+            KeyCode::Enter if prompt.field < last => {
+                prompt.field += 1;
+            }
+            KeyCode::Enter => {
+                if let Some(prompt) = self.state.filter_vars_prompt.take() {
+                    let values: BTreeMap<String, String> =
+                        prompt.names.into_iter().zip(prompt.values).collect();
+                    if let Some(f) = self.state.saved_filters.get_mut(prompt.filter) {
+                        f.defaults = values.clone();
+                    }
+                    self.fill_from_saved_filter(prompt.filter, &values);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::Focus;
+    use crate::app::state::AppState;
+
+    fn test_app() -> App {
+        let state = AppState {
+            focus: Focus::Filter,
+            ..AppState::for_test()
+        };
+
+        App::for_test(state)
+    }
+
+    #[test]
+    fn finds_and_substitutes_placeholders() {
+        assert_eq!(
+            placeholder_names("tenant_id=${tenant} req=${req-id} again=${tenant}"),
+            vec!["tenant", "req-id"]
+        );
+        // Not placeholders: empty, spaces, unterminated
+        assert!(placeholder_names("${} ${a b} ${open").is_empty());
+
+        let values = BTreeMap::from([("tenant".to_string(), "42".to_string())]);
+        assert_eq!(
+            substitute("tenant_id=${tenant} x=${other} ${", &values),
+            "tenant_id=42 x=${other} ${"
+        );
+    }
+
+    #[test]
+    fn loading_prompts_with_remembered_defaults() {
+        let mut app = test_app();
+        app.state.saved_filters.push(SavedFilter {
+            name: "tenant-errors".to_string(),
+            start: "-${window}".to_string(),
+            query: "tenant_id=${tenant} level=error".to_string(),
+            defaults: BTreeMap::from([("window".to_string(), "1h".to_string())]),
+            ..SavedFilter::default()
+        });
+
+        app.open_load_filter_popup();
+        app.handle_load_filter_popup_key(KeyCode::Enter);
+        let prompt = app.state.filter_vars_prompt.clone().unwrap();
+        assert_eq!(prompt.names, vec!["window", "tenant"]);
+        assert_eq!(prompt.values, vec!["1h", ""]);
+        // Nothing is filled in until the prompt is done
+        assert_eq!(app.state.filter_query, "");
+
+        app.handle_filter_vars_key(KeyCode::Enter);
+        for c in "acme".chars() {
+            app.handle_filter_vars_key(KeyCode::Char(c));
+        }
+        app.handle_filter_vars_key(KeyCode::Enter);
+
+        assert!(app.state.filter_vars_prompt.is_none());
+        assert_eq!(app.state.filter_start, "-1h");
+        assert_eq!(app.state.filter_query, "tenant_id=acme level=error");
+        assert_eq!(app.state.saved_filters[0].defaults["tenant"], "acme");
+        assert_eq!(app.state.saved_filters[0].use_count, 1);
+    }
+}
@@ -1,10 +1,103 @@
-use std::path::PathBuf;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 
+use chrono::Utc;
 use ratatui::crossterm::event::KeyCode;
 
+use super::context::{FilterLoad, context_label, resolve_groups};
+use super::filter_library::{EXPORT_FILTERS_FILE, read_filter_file};
+use super::filter_vars::substitute;
 use super::{App, FilterField, SavedFilter};
 
+/// Order of the rows in the `F` popup.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FilterSort {
+    #[default]
+    Name,
+    /// Most recently loaded first; never-used filters last.
+    LastUsed,
+}
+
+impl FilterSort {
+    pub fn label(self) -> &'static str {
+        match self {
+            FilterSort::Name => "name",
+            FilterSort::LastUsed => "last used",
+        }
+    }
+}
+
+/// Fields editable in place, in the order `Tab` visits them.
+pub const EDIT_FIELDS: [&str; 4] = ["Group", "Start", "End", "Query"];
+
+/// What the `F` popup is doing on top of listing filters.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum FilterPopupMode {
+    #[default]
+    List,
+    /// Typing into the search box.
+    Search,
+    ConfirmDelete,
+    Rename(String),
+    /// Values in `EDIT_FIELDS` order; `field` is the one being typed into.
+    Edit {
+        field: usize,
+        values: [String; 4],
+    },
+    /// Typing the file to export the marked (or selected) filters to.
+    Export(String),
+    /// Typing the file to import filters from.
+    Import(String),
+    /// `pending[0]` has the same name as one of the user's filters.
+    ImportConflict {
+        pending: Vec<SavedFilter>,
+        added: usize,
+        skipped: usize,
+    },
+}
+
+/// Path of `file` inside lumberjack's config directory, creating the
+/// directory if needed.
+pub fn config_file_path(file: &str) -> Result<PathBuf, String> {
+    let home = std::env::var("HOME").map_err(|e| format!("HOME not set: {e}"))?;
+    let mut path = PathBuf::from(home);
+    path.push(".config");
+    // In tests, use a separate location so we don't overwrite the user's
+    // real files.
+    if cfg!(test) {
+        path.push("lumberjack-test");
+    } else {
+        path.push("lumberjack");
+    }
+    std::fs::create_dir_all(&path)
+        .map_err(|e| format!("create_dir_all {}: {e}", path.display()))?;
+    path.push(file);
+    Ok(path)
+}
+
+/// The user's own `filters.json`.
+pub fn filters_path() -> Result<PathBuf, String> {
+    config_file_path("filters.json")
+}
+
+/// The filters saved at `path`; none when there's no file (or no path).
+pub fn load_saved_filters(path: Option<&Path>) -> Result<Vec<SavedFilter>, String> {
+    match path {
+        Some(path) if path.exists() => read_filter_file(path),
+        _ => Ok(Vec::new()),
+    }
+}
+
+/// `~/...` relative to `$HOME`; anything else as given.
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var("HOME")) {
+        (Some(rest), Ok(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
 impl App {
     pub fn open_save_filter_popup(&mut self) {
         self.state.save_filter_name.clear();
@@ -13,22 +106,93 @@ impl App {
 
     pub fn open_load_filter_popup(&mut self) {
         if self.state.saved_filters.is_empty() {
-            // Try to load from disk lazily
-            if let Ok(filters) = Self::load_saved_filters_from_disk() {
-                self.state.saved_filters = filters;
+            // Load lazily: the user's filters plus the shared libraries
+            if let Err(e) = self.load_all_filters() {
+                self.state.status_message = Some(e);
+                self.state.status_set_at = Some(Instant::now());
             }
         }
 
-        if self.state.saved_filters.is_empty() {
-            self.state.status_message = Some("No saved filters".to_string());
-            self.state.status_set_at = Some(Instant::now());
-            return;
-        }
-
+        // Opens even when empty, so there's somewhere to import into
         self.state.load_filter_selected = 0;
+        self.state.load_filter_marked.clear();
+        self.state.load_filter_search.clear();
+        self.state.load_filter_mode = FilterPopupMode::List;
         self.state.load_filter_popup_open = true;
     }
 
+    /// Indices into `saved_filters` for the popup rows: fuzzy-matched against
+    /// the search box, in the current sort order.
+    pub fn load_filter_entries(&self) -> Vec<usize> {
+        let needle = &self.state.load_filter_search;
+        let mut entries: Vec<usize> = self
+            .state
+            .saved_filters
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| {
+                let source = f.source.as_deref().unwrap_or_default();
+                Self::fuzzy_match(&format!("{} {} {source}", f.name, f.group), needle)
+            })
+            .map(|(idx, _)| idx)
+            .collect();
+
+        let filters = &self.state.saved_filters;
+        match self.state.load_filter_sort {
+            FilterSort::Name => {
+                entries.sort_by_key(|&idx| filters[idx].name.to_lowercase());
+            }
+            FilterSort::LastUsed => {
+                entries.sort_by_key(|&idx| {
+                    (
+                        Reverse(filters[idx].last_used_ms),
+                        filters[idx].name.to_lowercase(),
+                    )
+                });
+            }
+        }
+        entries
+    }
+
+    /// The filter under the popup cursor, as an index into `saved_filters`.
+    pub fn selected_filter_index(&self) -> Option<usize> {
+        self.load_filter_entries()
+            .get(self.state.load_filter_selected)
+            .copied()
+    }
+
+    /// Put the popup cursor on `saved_filters[idx]` (top row if it's hidden).
+    fn select_filter_row(&mut self, idx: usize) {
+        self.state.load_filter_selected = self
+            .load_filter_entries()
+            .iter()
+            .position(|&i| i == idx)
+            .unwrap_or(0);
+    }
+
+    /// Status telling the user `saved_filters[idx]` is from a shared file,
+    /// if it is.
+    fn refuse_read_only(&mut self, idx: usize) -> bool {
+        let Some(source) = self.state.saved_filters[idx].source.clone() else {
+            return false;
+        };
+        self.state.status_message = Some(format!(
+            "\"{}\" is read-only (from {source}); press c to copy it into your filters",
+            self.state.saved_filters[idx].name
+        ));
+        self.state.status_set_at = Some(Instant::now());
+        true
+    }
+
+    /// Write the user's own filters out and report `done`, or the error.
+    pub fn store_filters(&mut self, done: String) {
+        self.state.status_message = Some(match self.save_all_filters_to_disk() {
+            Ok(()) => done,
+            Err(e) => format!("Error saving filters: {e}"),
+        });
+        self.state.status_set_at = Some(Instant::now());
+    }
+
     pub fn handle_save_filter_popup_key(&mut self, code: KeyCode) {
         match code {
             KeyCode::Esc => {
@@ -38,20 +202,38 @@ impl App {
             KeyCode::Enter => {
                 if !self.state.save_filter_name.trim().is_empty() {
                     let name = self.state.save_filter_name.trim().to_string();
-                    let current_group = self
+                    let entry = self
                         .state
                         .groups
                         .get(self.state.selected_group)
                         .cloned()
                         .unwrap_or_default();
-                    // Overwrite if exists
-                    if let Some(existing) =
-                        self.state.saved_filters.iter_mut().find(|f| f.name == name)
+                    // A tagged entry is saved as its plain name and context
+                    let (profile, region, current_group) = self.group_target(&entry);
+                    // Groups bound by a loaded filter are saved along with it
+                    let extra_groups: Vec<String> = self
+                        .state
+                        .filter_groups
+                        .iter()
+                        .map(|g| self.group_target(g))
+                        .filter(|(p, r, g)| *p == profile && *r == region && *g != current_group)
+                        .map(|(_, _, g)| g)
+                        .collect();
+                    // Overwrite if exists (shared filters are never written to)
+                    if let Some(existing) = self
+                        .state
+                        .saved_filters
+                        .iter_mut()
+                        .find(|f| f.source.is_none() && f.name == name)
                     {
                         existing.group = current_group.clone();
                         existing.start = self.state.filter_start.clone();
                         existing.end = self.state.filter_end.clone();
                         existing.query = self.state.filter_query.clone();
+                        existing.groups = extra_groups.clone();
+                        existing.stream_prefixes = self.state.filter_streams.clone();
+                        existing.profile = profile.clone();
+                        existing.region = region.clone();
                     } else {
                         self.state.saved_filters.push(SavedFilter {
                             name: name.clone(),
@@ -59,11 +241,16 @@ impl App {
                             start: self.state.filter_start.clone(),
                             end: self.state.filter_end.clone(),
                             query: self.state.filter_query.clone(),
+                            groups: extra_groups,
+                            stream_prefixes: self.state.filter_streams.clone(),
+                            profile,
+                            region,
+                            ..SavedFilter::default()
                         });
                     }
 
                     // Best-effort persistence; update status on success or failure
-                    match Self::save_all_filters_to_disk(&self.state.saved_filters) {
+                    match self.save_all_filters_to_disk() {
                         Ok(()) => {
                             self.state.status_message = Some(format!("Saved filter \"{}\"", name));
                         }
@@ -81,111 +268,376 @@ impl App {
                 self.state.save_filter_name.pop();
             }
 
-            KeyCode::Char(c) => {
-                if !c.is_control() {
-                    self.state.save_filter_name.push(c);
-                }
+            KeyCode::Char(c) if !c.is_control() => {
+                self.state.save_filter_name.push(c);
             }
             _ => {}
         }
     }
 
     pub fn handle_load_filter_popup_key(&mut self, code: KeyCode) {
-        if self.state.saved_filters.is_empty() {
-            self.state.load_filter_popup_open = false;
-            return;
+        match self.state.load_filter_mode {
+            FilterPopupMode::List => self.handle_filter_list_key(code),
+            FilterPopupMode::Search => self.handle_filter_search_key(code),
+            FilterPopupMode::ConfirmDelete => {
+                if matches!(code, KeyCode::Char('y') | KeyCode::Char('Y')) {
+                    self.delete_selected_filter();
+                }
+                self.state.load_filter_mode = FilterPopupMode::List;
+            }
+            FilterPopupMode::Rename(_) => self.handle_filter_rename_key(code),
+            FilterPopupMode::Edit { .. } => self.handle_filter_edit_key(code),
+            FilterPopupMode::Export(_) | FilterPopupMode::Import(_) => {
+                self.handle_filter_file_key(code)
+            }
+            FilterPopupMode::ImportConflict { .. } => self.handle_import_conflict_key(code),
         }
+    }
+
+    fn handle_filter_list_key(&mut self, code: KeyCode) {
+        let count = self.load_filter_entries().len();
+        let selected = self.selected_filter_index();
 
         match code {
             KeyCode::Esc => {
                 self.state.load_filter_popup_open = false;
             }
-            KeyCode::Up => {
-                if self.state.load_filter_selected > 0 {
-                    self.state.load_filter_selected -= 1;
+            KeyCode::Up if self.state.load_filter_selected > 0 => {
+                self.state.load_filter_selected -= 1;
+            }
+            KeyCode::Down if self.state.load_filter_selected + 1 < count => {
+                self.state.load_filter_selected += 1;
+            }
+            KeyCode::Enter => {
+                if let Some(idx) = selected {
+                    self.apply_saved_filter(idx);
                 }
+                self.state.load_filter_popup_open = false;
+            }
+            KeyCode::Char('/') => {
+                self.state.load_filter_mode = FilterPopupMode::Search;
             }
-            KeyCode::Down => {
-                if self.state.load_filter_selected + 1 < self.state.saved_filters.len() {
-                    self.state.load_filter_selected += 1;
+            KeyCode::Char('s') => {
+                self.state.load_filter_sort = match self.state.load_filter_sort {
+                    FilterSort::Name => FilterSort::LastUsed,
+                    FilterSort::LastUsed => FilterSort::Name,
+                };
+                if let Some(idx) = selected {
+                    self.select_filter_row(idx);
                 }
             }
-            KeyCode::Enter => {
-                if let Some(f) = self
-                    .state
-                    .saved_filters
-                    .get(self.state.load_filter_selected)
+            KeyCode::Char('d') => {
+                if let Some(idx) = selected
+                    && !self.refuse_read_only(idx)
                 {
-                    let start = f.start.clone();
-                    let end = f.end.clone();
-                    let query = f.query.clone();
-                    let group = f.group.clone();
-                    let name = f.name.clone();
-
-                    self.state.filter_start = start;
-                    self.state.filter_end = end;
-                    self.state.filter_query = query;
-                    self.state.filter_field = FilterField::Query;
-
-                    if !group.is_empty() {
-                        if let Some(idx) = self.state.groups.iter().position(|g| g == &group) {
-                            self.state.selected_group = idx;
-                            let visible_rows = self.visible_group_rows();
-                            self.clamp_groups_scroll(visible_rows);
-                        }
+                    self.state.load_filter_mode = FilterPopupMode::ConfirmDelete;
+                }
+            }
+            KeyCode::Char('r') => {
+                if let Some(idx) = selected
+                    && !self.refuse_read_only(idx)
+                {
+                    let name = self.state.saved_filters[idx].name.clone();
+                    self.state.load_filter_mode = FilterPopupMode::Rename(name);
+                }
+            }
+            KeyCode::Char('c') => {
+                if let Some(idx) = selected {
+                    self.duplicate_filter(idx);
+                }
+            }
+            KeyCode::Char('e') => {
+                if let Some(idx) = selected
+                    && !self.refuse_read_only(idx)
+                {
+                    let f = &self.state.saved_filters[idx];
+                    let values = [
+                        f.group.clone(),
+                        f.start.clone(),
+                        f.end.clone(),
+                        f.query.clone(),
+                    ];
+                    self.state.load_filter_mode = FilterPopupMode::Edit { field: 3, values };
+                }
+            }
+            KeyCode::Char(' ') => {
+                if let Some(idx) = selected {
+                    if !self.state.load_filter_marked.remove(&idx) {
+                        self.state.load_filter_marked.insert(idx);
                     }
+                    self.state.load_filter_selected =
+                        (self.state.load_filter_selected + 1).min(count.saturating_sub(1));
+                }
+            }
+            KeyCode::Char('x') if selected.is_some() => {
+                self.state.load_filter_mode =
+                    FilterPopupMode::Export(EXPORT_FILTERS_FILE.to_string());
+            }
+            KeyCode::Char('i') => {
+                self.state.load_filter_mode = FilterPopupMode::Import(String::new());
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_filter_search_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Esc => {
+                self.state.load_filter_search.clear();
+                self.state.load_filter_mode = FilterPopupMode::List;
+            }
+            KeyCode::Enter => {
+                self.state.load_filter_mode = FilterPopupMode::List;
+            }
+            KeyCode::Up | KeyCode::Down => self.handle_filter_list_key(code),
+            KeyCode::Backspace => {
+                self.state.load_filter_search.pop();
+                self.state.load_filter_selected = 0;
+            }
+            KeyCode::Char(c) if !c.is_control() => {
+                self.state.load_filter_search.push(c);
+                self.state.load_filter_selected = 0;
+            }
+            _ => {}
+        }
+    }
 
-                    self.state.status_message = Some(format!("Loaded filter \"{}\"", name));
+    fn handle_filter_rename_key(&mut self, code: KeyCode) {
+        let FilterPopupMode::Rename(input) = &mut self.state.load_filter_mode else {
+            return;
+        };
+        match code {
+            KeyCode::Esc => {
+                self.state.load_filter_mode = FilterPopupMode::List;
+            }
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) if !c.is_control() => {
+                input.push(c);
+            }
+            KeyCode::Enter => {
+                let new_name = input.trim().to_string();
+                let Some(idx) = self.selected_filter_index() else {
+                    return;
+                };
+                if new_name.is_empty() {
+                    return;
+                }
+                let taken = self
+                    .state
+                    .saved_filters
+                    .iter()
+                    .enumerate()
+                    .any(|(i, f)| i != idx && f.source.is_none() && f.name == new_name);
+                if taken {
+                    self.state.status_message =
+                        Some(format!("A filter named \"{new_name}\" already exists"));
                     self.state.status_set_at = Some(Instant::now());
+                    return;
                 }
-                self.state.load_filter_popup_open = false;
+
+                let old_name =
+                    std::mem::replace(&mut self.state.saved_filters[idx].name, new_name.clone());
+                self.state.load_filter_mode = FilterPopupMode::List;
+                self.select_filter_row(idx);
+                self.store_filters(format!("Renamed \"{old_name}\" to \"{new_name}\""));
             }
+            _ => {}
+        }
+    }
 
+    fn handle_filter_edit_key(&mut self, code: KeyCode) {
+        let FilterPopupMode::Edit { field, values } = &mut self.state.load_filter_mode else {
+            return;
+        };
+        match code {
+            KeyCode::Esc => {
+                self.state.load_filter_mode = FilterPopupMode::List;
+            }
+            KeyCode::Tab | KeyCode::Down => {
+                *field = (*field + 1) % EDIT_FIELDS.len();
+            }
+            KeyCode::BackTab | KeyCode::Up => {
+                *field = (*field + EDIT_FIELDS.len() - 1) % EDIT_FIELDS.len();
+            }
+            KeyCode::Backspace => {
+                values[*field].pop();
+            }
+            KeyCode::Char(c) if !c.is_control() => {
+                values[*field].push(c);
+            }
+            KeyCode::Enter => {
+                let [group, start, end, query] = values.clone();
+                self.state.load_filter_mode = FilterPopupMode::List;
+                let Some(idx) = self.selected_filter_index() else {
+                    return;
+                };
+                let f = &mut self.state.saved_filters[idx];
+                f.group = group.trim().to_string();
+                f.start = start.trim().to_string();
+                f.end = end.trim().to_string();
+                f.query = query;
+                let name = f.name.clone();
+                self.store_filters(format!("Updated filter \"{name}\""));
+            }
             _ => {}
         }
     }
 
-    fn filters_path() -> Result<PathBuf, String> {
-        // In tests, write filters to a separate location so we don't overwrite
-        // the user's real filters.
-        if cfg!(test) {
-            let home = std::env::var("HOME").map_err(|e| format!("HOME not set: {e}"))?;
-            let mut path = PathBuf::from(home);
-            path.push(".config");
-            path.push("lumberjack-test");
-            std::fs::create_dir_all(&path)
-                .map_err(|e| format!("create_dir_all {}: {e}", path.display()))?;
-            path.push("filters.json");
-            return Ok(path);
+    fn delete_selected_filter(&mut self) {
+        let Some(idx) = self.selected_filter_index() else {
+            return;
+        };
+        let removed = self.state.saved_filters.remove(idx);
+        // Marks are indices, which just shifted
+        self.state.load_filter_marked.clear();
+        let count = self.load_filter_entries().len();
+        self.state.load_filter_selected =
+            self.state.load_filter_selected.min(count.saturating_sub(1));
+        if self.state.saved_filters.is_empty() {
+            self.state.load_filter_popup_open = false;
+        }
+        self.store_filters(format!("Deleted filter \"{}\"", removed.name));
+    }
+
+    /// Copy `saved_filters[idx]` into the user's own filters as "<name> copy"
+    /// (or "copy 2", ...), with fresh usage stats, and select it.
+    fn duplicate_filter(&mut self, idx: usize) {
+        let base = format!("{} copy", self.state.saved_filters[idx].name);
+        let mut name = base.clone();
+        let mut n = 2;
+        while self.state.saved_filters.iter().any(|f| f.name == name) {
+            name = format!("{base} {n}");
+            n += 1;
         }
 
-        // Normal runtime path
-        let home = std::env::var("HOME").map_err(|e| format!("HOME not set: {e}"))?;
-        let mut path = PathBuf::from(home);
-        path.push(".config");
-        path.push("lumberjack");
-        std::fs::create_dir_all(&path)
-            .map_err(|e| format!("create_dir_all {}: {e}", path.display()))?;
-        path.push("filters.json");
-        Ok(path)
+        let copy = SavedFilter {
+            name: name.clone(),
+            last_used_ms: None,
+            use_count: 0,
+            source: None,
+            ..self.state.saved_filters[idx].clone()
+        };
+        self.state.saved_filters.insert(idx + 1, copy);
+        self.state.load_filter_marked.clear();
+        // A search that hides the copy would leave the cursor somewhere odd
+        self.state.load_filter_search.clear();
+        self.select_filter_row(idx + 1);
+        self.store_filters(format!("Duplicated as \"{name}\""));
+    }
+
+    /// Load saved filter `idx`: straight away, or after asking for its
+    /// `${name}` placeholders.
+    fn apply_saved_filter(&mut self, idx: usize) {
+        let Some(f) = self.state.saved_filters.get(idx) else {
+            return;
+        };
+        if f.placeholders().is_empty() {
+            self.fill_from_saved_filter(idx, &BTreeMap::new());
+        } else {
+            self.open_filter_vars_prompt(idx);
+        }
+    }
+
+    /// Copy saved filter `idx` into the Filter pane with `values` substituted
+    /// for its placeholders, first offering to switch context if it was
+    /// saved for another profile/region.
+    pub fn fill_from_saved_filter(&mut self, idx: usize, values: &BTreeMap<String, String>) {
+        let Some(f) = self.state.saved_filters.get(idx) else {
+            return;
+        };
+        let load = FilterLoad {
+            filter: idx,
+            values: values.clone(),
+        };
+        if self.foreign_context(f).is_some() {
+            self.state.context_switch_prompt = Some(load);
+        } else {
+            self.finish_filter_load(load);
+        }
+    }
+
+    /// Fill the Filter pane, select (or bind) the filter's groups, bump its
+    /// usage stats and report any groups that aren't there.
+    pub fn finish_filter_load(&mut self, load: FilterLoad) {
+        let Some(f) = self.state.saved_filters.get_mut(load.filter) else {
+            return;
+        };
+        f.last_used_ms = Some(Utc::now().timestamp_millis());
+        f.use_count += 1;
+        let f = f.clone();
+
+        self.state.filter_start = substitute(&f.start, &load.values);
+        self.state.filter_end = substitute(&f.end, &load.values);
+        self.state.filter_query = substitute(&f.query, &load.values);
+        self.state.filter_field = FilterField::Query;
+
+        // The filter's own context when its groups are listed (possibly
+        // tagged, among other contexts); else it's loaded here anyway
+        let (profile, region) = match self.saved_context(&f) {
+            (p, r) if self.lists_context(&p, &r) => (p, r),
+            _ => (self.state.profile.clone(), self.state.region.clone()),
+        };
+        let available = self.groups_in_context(&profile, &region);
+        let (found, missing) = resolve_groups(&f.group_patterns(), &available);
+        let found: Vec<String> = found
+            .iter()
+            .map(|g| self.pane_entry(&profile, &region, g))
+            .collect();
+        self.clear_group_binding();
+        if let Some(first) = found.first() {
+            self.select_group_by_name(first);
+        }
+        // One plain group is just a selection; more (or streams) are bound
+        if found.len() > 1 || !f.stream_prefixes.is_empty() {
+            self.state.filter_groups = found.clone();
+            self.state.filter_streams = f.stream_prefixes.clone();
+        }
+
+        let mut message = format!("Loaded filter \"{}\"", f.name);
+        if found.len() > 1 {
+            message.push_str(&format!(" ({} groups)", found.len()));
+        }
+        if !missing.is_empty() {
+            message.push_str(&format!(
+                "; not in {}: {}",
+                context_label(&profile, &region),
+                missing.join(", ")
+            ));
+        }
+        self.store_filters(message);
     }
 
-    fn load_saved_filters_from_disk() -> Result<Vec<SavedFilter>, String> {
-        let path = Self::filters_path()?;
-        if !path.exists() {
-            return Ok(Vec::new());
+    /// Load saved filter `name` (from disk if needed), as `F` would.
+    pub fn apply_saved_filter_by_name(&mut self, name: &str) -> Result<(), String> {
+        if self.state.saved_filters.is_empty() {
+            self.load_all_filters()?;
         }
-        let data = std::fs::read_to_string(&path)
-            .map_err(|e| format!("read_to_string {}: {e}", path.display()))?;
-        let filters: Vec<SavedFilter> =
-            serde_json::from_str(&data).map_err(|e| format!("decode: {e}"))?;
-        Ok(filters)
+        let idx = self
+            .state
+            .saved_filters
+            .iter()
+            .position(|f| f.name == name)
+            .ok_or_else(|| format!("no saved filter named \"{name}\""))?;
+        self.apply_saved_filter(idx);
+        Ok(())
     }
 
-    fn save_all_filters_to_disk(filters: &[SavedFilter]) -> Result<(), String> {
-        let path = Self::filters_path()?;
-        let data = serde_json::to_string_pretty(filters).map_err(|e| format!("encode: {e}"))?;
-        std::fs::write(&path, data).map_err(|e| format!("write {}: {e}", path.display()))?;
+    /// Only the user's own filters are written; shared ones stay in their
+    /// files. Without a `filters_path` (tests) nothing is written.
+    fn save_all_filters_to_disk(&self) -> Result<(), String> {
+        let Some(path) = &self.state.filters_path else {
+            return Ok(());
+        };
+        let own: Vec<&SavedFilter> = self
+            .state
+            .saved_filters
+            .iter()
+            .filter(|f| f.source.is_none())
+            .collect();
+        let data = serde_json::to_string_pretty(&own).map_err(|e| format!("encode: {e}"))?;
+        std::fs::write(path, data).map_err(|e| format!("write {}: {e}", path.display()))?;
         Ok(())
     }
 }
@@ -194,66 +646,23 @@ impl App {
 mod tests {
     use super::*;
     use crate::AppState;
+
     use crate::app::{App, Focus};
-    use crate::ui::styles::Theme;
-    use std::sync::atomic::AtomicBool;
-    use std::sync::{Arc, mpsc};
-    use std::time::Instant as StdInstant;
 
-    fn app_with_filter_state() -> App {
-        let (tx, rx) = mpsc::channel();
+    use std::collections::BTreeSet;
+
+    /// A file of this test's own, outside any config directory.
+    fn scratch_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("lumberjack-test-{}-{name}", std::process::id()))
+    }
 
+    fn app_with_filter_state() -> App {
         let state = AppState {
-            app_title: "Test".to_string(),
-            theme: Theme::default_dark(),
-            theme_name: "dark".to_string(),
-            lines: Vec::new(),
-            filter_cursor_pos: 0,
-
-            all_groups: Vec::new(),
-            groups: Vec::new(),
-            selected_group: 0,
-            groups_scroll: 0,
-
-            profile: "test-profile".to_string(),
-            region: "eu-west-1".to_string(),
             focus: Focus::Filter,
-
-            filter_start: String::new(),
-            filter_end: String::new(),
-            filter_query: String::new(),
-            filter_field: FilterField::Query,
-            editing: false,
-            cursor_on: true,
-            last_blink: StdInstant::now(),
-
-            group_search_active: false,
-            group_search_input: String::new(),
-
-            searching: false,
-            dots: 0,
-            last_dots: StdInstant::now(),
-            results_scroll: 0,
-
-            tail_mode: false,
-
-            status_message: None,
-            status_set_at: None,
-
-            saved_filters: Vec::new(),
-            save_filter_popup_open: false,
-            save_filter_name: String::new(),
-            load_filter_popup_open: false,
-            load_filter_selected: 0,
+            ..AppState::for_test()
         };
 
-        App {
-            state,
-            exit: false,
-            search_tx: tx,
-            search_rx: rx,
-            tail_stop: Arc::new(AtomicBool::new(false)),
-        }
+        App::for_test(state)
     }
 
     #[test]
@@ -275,6 +684,61 @@ mod tests {
         assert_eq!(f.query, "routing_id=123");
     }
 
+    #[test]
+    fn saved_filters_are_written_to_the_filters_path() {
+        let path = scratch_path("saved-filters.json");
+        let mut app = app_with_filter_state();
+        app.state.filters_path = Some(path.clone());
+        app.state.filter_query = "level=error".to_string();
+
+        app.open_save_filter_popup();
+        app.state.save_filter_name = "errors".to_string();
+        app.handle_save_filter_popup_key(KeyCode::Enter);
+
+        let saved = load_saved_filters(Some(&path)).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(saved, app.state.saved_filters);
+        assert_eq!(saved[0].query, "level=error");
+    }
+
+    #[test]
+    fn filter_saved_from_a_tagged_group_reloads_in_its_context() {
+        let groups: Vec<String> = [
+            "[prod/eu-west-1] /app/api",
+            "[prod/us-east-1] /app/api",
+            "[prod/us-east-1] /app/worker",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        let mut app = app_with_filter_state();
+        app.state.settings.contexts = vec![
+            ("prod".to_string(), "eu-west-1".to_string()),
+            ("prod".to_string(), "us-east-1".to_string()),
+        ];
+        app.state.all_groups = groups.clone();
+        app.state.groups = groups;
+        app.state.selected_group = 1;
+        app.state.filter_query = "ERROR".to_string();
+
+        app.open_save_filter_popup();
+        app.state.save_filter_name = "us errors".to_string();
+        app.handle_save_filter_popup_key(KeyCode::Enter);
+        let f = app.state.saved_filters.last().unwrap();
+        assert_eq!(
+            (f.group.as_str(), f.profile.as_str(), f.region.as_str()),
+            ("/app/api", "prod", "us-east-1")
+        );
+
+        // Loading it picks the entry of that context, no switch needed
+        app.state.selected_group = 0;
+        app.state.filter_query.clear();
+        app.apply_saved_filter_by_name("us errors").unwrap();
+        assert!(app.state.context_switch_prompt.is_none());
+        assert_eq!(app.state.selected_group, 1);
+        assert_eq!(app.state.filter_query, "ERROR");
+    }
+
     #[test]
     fn save_filter_overwrites_existing_entry_with_same_name() {
         let mut app = app_with_filter_state();
@@ -284,6 +748,7 @@ mod tests {
             start: "-5m".to_string(),
             end: "".to_string(),
             query: "routing_id=111".to_string(),
+            ..SavedFilter::default()
         });
 
         app.state.filter_start = "-15m".to_string();
@@ -311,6 +776,7 @@ mod tests {
             start: "-1h".to_string(),
             end: "".to_string(),
             query: "level=error".to_string(),
+            ..SavedFilter::default()
         });
 
         app.open_load_filter_popup();
@@ -331,6 +797,7 @@ mod tests {
             start: "-5m".to_string(),
             end: "".to_string(),
             query: "a=1".to_string(),
+            ..SavedFilter::default()
         });
         app.state.saved_filters.push(SavedFilter {
             name: "second".to_string(),
@@ -338,6 +805,7 @@ mod tests {
             start: "-15m".to_string(),
             end: "".to_string(),
             query: "b=2".to_string(),
+            ..SavedFilter::default()
         });
 
         app.open_load_filter_popup();
@@ -358,6 +826,7 @@ mod tests {
             "/aws/lambda/first".to_string(),
             "/aws/lambda/second".to_string(),
         ];
+        app.state.all_groups = app.state.groups.clone();
         app.state.selected_group = 1; // "/aws/lambda/second"
 
         app.state.filter_start = "-5m".to_string();
@@ -390,6 +859,8 @@ mod tests {
 
         app.state.groups = (0..10).map(|i| format!("/aws/lambda/group-{i}")).collect();
 
+        app.state.all_groups = app.state.groups.clone();
+
         app.state.selected_group = 0;
         app.state.groups_scroll = 0;
 
@@ -422,4 +893,364 @@ mod tests {
             visible_rows
         );
     }
+
+    #[test]
+    fn apply_saved_filter_by_name_finds_filter_or_reports_missing() {
+        let mut app = app_with_filter_state();
+        app.state.groups = vec!["/aws/lambda/a".to_string(), "/aws/lambda/b".to_string()];
+        app.state.all_groups = app.state.groups.clone();
+        app.state.saved_filters.push(SavedFilter {
+            name: "b-errors".to_string(),
+            group: "/aws/lambda/b".to_string(),
+            start: "-1h".to_string(),
+            end: "".to_string(),
+            query: "level=error".to_string(),
+            ..SavedFilter::default()
+        });
+
+        assert!(app.apply_saved_filter_by_name("b-errors").is_ok());
+        assert_eq!(app.state.selected_group, 1);
+        assert_eq!(app.state.filter_query, "level=error");
+
+        let err = app.apply_saved_filter_by_name("nope").unwrap_err();
+        assert!(err.contains("nope"));
+    }
+
+    fn named(name: &str, group: &str, last_used_ms: Option<i64>) -> SavedFilter {
+        SavedFilter {
+            name: name.to_string(),
+            group: group.to_string(),
+            last_used_ms,
+            ..SavedFilter::default()
+        }
+    }
+
+    #[test]
+    fn popup_search_and_sort_order_rows() {
+        let mut app = app_with_filter_state();
+        app.state.saved_filters = vec![
+            named("timeouts", "/aws/lambda/api", Some(100)),
+            named("Api-errors", "/aws/lambda/api", None),
+            named("batch", "/ecs/worker", Some(300)),
+        ];
+        app.open_load_filter_popup();
+        assert_eq!(app.load_filter_entries(), vec![1, 2, 0]);
+
+        app.handle_load_filter_popup_key(KeyCode::Char('s'));
+        assert_eq!(app.state.load_filter_sort, FilterSort::LastUsed);
+        assert_eq!(app.load_filter_entries(), vec![2, 0, 1]);
+
+        app.handle_load_filter_popup_key(KeyCode::Char('/'));
+        for c in "lambda".chars() {
+            app.handle_load_filter_popup_key(KeyCode::Char(c));
+        }
+        app.handle_load_filter_popup_key(KeyCode::Enter);
+        assert_eq!(app.load_filter_entries(), vec![0, 1]);
+        assert_eq!(app.state.load_filter_mode, FilterPopupMode::List);
+    }
+
+    #[test]
+    fn delete_needs_confirmation() {
+        let mut app = app_with_filter_state();
+        app.state.saved_filters = vec![named("a", "", None), named("b", "", None)];
+        app.open_load_filter_popup();
+
+        app.handle_load_filter_popup_key(KeyCode::Char('d'));
+        app.handle_load_filter_popup_key(KeyCode::Char('n'));
+        assert_eq!(app.state.saved_filters.len(), 2);
+
+        app.handle_load_filter_popup_key(KeyCode::Down);
+        app.handle_load_filter_popup_key(KeyCode::Char('d'));
+        assert_eq!(app.state.load_filter_mode, FilterPopupMode::ConfirmDelete);
+        app.handle_load_filter_popup_key(KeyCode::Char('y'));
+        let names: Vec<&str> = app
+            .state
+            .saved_filters
+            .iter()
+            .map(|f| f.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["a"]);
+        assert_eq!(app.state.load_filter_selected, 0);
+    }
+
+    #[test]
+    fn rename_rejects_taken_names_and_duplicate_gets_fresh_name() {
+        let mut app = app_with_filter_state();
+        app.state.saved_filters = vec![named("a", "g", Some(5)), named("b", "", None)];
+        app.state.saved_filters[0].use_count = 3;
+        app.open_load_filter_popup();
+
+        app.handle_load_filter_popup_key(KeyCode::Char('r'));
+        app.handle_load_filter_popup_key(KeyCode::Backspace);
+        app.handle_load_filter_popup_key(KeyCode::Char('b'));
+        app.handle_load_filter_popup_key(KeyCode::Enter);
+        assert_eq!(app.state.saved_filters[0].name, "a");
+        assert!(matches!(
+            app.state.load_filter_mode,
+            FilterPopupMode::Rename(_)
+        ));
+
+        app.handle_load_filter_popup_key(KeyCode::Char('2'));
+        app.handle_load_filter_popup_key(KeyCode::Enter);
+        assert_eq!(app.state.saved_filters[0].name, "b2");
+        assert_eq!(app.state.load_filter_mode, FilterPopupMode::List);
+
+        // "b2" sorts after "b", so it's the second row
+        assert_eq!(app.selected_filter_index(), Some(0));
+        app.handle_load_filter_popup_key(KeyCode::Char('c'));
+        let copy = &app.state.saved_filters[1];
+        assert_eq!(copy.name, "b2 copy");
+        assert_eq!(copy.group, "g");
+        assert_eq!((copy.use_count, copy.last_used_ms), (0, None));
+        assert_eq!(app.selected_filter_index(), Some(1));
+    }
+
+    #[test]
+    fn edit_in_place_and_usage_stats() {
+        let mut app = app_with_filter_state();
+        app.state.saved_filters = vec![named("a", "", None)];
+        app.open_load_filter_popup();
+
+        app.handle_load_filter_popup_key(KeyCode::Char('e'));
+        for c in "level=warn".chars() {
+            app.handle_load_filter_popup_key(KeyCode::Char(c));
+        }
+        app.handle_load_filter_popup_key(KeyCode::Tab);
+        for c in "/app".chars() {
+            app.handle_load_filter_popup_key(KeyCode::Char(c));
+        }
+        app.handle_load_filter_popup_key(KeyCode::Enter);
+        assert_eq!(app.state.saved_filters[0].query, "level=warn");
+        assert_eq!(app.state.saved_filters[0].group, "/app");
+
+        app.handle_load_filter_popup_key(KeyCode::Enter);
+        assert!(!app.state.load_filter_popup_open);
+        assert_eq!(app.state.filter_query, "level=warn");
+        assert_eq!(app.state.saved_filters[0].use_count, 1);
+        assert!(app.state.saved_filters[0].last_used_ms.is_some());
+    }
+
+    #[test]
+    fn shared_filters_are_read_only_but_can_be_copied() {
+        let mut app = app_with_filter_state();
+        app.state.saved_filters = vec![SavedFilter {
+            source: Some("team".to_string()),
+            ..named("oncall", "/app", None)
+        }];
+        app.open_load_filter_popup();
+
+        app.handle_load_filter_popup_key(KeyCode::Char('d'));
+        assert_eq!(app.state.load_filter_mode, FilterPopupMode::List);
+        assert!(
+            app.state
+                .status_message
+                .as_ref()
+                .unwrap()
+                .contains("read-only")
+        );
+
+        app.handle_load_filter_popup_key(KeyCode::Char('c'));
+        let copy = &app.state.saved_filters[1];
+        assert_eq!(copy.name, "oncall copy");
+        assert_eq!(copy.source, None);
+    }
+
+    #[test]
+    fn import_asks_about_clashing_names() {
+        let path = scratch_path("import-test.json");
+        let incoming = vec![
+            SavedFilter {
+                query: "level=error".to_string(),
+                ..named("errors", "/app", None)
+            },
+            named("same", "/app", None),
+            named("new", "", None),
+        ];
+        std::fs::write(&path, serde_json::to_string(&incoming).unwrap()).unwrap();
+
+        let mut app = app_with_filter_state();
+        app.state.saved_filters = vec![
+            SavedFilter {
+                use_count: 4,
+                ..named("errors", "/app", None)
+            },
+            named("same", "/app", None),
+        ];
+        app.open_load_filter_popup();
+        app.state.load_filter_mode = FilterPopupMode::Import(path.display().to_string());
+        app.handle_load_filter_popup_key(KeyCode::Enter);
+
+        let FilterPopupMode::ImportConflict { pending, .. } = &app.state.load_filter_mode else {
+            panic!("expected a conflict prompt");
+        };
+        assert_eq!(pending[0].name, "errors");
+
+        // Overwrite keeps the usage stats; the identical "same" is skipped quietly
+        app.handle_load_filter_popup_key(KeyCode::Char('o'));
+        assert_eq!(app.state.load_filter_mode, FilterPopupMode::List);
+        let names: Vec<&str> = app
+            .state
+            .saved_filters
+            .iter()
+            .map(|f| f.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["errors", "same", "new"]);
+        assert_eq!(app.state.saved_filters[0].query, "level=error");
+        assert_eq!(app.state.saved_filters[0].use_count, 4);
+        assert_eq!(
+            app.state.status_message.as_deref(),
+            Some("Imported 2 filter(s), 1 skipped")
+        );
+
+        // Once they differ again, keeping both imports it under a new name
+        app.state.saved_filters[0].query = "level=warn".to_string();
+        app.state.load_filter_mode = FilterPopupMode::Import(path.display().to_string());
+        app.handle_load_filter_popup_key(KeyCode::Enter);
+        app.handle_load_filter_popup_key(KeyCode::Char('b'));
+        let imported = app.state.saved_filters.last().unwrap();
+        assert_eq!(imported.name, "errors (imported)");
+        assert_eq!(imported.query, "level=error");
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn import_only_skips_filters_that_match_in_every_field() {
+        let path = scratch_path("import-same-test.json");
+        let incoming = vec![
+            SavedFilter {
+                groups: vec!["/app/worker".to_string()],
+                ..named("groups", "/app", None)
+            },
+            SavedFilter {
+                region: "us-east-1".to_string(),
+                ..named("region", "/app", None)
+            },
+            SavedFilter {
+                use_count: 9,
+                ..named("stats", "/app", None)
+            },
+        ];
+        std::fs::write(&path, serde_json::to_string(&incoming).unwrap()).unwrap();
+
+        let mut app = app_with_filter_state();
+        app.state.saved_filters = vec![
+            named("groups", "/app", None),
+            named("region", "/app", None),
+            named("stats", "/app", None),
+        ];
+        app.open_load_filter_popup();
+        app.state.load_filter_mode = FilterPopupMode::Import(path.display().to_string());
+        app.handle_load_filter_popup_key(KeyCode::Enter);
+        let _ = std::fs::remove_file(&path);
+
+        // Differing extra groups clash; so does a different region
+        let FilterPopupMode::ImportConflict { pending, .. } = &app.state.load_filter_mode else {
+            panic!("expected a conflict prompt");
+        };
+        assert_eq!(pending[0].name, "groups");
+        app.handle_load_filter_popup_key(KeyCode::Char('k'));
+        let FilterPopupMode::ImportConflict { pending, .. } = &app.state.load_filter_mode else {
+            panic!("expected a conflict prompt");
+        };
+        assert_eq!(pending[0].name, "region");
+        app.handle_load_filter_popup_key(KeyCode::Char('k'));
+
+        // Usage stats alone don't
+        assert_eq!(app.state.load_filter_mode, FilterPopupMode::List);
+        assert_eq!(
+            app.state.status_message.as_deref(),
+            Some("Imported 0 filter(s), 3 skipped")
+        );
+    }
+
+    #[test]
+    fn export_writes_marked_filters() {
+        let path = scratch_path("export-test.json");
+        let mut app = app_with_filter_state();
+        app.state.saved_filters = vec![
+            named("a", "", Some(1)),
+            named("b", "", None),
+            named("c", "", None),
+        ];
+        app.open_load_filter_popup();
+        app.handle_load_filter_popup_key(KeyCode::Char(' '));
+        app.handle_load_filter_popup_key(KeyCode::Down);
+        app.handle_load_filter_popup_key(KeyCode::Char(' '));
+        assert_eq!(app.state.load_filter_marked, BTreeSet::from([0, 2]));
+
+        app.handle_load_filter_popup_key(KeyCode::Char('x'));
+        // Never defaults to the shared library it might overwrite
+        assert_eq!(
+            app.state.load_filter_mode,
+            FilterPopupMode::Export(EXPORT_FILTERS_FILE.to_string())
+        );
+        app.state.load_filter_mode = FilterPopupMode::Export(path.display().to_string());
+        app.handle_load_filter_popup_key(KeyCode::Enter);
+
+        let written = crate::app::filter_library::read_filter_file(&path).unwrap();
+        let names: Vec<&str> = written.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "c"]);
+        assert_eq!(written[0].last_used_ms, None);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn multi_group_filter_binds_groups_and_reports_missing() {
+        let mut app = app_with_filter_state();
+        app.state.all_groups = vec![
+            "/app/api".to_string(),
+            "/app/worker".to_string(),
+            "/db".to_string(),
+        ];
+        app.state.groups = app.state.all_groups.clone();
+        app.state.saved_filters = vec![SavedFilter {
+            groups: vec!["/app/*".to_string(), "/cache".to_string()],
+            stream_prefixes: vec!["prod/".to_string()],
+            ..named("app", "/db", None)
+        }];
+
+        app.open_load_filter_popup();
+        app.handle_load_filter_popup_key(KeyCode::Enter);
+
+        assert_eq!(
+            app.state.filter_groups,
+            vec!["/db", "/app/api", "/app/worker"]
+        );
+        assert_eq!(app.state.filter_streams, vec!["prod/"]);
+        assert_eq!(app.state.selected_group, 2);
+        let status = app.state.status_message.clone().unwrap();
+        assert!(status.contains("3 groups"), "{status}");
+        assert!(status.contains("/cache"), "{status}");
+
+        // Picking another group by hand drops the binding
+        app.state.focus = Focus::Groups;
+        app.groups_up();
+        assert!(app.state.filter_groups.is_empty());
+    }
+
+    #[test]
+    fn filter_from_another_region_offers_to_switch() {
+        let mut app = app_with_filter_state();
+        app.state.saved_filters = vec![SavedFilter {
+            region: "us-east-1".to_string(),
+            query: "level=error".to_string(),
+            ..named("east", "/app/api", None)
+        }];
+
+        app.open_load_filter_popup();
+        app.handle_load_filter_popup_key(KeyCode::Enter);
+        assert!(app.state.context_switch_prompt.is_some());
+        assert_eq!(app.state.filter_query, "");
+
+        app.handle_context_switch_key(KeyCode::Char('y'));
+        assert_eq!(app.state.region, "us-east-1");
+        assert_eq!(app.state.profile, "test-profile");
+        assert!(app.state.pending_filter_load.is_some());
+
+        // The load finishes once the new region's groups arrive
+        app.apply_loaded_groups(Ok(vec!["/app/api".to_string()]));
+        assert!(app.state.pending_filter_load.is_none());
+        assert_eq!(app.state.filter_query, "level=error");
+        assert_eq!(app.state.all_groups[app.state.selected_group], "/app/api");
+    }
 }
@@ -0,0 +1,223 @@
+use chrono::{DateTime, Utc};
+
+use super::{App, FilterField};
+
+/// Upper bound on the number of buckets; the bucket size is picked so the
+/// search window fits in at most this many.
+pub const HISTOGRAM_MAX_BUCKETS: usize = 60;
+
+// "Nice" bucket sizes in milliseconds, smallest first.
+const BUCKET_SIZES_MS: [i64; 16] = [
+    1_000,
+    5_000,
+    10_000,
+    30_000,
+    60_000,
+    2 * 60_000,
+    5 * 60_000,
+    10 * 60_000,
+    15 * 60_000,
+    30 * 60_000,
+    60 * 60_000,
+    3 * 60 * 60_000,
+    6 * 60 * 60_000,
+    12 * 60 * 60_000,
+    24 * 60 * 60_000,
+    7 * 24 * 60 * 60_000,
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Histogram {
+    pub start_ms: i64,
+    pub bucket_ms: i64,
+    pub counts: Vec<usize>,
+}
+
+impl Histogram {
+    /// Bucket events with the given timestamps across `[start_ms, end_ms)`.
+    pub fn build(start_ms: i64, end_ms: i64, timestamps: impl Iterator<Item = i64>) -> Self {
+        let span = (end_ms - start_ms).max(1);
+        let bucket_ms = pick_bucket_ms(span, HISTOGRAM_MAX_BUCKETS);
+        let len = ((span + bucket_ms - 1) / bucket_ms).max(1) as usize;
+
+        let mut hist = Histogram {
+            start_ms,
+            bucket_ms,
+            counts: vec![0; len],
+        };
+        for ts in timestamps {
+            if let Some(idx) = hist.bucket_of(ts) {
+                hist.counts[idx] += 1;
+            }
+        }
+        hist
+    }
+
+    pub fn bucket_of(&self, ts: i64) -> Option<usize> {
+        if ts < self.start_ms {
+            return None;
+        }
+        let idx = ((ts - self.start_ms) / self.bucket_ms) as usize;
+        (idx < self.counts.len()).then_some(idx)
+    }
+
+    /// `[start, end)` of bucket `idx` in epoch millis.
+    pub fn bucket_range(&self, idx: usize) -> (i64, i64) {
+        let start = self.start_ms + idx as i64 * self.bucket_ms;
+        (start, start + self.bucket_ms)
+    }
+
+    pub fn max_count(&self) -> usize {
+        self.counts.iter().copied().max().unwrap_or(0)
+    }
+
+    /// Human label for the bucket size, e.g. "30s", "5m", "1h", "1d".
+    pub fn bucket_label(&self) -> String {
+        let secs = self.bucket_ms / 1_000;
+        if secs % 86_400 == 0 {
+            format!("{}d", secs / 86_400)
+        } else if secs % 3_600 == 0 {
+            format!("{}h", secs / 3_600)
+        } else if secs % 60 == 0 {
+            format!("{}m", secs / 60)
+        } else {
+            format!("{secs}s")
+        }
+    }
+}
+
+fn pick_bucket_ms(span_ms: i64, max_buckets: usize) -> i64 {
+    let max_buckets = max_buckets.max(1) as i64;
+    BUCKET_SIZES_MS
+        .iter()
+        .copied()
+        .find(|size| (span_ms + size - 1) / size <= max_buckets)
+        .unwrap_or_else(|| {
+            // Very long windows: whole days, enough of them to fit.
+            let day = 24 * 60 * 60_000;
+            let days = (span_ms / max_buckets + day - 1) / day;
+            days.max(1) * day
+        })
+}
+
+impl App {
+    /// Histogram of the events currently in Results, or None when there are none.
+    pub fn histogram(&self) -> Option<Histogram> {
+        let mut timestamps = self
+            .state
+            .line_events
+            .iter()
+            .flatten()
+            .map(|ev| ev.timestamp_ms)
+            .peekable();
+        timestamps.peek()?;
+
+        let (first, last) = self
+            .state
+            .line_events
+            .iter()
+            .flatten()
+            .fold((i64::MAX, i64::MIN), |(lo, hi), ev| {
+                (lo.min(ev.timestamp_ms), hi.max(ev.timestamp_ms))
+            });
+
+        // Tailing pushes events past the original end; grow the window to fit.
+        let (start_ms, end_ms) = match self.state.search_window {
+            Some((start, end)) => (start.min(first), end.max(last + 1)),
+            None => (first, last + 1),
+        };
+
+        Some(Histogram::build(start_ms, end_ms, timestamps))
+    }
+
+    /// Bucket containing the event under the Results cursor.
+    pub fn current_histogram_bucket(&self, hist: &Histogram) -> Option<usize> {
+        self.current_event()
+            .and_then(|ev| hist.bucket_of(ev.timestamp_ms))
+    }
+
+    pub fn histogram_move(&mut self, delta: isize) {
+        let Some(hist) = self.histogram() else {
+            return;
+        };
+        let from = self
+            .state
+            .histogram_selected
+            .or_else(|| self.current_histogram_bucket(&hist))
+            .unwrap_or(0);
+        let last = hist.counts.len() - 1;
+        self.state.histogram_selected = Some(from.saturating_add_signed(delta).min(last));
+    }
+
+    /// Narrow Start/End to the selected (or current) bucket. Returns false
+    /// when there is nothing to zoom into.
+    pub fn zoom_to_histogram_bucket(&mut self) -> bool {
+        let Some(hist) = self.histogram() else {
+            return false;
+        };
+        let Some(idx) = self
+            .state
+            .histogram_selected
+            .or_else(|| self.current_histogram_bucket(&hist))
+        else {
+            return false;
+        };
+
+        let (start, end) = hist.bucket_range(idx);
+        let fmt = |ms: i64| {
+            DateTime::<Utc>::from_timestamp_millis(ms)
+                .map(|dt| dt.to_rfc3339())
+                .unwrap_or_default()
+        };
+        self.state.filter_start = fmt(start);
+        self.state.filter_end = fmt(end);
+        self.state.filter_field = FilterField::Query;
+        self.state.histogram_selected = None;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_smallest_bucket_that_fits() {
+        // 15 minutes -> 30s buckets (30 of them)
+        assert_eq!(pick_bucket_ms(15 * 60_000, 60), 30_000);
+        // 1 hour -> 1m buckets
+        assert_eq!(pick_bucket_ms(60 * 60_000, 60), 60_000);
+        // just over an hour no longer fits in 60 x 1m
+        assert_eq!(pick_bucket_ms(61 * 60_000, 60), 2 * 60_000);
+        // 30 seconds -> 1s buckets
+        assert_eq!(pick_bucket_ms(30_000, 60), 1_000);
+    }
+
+    #[test]
+    fn very_long_windows_fall_back_to_whole_days() {
+        let day = 24 * 60 * 60_000;
+        let bucket = pick_bucket_ms(3650 * day, 60);
+        assert_eq!(bucket % day, 0);
+        assert!(3650 * day / bucket <= 60);
+    }
+
+    #[test]
+    fn build_counts_events_per_bucket() {
+        let hist = Histogram::build(0, 60_000, [0, 500, 1_000, 59_999, 70_000].into_iter());
+        assert_eq!(hist.bucket_ms, 1_000);
+        assert_eq!(hist.counts.len(), 60);
+        assert_eq!(hist.counts[0], 2);
+        assert_eq!(hist.counts[1], 1);
+        assert_eq!(hist.counts[59], 1);
+        // Out-of-window timestamps are ignored
+        assert_eq!(hist.counts.iter().sum::<usize>(), 4);
+        assert_eq!(hist.max_count(), 2);
+    }
+
+    #[test]
+    fn bucket_range_and_label() {
+        let hist = Histogram::build(1_000, 1_000 + 15 * 60_000, std::iter::empty());
+        assert_eq!(hist.bucket_range(2), (61_000, 91_000));
+        assert_eq!(hist.bucket_label(), "30s");
+    }
+}
@@ -0,0 +1,398 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use chrono::Utc;
+use ratatui::crossterm::event::KeyCode;
+use serde::{Deserialize, Serialize};
+
+use super::filters::config_file_path;
+use super::session::write_atomic;
+use super::{App, FilterField};
+use crate::aws::normalize_filter_pattern;
+
+/// Entries kept on disk and in memory; older ones are dropped.
+pub const MAX_HISTORY: usize = 1000;
+
+/// One executed search.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct HistoryEntry {
+    pub group: String,
+    /// Context `group` was searched in; empty in entries from before
+    /// contexts were recorded.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub profile: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub region: String,
+    pub start: String,
+    pub end: String,
+    /// The pattern as sent to CloudWatch (after shorthand normalization).
+    pub query: String,
+    /// When the search ran (epoch ms).
+    pub timestamp_ms: i64,
+    pub result_count: usize,
+}
+
+pub fn history_path() -> Result<PathBuf, String> {
+    config_file_path("history.ndjson")
+}
+
+/// History from `path`, oldest first. Lines that don't parse are skipped
+/// rather than losing the whole file.
+pub fn load_history(path: &Path) -> Result<Vec<HistoryEntry>, String> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let data = std::fs::read_to_string(path)
+        .map_err(|e| format!("read_to_string {}: {e}", path.display()))?;
+    Ok(parse_history(&data))
+}
+
+pub fn parse_history(data: &str) -> Vec<HistoryEntry> {
+    let mut entries: Vec<HistoryEntry> = data
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    let excess = entries.len().saturating_sub(MAX_HISTORY);
+    entries.drain(0..excess);
+    entries
+}
+
+fn append_history(path: &Path, entry: &HistoryEntry) -> Result<(), String> {
+    let line = serde_json::to_string(entry).map_err(|e| format!("encode: {e}"))?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("open {}: {e}", path.display()))?;
+    writeln!(file, "{line}").map_err(|e| format!("write {}: {e}", path.display()))
+}
+
+fn rewrite_history(path: &Path, entries: &[HistoryEntry]) -> Result<(), String> {
+    let mut data = String::new();
+    for entry in entries {
+        data.push_str(&serde_json::to_string(entry).map_err(|e| format!("encode: {e}"))?);
+        data.push('\n');
+    }
+    write_atomic(path, &data)
+}
+
+impl App {
+    /// Remember the search that's about to run for Groups pane `entry`; it
+    /// is recorded once its result count arrives.
+    pub fn begin_history_entry(&mut self, entry: &str, start: &str, end: &str, query: &str) {
+        let (profile, region, group) = self.group_target(entry);
+        self.state.history_pending = Some(HistoryEntry {
+            group,
+            profile,
+            region,
+            start: start.to_string(),
+            end: end.to_string(),
+            query: normalize_filter_pattern(query),
+            timestamp_ms: Utc::now().timestamp_millis(),
+            result_count: 0,
+        });
+    }
+
+    pub fn record_history(&mut self, result_count: usize) {
+        let Some(mut entry) = self.state.history_pending.take() else {
+            return;
+        };
+        entry.result_count = result_count;
+        self.state.history.push(entry.clone());
+
+        let Some(path) = self.state.history_path.clone() else {
+            return;
+        };
+        // Append normally; compact the file once it has grown well past the cap
+        let result = if self.state.history.len() > MAX_HISTORY + MAX_HISTORY / 5 {
+            let excess = self.state.history.len() - MAX_HISTORY;
+            self.state.history.drain(0..excess);
+            rewrite_history(&path, &self.state.history)
+        } else {
+            append_history(&path, &entry)
+        };
+        if let Err(e) = result {
+            self.state.status_message = Some(format!("Couldn't save history: {e}"));
+            self.state.status_set_at = Some(Instant::now());
+        }
+    }
+
+    /// Distinct non-empty queries, newest first, for Up/Down recall.
+    pub fn recall_queries(&self) -> Vec<&str> {
+        let mut out: Vec<&str> = Vec::new();
+        for entry in self.state.history.iter().rev() {
+            if !entry.query.is_empty() && !out.contains(&entry.query.as_str()) {
+                out.push(&entry.query);
+            }
+        }
+        out
+    }
+
+    /// Up (`older`) / Down while editing Query: step through past queries;
+    /// stepping past the newest one restores what was being typed.
+    pub fn recall_history(&mut self, older: bool) {
+        let queries = self.recall_queries();
+        if queries.is_empty() {
+            return;
+        }
+        let next = match (self.state.history_recall, older) {
+            (None, false) => return,
+            (None, true) => Some(0),
+            (Some(i), true) => Some((i + 1).min(queries.len() - 1)),
+            (Some(0), false) => None,
+            (Some(i), false) => Some(i - 1),
+        };
+        let text = next.map(|i| queries[i].to_string());
+
+        if self.state.history_recall.is_none() {
+            self.state.history_draft = self.state.filter_query.clone();
+        }
+        self.state.filter_query = text.unwrap_or_else(|| self.state.history_draft.clone());
+        self.state.filter_cursor_pos = self.state.filter_query.len();
+        self.state.history_recall = next;
+    }
+
+    /// History popup rows: newest first, fuzzy-filtered on group and query.
+    pub fn history_popup_entries(&self) -> Vec<&HistoryEntry> {
+        self.state
+            .history
+            .iter()
+            .rev()
+            .filter(|e| {
+                Self::fuzzy_match(
+                    &format!("{} {}", e.group, e.query),
+                    &self.state.history_popup_input,
+                )
+            })
+            .collect()
+    }
+
+    pub fn open_history_popup(&mut self) {
+        if self.state.history.is_empty() {
+            self.state.status_message = Some("No search history yet".to_string());
+            self.state.status_set_at = Some(Instant::now());
+            return;
+        }
+        self.state.history_popup_input.clear();
+        self.state.history_popup_selected = 0;
+        self.state.history_popup_open = true;
+    }
+
+    pub fn handle_history_popup_key(&mut self, code: KeyCode) {
+        let count = self.history_popup_entries().len();
+        match code {
+            KeyCode::Esc => {
+                self.state.history_popup_open = false;
+            }
+            KeyCode::Up => {
+                self.state.history_popup_selected =
+                    self.state.history_popup_selected.saturating_sub(1);
+            }
+            KeyCode::Down if self.state.history_popup_selected + 1 < count => {
+                self.state.history_popup_selected += 1;
+            }
+            KeyCode::Enter => {
+                let entry = self
+                    .history_popup_entries()
+                    .get(self.state.history_popup_selected)
+                    .map(|e| (*e).clone());
+                self.state.history_popup_open = false;
+                if let Some(entry) = entry {
+                    self.apply_history_entry(&entry);
+                }
+            }
+            KeyCode::Backspace => {
+                self.state.history_popup_input.pop();
+                self.state.history_popup_selected = 0;
+            }
+            KeyCode::Char(c) if !c.is_control() => {
+                self.state.history_popup_input.push(c);
+                self.state.history_popup_selected = 0;
+            }
+            _ => {}
+        }
+    }
+
+    fn apply_history_entry(&mut self, entry: &HistoryEntry) {
+        self.state.filter_start = entry.start.clone();
+        self.state.filter_end = entry.end.clone();
+        self.state.filter_query = entry.query.clone();
+        self.state.filter_field = FilterField::Query;
+
+        let (profile, region) = if entry.profile.is_empty() && entry.region.is_empty() {
+            (self.state.profile.clone(), self.state.region.clone())
+        } else {
+            (entry.profile.clone(), entry.region.clone())
+        };
+        let message =
+            if entry.group.is_empty() || self.select_group_in(&profile, &region, &entry.group) {
+                "Loaded search from history".to_string()
+            } else {
+                format!("Loaded search; group {} is not in this list", entry.group)
+            };
+        self.state.status_message = Some(message);
+        self.state.status_set_at = Some(Instant::now());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::state::AppState;
+    use crate::app::{App, Focus, SearchMsg};
+
+    use ratatui::crossterm::event::{KeyEvent, KeyModifiers};
+
+    fn app_with_groups(groups: Vec<&str>) -> App {
+        let groups: Vec<String> = groups.into_iter().map(String::from).collect();
+
+        let state = AppState {
+            all_groups: groups.clone(),
+            groups,
+            focus: Focus::Filter,
+            ..AppState::for_test()
+        };
+
+        App::for_test(state)
+    }
+
+    fn entry(group: &str, query: &str) -> HistoryEntry {
+        HistoryEntry {
+            group: group.to_string(),
+            profile: String::new(),
+            region: String::new(),
+            start: "-1h".to_string(),
+            end: String::new(),
+            query: query.to_string(),
+            timestamp_ms: 0,
+            result_count: 3,
+        }
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::empty())
+    }
+
+    #[test]
+    fn records_normalized_query_with_count() {
+        let mut app = app_with_groups(vec!["api"]);
+        app.begin_history_entry("api", "-15m", "", "level=error");
+        // Nothing is recorded until the count arrives
+        assert!(app.state.history.is_empty());
+
+        app.record_history(42);
+        let recorded = &app.state.history[0];
+        assert_eq!(recorded.group, "api");
+        assert_eq!(recorded.query, normalize_filter_pattern("level=error"));
+        assert_eq!(recorded.result_count, 42);
+        assert!(app.state.history_pending.is_none());
+    }
+
+    #[test]
+    fn tagged_group_is_recorded_with_its_context() {
+        let mut app = app_with_groups(vec!["[prod/eu-west-1] api", "[dr/us-east-1] api"]);
+        app.state.settings.contexts = vec![
+            ("prod".to_string(), "eu-west-1".to_string()),
+            ("dr".to_string(), "us-east-1".to_string()),
+        ];
+        app.begin_history_entry("[dr/us-east-1] api", "", "", "x");
+        app.record_history(1);
+        let recorded = app.state.history[0].clone();
+        assert_eq!(
+            (
+                recorded.group.as_str(),
+                recorded.profile.as_str(),
+                recorded.region.as_str()
+            ),
+            ("api", "dr", "us-east-1")
+        );
+
+        app.apply_history_entry(&recorded);
+        assert_eq!(app.state.selected_group, 1);
+    }
+
+    #[test]
+    fn fetched_message_completes_the_entry() {
+        let mut app = app_with_groups(vec!["api"]);
+        app.begin_history_entry("api", "", "", "x");
+        app.search_tx.send(SearchMsg::Fetched(7)).unwrap();
+        app.drain_search_messages();
+        assert_eq!(app.state.history[0].result_count, 7);
+    }
+
+    #[test]
+    fn up_down_recall_while_editing_query() {
+        let mut app = app_with_groups(vec!["api"]);
+        app.state.history = vec![
+            entry("api", "one"),
+            entry("api", "two"),
+            entry("api", "two"),
+        ];
+        app.state.editing = true;
+        app.state.filter_query = "draft".to_string();
+
+        app.handle_key_event(key(KeyCode::Up)).unwrap();
+        assert_eq!(app.state.filter_query, "two");
+        app.handle_key_event(key(KeyCode::Up)).unwrap();
+        assert_eq!(app.state.filter_query, "one");
+        // Already at the oldest distinct query
+        app.handle_key_event(key(KeyCode::Up)).unwrap();
+        assert_eq!(app.state.filter_query, "one");
+        app.handle_key_event(key(KeyCode::Down)).unwrap();
+        app.handle_key_event(key(KeyCode::Down)).unwrap();
+        assert_eq!(app.state.filter_query, "draft");
+        assert_eq!(app.state.filter_cursor_pos, 5);
+    }
+
+    #[test]
+    fn popup_fuzzy_filters_and_loads() {
+        let mut app = app_with_groups(vec!["api", "worker"]);
+        app.state.editing = false;
+        app.state.history = vec![entry("worker", "timeout"), entry("api", "level=error")];
+
+        app.handle_key_event(key(KeyCode::Char('H'))).unwrap();
+        assert!(app.state.history_popup_open);
+        // Newest first
+        assert_eq!(app.history_popup_entries()[0].group, "api");
+
+        for c in "wkto".chars() {
+            app.handle_key_event(key(KeyCode::Char(c))).unwrap();
+        }
+        assert_eq!(app.history_popup_entries().len(), 1);
+
+        app.handle_key_event(key(KeyCode::Enter)).unwrap();
+        assert!(!app.state.history_popup_open);
+        assert_eq!(app.state.selected_group, 1);
+        assert_eq!(app.state.filter_query, "timeout");
+        assert_eq!(app.state.filter_start, "-1h");
+    }
+
+    #[test]
+    fn recorded_entries_are_appended_to_the_history_file() {
+        let path = config_file_path("history-record-test.ndjson").unwrap();
+        let _ = std::fs::remove_file(&path);
+        let mut app = app_with_groups(vec!["api"]);
+        app.state.history_path = Some(path.clone());
+
+        app.begin_history_entry("api", "-1h", "", "one");
+        app.record_history(1);
+        app.begin_history_entry("api", "-1h", "", "two");
+        app.record_history(2);
+
+        let saved = load_history(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(saved, app.state.history);
+        assert_eq!(saved[1].result_count, 2);
+    }
+
+    #[test]
+    fn parse_skips_bad_lines_and_caps_length() {
+        let good = serde_json::to_string(&entry("api", "q")).unwrap();
+        let data = format!("{good}\nnot json\n{good}\n");
+        assert_eq!(parse_history(&data).len(), 2);
+
+        let many = format!("{good}\n").repeat(MAX_HISTORY + 5);
+        assert_eq!(parse_history(&many).len(), MAX_HISTORY);
+    }
+}
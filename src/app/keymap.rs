@@ -1,5 +1,4 @@
 use super::{App, FilterField, Focus};
-use crate::ui::styles::Theme;
 use ratatui::crossterm::event::{KeyCode, KeyEventKind};
 use std::io;
 
@@ -12,7 +11,20 @@ impl App {
             return Ok(());
         }
 
-        // While a popup is open, route keys to the popup handlers first.
+        // While a popup is open, route keys to the popup handlers first. An
+        // MFA prompt beats the rest: a fetch is waiting on it.
+        if self.state.mfa_prompt.is_some() {
+            self.handle_mfa_prompt_key(key_event.code);
+            return Ok(());
+        }
+        if self.state.filter_vars_prompt.is_some() {
+            self.handle_filter_vars_key(key_event.code);
+            return Ok(());
+        }
+        if self.state.context_switch_prompt.is_some() {
+            self.handle_context_switch_key(key_event.code);
+            return Ok(());
+        }
         if self.state.save_filter_popup_open {
             self.handle_save_filter_popup_key(key_event.code);
             return Ok(());
@@ -21,6 +33,38 @@ impl App {
             self.handle_load_filter_popup_key(key_event.code);
             return Ok(());
         }
+        if self.state.field_panel_open {
+            self.handle_field_panel_key(key_event.code);
+            return Ok(());
+        }
+        if self.state.value_picker_open {
+            self.handle_value_picker_key(key_event.code);
+            return Ok(());
+        }
+        if self.state.export_popup_open {
+            self.handle_export_popup_key(key_event.code);
+            return Ok(());
+        }
+        if self.state.pipe_menu_open {
+            self.handle_pipe_menu_key(key_event.code);
+            return Ok(());
+        }
+        if self.state.copy_menu_open {
+            self.handle_copy_menu_key(key_event.code);
+            return Ok(());
+        }
+        if self.state.import_url_popup_open {
+            self.handle_import_url_popup_key(key_event.code);
+            return Ok(());
+        }
+        if self.state.history_popup_open {
+            self.handle_history_popup_key(key_event.code);
+            return Ok(());
+        }
+        if self.state.context_picker.is_some() {
+            self.handle_context_picker_key(key_event.code);
+            return Ok(());
+        }
 
         match key_event.code {
             // q should NOT quit while editing or while group search is active
@@ -54,7 +98,14 @@ impl App {
                     self.apply_group_search_filter();
                     return Ok(());
                 }
+                // A second Esc (not editing) dismisses the auth banner
+                if !self.state.editing {
+                    self.state.auth_banner = None;
+                }
                 self.state.editing = false;
+                self.state.history_recall = None;
+                self.state.histogram_selected = None;
+                self.state.selection_anchor = None;
             }
 
             // While group search is active: handle its input
@@ -79,10 +130,8 @@ impl App {
 
             // === Filter editing logic ===
             // Move cursor within the active field
-            KeyCode::Left if self.state.editing => {
-                if self.state.filter_cursor_pos > 0 {
-                    self.state.filter_cursor_pos -= 1;
-                }
+            KeyCode::Left if self.state.editing && self.state.filter_cursor_pos > 0 => {
+                self.state.filter_cursor_pos -= 1;
             }
             KeyCode::Right if self.state.editing => {
                 let len = self.active_field_len();
@@ -106,14 +155,12 @@ impl App {
             }
 
             // Insert char at cursor
-            KeyCode::Char(c) if self.state.editing => {
-                if !c.is_control() {
-                    let idx = self.state.filter_cursor_pos;
-                    let field = self.active_field_mut();
-                    if idx <= field.len() {
-                        field.insert(idx, c);
-                        self.state.filter_cursor_pos += 1;
-                    }
+            KeyCode::Char(c) if self.state.editing && !c.is_control() => {
+                let idx = self.state.filter_cursor_pos;
+                let field = self.active_field_mut();
+                if idx <= field.len() {
+                    field.insert(idx, c);
+                    self.state.filter_cursor_pos += 1;
                 }
             }
 
@@ -130,9 +177,20 @@ impl App {
                         self.state.filter_cursor_pos = self.active_field_len();
                     }
                     self.state.editing = !self.state.editing;
+                    self.state.history_recall = None;
                 }
             }
 
+            // Recall past queries while editing Query
+            KeyCode::Up if self.state.editing && self.state.filter_field == FilterField::Query => {
+                self.recall_history(true);
+            }
+            KeyCode::Down
+                if self.state.editing && self.state.filter_field == FilterField::Query =>
+            {
+                self.recall_history(false);
+            }
+
             // Navigation when NOT editing
             KeyCode::Up if !self.state.editing => match self.state.focus {
                 Focus::Groups => self.groups_up(),
@@ -150,6 +208,57 @@ impl App {
                 self.copy_results_to_clipboard();
             }
 
+            // Copy menu for the event under the cursor (Results pane)
+            KeyCode::Char('Y') if !self.state.editing && self.state.focus == Focus::Results => {
+                self.open_copy_menu();
+            }
+
+            // Field discovery panel (Results pane, not editing)
+            KeyCode::Char('f') if !self.state.editing && self.state.focus == Focus::Results => {
+                self.open_field_panel();
+            }
+
+            // Filter by a value of the event under the cursor (Results pane)
+            KeyCode::Char('v') if !self.state.editing && self.state.focus == Focus::Results => {
+                self.open_value_picker();
+            }
+
+            // Start/clear a selection of entries (Results pane)
+            KeyCode::Char('V') if !self.state.editing && self.state.focus == Focus::Results => {
+                self.toggle_selection();
+            }
+
+            // Export results (or the selection) to a file
+            KeyCode::Char('e') if !self.state.editing && self.state.focus == Focus::Results => {
+                self.open_export_popup();
+            }
+
+            // Suspend the TUI: page results, edit the current event, or pipe
+            // either into a configured command (Results pane)
+            KeyCode::Char('p') if !self.state.editing && self.state.focus == Focus::Results => {
+                self.open_in_pager();
+            }
+            KeyCode::Char('o') if !self.state.editing && self.state.focus == Focus::Results => {
+                self.open_event_in_editor();
+            }
+            KeyCode::Char('|') if !self.state.editing && self.state.focus == Focus::Results => {
+                self.open_pipe_menu();
+            }
+
+            // Histogram: pick a bucket and zoom the time filter into it
+            KeyCode::Char('[') if !self.state.editing && self.state.focus == Focus::Results => {
+                self.histogram_move(-1);
+            }
+            KeyCode::Char(']') if !self.state.editing && self.state.focus == Focus::Results => {
+                self.histogram_move(1);
+            }
+            KeyCode::Char('z') if !self.state.editing && self.state.focus == Focus::Results => {
+                let zoomed = self.zoom_to_histogram_bucket();
+                if zoomed {
+                    self.start_search();
+                }
+            }
+
             // Toggle tail mode
             KeyCode::Char('t') if !self.state.editing && !self.state.group_search_active => {
                 self.state.tail_mode = !self.state.tail_mode;
@@ -168,6 +277,36 @@ impl App {
                 self.open_save_filter_popup();
             }
 
+            // Console links: copy one for the current query, or import one
+            KeyCode::Char('L') if !self.state.editing && !self.state.group_search_active => {
+                self.copy_query_console_link();
+            }
+            KeyCode::Char('U') if !self.state.editing && !self.state.group_search_active => {
+                self.open_import_url_popup();
+            }
+
+            // Search history popup (any focus, not editing)
+            KeyCode::Char('H') if !self.state.editing && !self.state.group_search_active => {
+                self.open_history_popup();
+            }
+
+            // Log in again / retry after an auth failure
+            KeyCode::Char('A')
+                if !self.state.editing
+                    && !self.state.group_search_active
+                    && self.state.auth_banner.is_some() =>
+            {
+                self.reauthenticate();
+            }
+
+            // Switch AWS profile / region (any focus, not editing)
+            KeyCode::Char('P') if !self.state.editing && !self.state.group_search_active => {
+                self.open_profile_picker();
+            }
+            KeyCode::Char('R') if !self.state.editing && !self.state.group_search_active => {
+                self.open_region_picker();
+            }
+
             // Open "Load filter" popup (any focus, not editing)
             KeyCode::Char('F') if !self.state.editing && !self.state.group_search_active => {
                 self.open_load_filter_popup();
@@ -204,16 +343,7 @@ impl App {
             }
 
             KeyCode::Char('T') if !self.state.editing => {
-                if self.state.theme_name == "dark" {
-                    self.state.theme = Theme::light();
-                    self.state.theme_name = "light".to_string();
-                } else if self.state.theme_name == "light" {
-                    self.state.theme = Theme::green();
-                    self.state.theme_name = "green".to_string();
-                } else {
-                    self.state.theme = Theme::default_dark();
-                    self.state.theme_name = "dark".to_string();
-                }
+                self.cycle_theme();
             }
 
             _ => {}
@@ -226,72 +356,22 @@ impl App {
 #[cfg(test)]
 mod tests {
     use crate::app::state::AppState;
-    use crate::app::{App, FilterField, Focus};
+    use crate::app::{App, Focus};
     use crate::ui::styles::Theme;
     use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
-    use std::sync::atomic::AtomicBool;
-
-    use std::sync::{Arc, mpsc};
-    use std::time::Instant as StdInstant;
 
     fn key(code: KeyCode) -> KeyEvent {
         KeyEvent::new(code, KeyModifiers::NONE)
     }
 
     fn app_with_filter_query(query: &str) -> App {
-        let (tx, rx) = mpsc::channel();
-
         let state = AppState {
-            app_title: "Test".to_string(),
-            theme: Theme::default_dark(),
-            theme_name: "dark".to_string(),
-            lines: Vec::new(),
-            filter_cursor_pos: 0,
-
-            all_groups: Vec::new(),
-            groups: Vec::new(),
-            selected_group: 0,
-            groups_scroll: 0,
-
-            profile: "test-profile".to_string(),
-            region: "eu-west-1".to_string(),
             focus: Focus::Filter,
-
-            filter_start: String::new(),
-            filter_end: String::new(),
             filter_query: query.to_string(),
-            filter_field: FilterField::Query,
-            editing: false,
-            cursor_on: true,
-            last_blink: StdInstant::now(),
-
-            group_search_active: false,
-            group_search_input: String::new(),
-
-            searching: false,
-            dots: 0,
-            last_dots: StdInstant::now(),
-            results_scroll: 0,
-
-            tail_mode: false,
-
-            status_message: None,
-            status_set_at: None,
-
-            saved_filters: Vec::new(),
-            save_filter_popup_open: false,
-            save_filter_name: String::new(),
-            load_filter_popup_open: false,
-            load_filter_selected: 0,
+            ..AppState::for_test()
         };
 
-        App {
-            state,
-            exit: false,
-            search_tx: tx,
-            search_rx: rx,
-            tail_stop: Arc::new(AtomicBool::new(false)),
-        }
+        App::for_test(state)
     }
 
     #[test]
@@ -337,4 +417,140 @@ mod tests {
         app.handle_key_event(key(KeyCode::Char('T'))).unwrap();
         assert_eq!(app.state.theme_name, "dark");
     }
+
+    #[test]
+    fn theme_cycle_includes_user_themes_but_not_with_no_color() {
+        let mut app = app_with_filter_query("");
+        app.state.user_themes = vec![("nord".to_string(), Theme::default_dark())];
+        app.state.theme_name = "green".to_string();
+
+        app.handle_key_event(key(KeyCode::Char('T'))).unwrap();
+        assert_eq!(app.state.theme_name, "nord");
+        app.handle_key_event(key(KeyCode::Char('T'))).unwrap();
+        assert_eq!(app.state.theme_name, "dark");
+
+        app.state.theme_name = crate::ui::styles::NO_COLOR_THEME.to_string();
+        app.handle_key_event(key(KeyCode::Char('T'))).unwrap();
+        assert_eq!(app.state.theme_name, "no-color");
+        assert!(app.state.status_message.is_some());
+    }
+
+    #[test]
+    fn region_picker_switches_and_keeps_the_search() {
+        let mut app = app_with_filter_query("level=error");
+        app.state.groups = vec!["api".to_string(), "worker".to_string()];
+        app.state.all_groups = app.state.groups.clone();
+        app.state.selected_group = 1;
+        app.state.focus = Focus::Groups;
+
+        app.handle_key_event(key(KeyCode::Char('R'))).unwrap();
+        let picker = app.state.context_picker.as_ref().unwrap();
+        assert_eq!(picker.items[picker.selected], "eu-west-1");
+
+        for c in "useast2".chars() {
+            app.handle_key_event(key(KeyCode::Char(c))).unwrap();
+        }
+        assert_eq!(app.context_picker_entries(), vec!["us-east-2"]);
+        app.handle_key_event(key(KeyCode::Enter)).unwrap();
+
+        assert!(app.state.context_picker.is_none());
+        assert_eq!(app.state.region, "us-east-2");
+        assert_eq!(app.state.profile, "test-profile");
+        assert_eq!(app.state.filter_query, "level=error");
+        assert!(app.tail_stop.load(std::sync::atomic::Ordering::Relaxed));
+        // Picked again once the new region's groups arrive
+        assert_eq!(app.state.pending_group_select.as_deref(), Some("worker"));
+        app.apply_loaded_groups(Ok(vec!["worker".to_string(), "x".to_string()]));
+        assert_eq!(app.state.selected_group, 0);
+        assert_eq!(app.state.groups[0], "worker");
+
+        // Esc leaves everything as it was
+        app.handle_key_event(key(KeyCode::Char('R'))).unwrap();
+        app.handle_key_event(key(KeyCode::Esc)).unwrap();
+        assert!(app.state.context_picker.is_none());
+        assert_eq!(app.state.region, "us-east-2");
+    }
+
+    #[test]
+    fn auth_banner_logs_in_then_retries() {
+        use crate::app::auth::AuthBanner;
+        use crate::app::external::ExternalInput;
+        use crate::aws::{AuthErrorKind, AuthFailure};
+
+        let mut app = app_with_filter_query("");
+        app.state.all_groups = vec!["(error fetching log groups: expired)".to_string()];
+        app.state.groups = app.state.all_groups.clone();
+        let failure = AuthFailure {
+            kind: AuthErrorKind::Expired,
+            profile: "prod".to_string(),
+            region: "eu-west-1".to_string(),
+            message: "expired".to_string(),
+        };
+        app.state.auth_banner = Some(AuthBanner::new(failure, Some("prod".to_string())));
+
+        app.handle_key_event(key(KeyCode::Char('A'))).unwrap();
+        let cmd = app.state.pending_external.take().unwrap();
+        assert_eq!(cmd.command, "aws sso login --profile 'prod'");
+        assert_eq!(cmd.input, ExternalInput::Terminal);
+        assert!(app.state.auth_retry);
+
+        // A failed login keeps the banner up for another try
+        app.finish_login(false);
+        assert!(app.state.auth_banner.is_some());
+        assert!(!app.state.auth_retry);
+
+        app.handle_key_event(key(KeyCode::Char('A'))).unwrap();
+        app.finish_login(true);
+        assert!(app.state.auth_banner.is_none());
+        // The failed group list is fetched again
+        assert_eq!(app.state.groups, vec!["(loading log groups...)"]);
+
+        // Esc dismisses a banner (once not editing)
+        app.state.auth_banner = Some(AuthBanner::new(
+            AuthFailure {
+                kind: AuthErrorKind::AccessDenied,
+                profile: String::new(),
+                region: "eu-west-1".to_string(),
+                message: String::new(),
+            },
+            None,
+        ));
+        app.handle_key_event(key(KeyCode::Esc)).unwrap();
+        assert!(app.state.auth_banner.is_none());
+    }
+
+    #[test]
+    fn mfa_prompt_answers_the_waiting_fetch() {
+        use crate::app::auth::tui_mfa_prompt;
+
+        let mut app = app_with_filter_query("");
+        let prompt = tui_mfa_prompt(app.search_tx.clone());
+        let waiting = std::thread::spawn({
+            let prompt = prompt.clone();
+            move || prompt("app-prod", "arn:aws:iam::111111111111:mfa/me")
+        });
+        while app.state.mfa_prompt.is_none() {
+            app.drain_search_messages();
+        }
+        assert_eq!(app.state.mfa_prompt.as_ref().unwrap().role, "app-prod");
+
+        // Digits only, and Enter needs some
+        app.handle_key_event(key(KeyCode::Enter)).unwrap();
+        for c in "12q3456".chars() {
+            app.handle_key_event(key(KeyCode::Char(c))).unwrap();
+        }
+        // Keys don't leak to the panes while it's open
+        assert!(!app.exit);
+        app.handle_key_event(key(KeyCode::Enter)).unwrap();
+        assert!(app.state.mfa_prompt.is_none());
+        assert_eq!(waiting.join().unwrap().as_deref(), Some("123456"));
+
+        // Esc gives up on the role
+        let waiting = std::thread::spawn(move || prompt("app-prod", "serial"));
+        while app.state.mfa_prompt.is_none() {
+            app.drain_search_messages();
+        }
+        app.handle_key_event(key(KeyCode::Esc)).unwrap();
+        assert_eq!(waiting.join().unwrap(), None);
+    }
 }
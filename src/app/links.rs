@@ -0,0 +1,429 @@
+use std::time::Instant;
+
+use arboard::Clipboard;
+use chrono::Utc;
+use ratatui::crossterm::event::KeyCode;
+
+use super::context::{split_context_tag, tagged_groups};
+use super::{App, FilterField, SearchMsg};
+use crate::aws::console::{log_events_url, parse_console_url};
+use crate::aws::{
+    AwsLogError, fetch_log_groups_many, normalize_filter_pattern, resolve_time_window, runtime,
+};
+
+/// Lists the log groups of each `(profile, region)`, blocking; one result
+/// per context, in order. Tests swap in canned lists.
+pub type GroupFetcher = fn(&[(String, String)]) -> Vec<Result<Vec<String>, AwsLogError>>;
+
+/// The real `GroupFetcher`: every context at once on the shared runtime.
+pub fn fetch_groups_from_aws(
+    contexts: &[(String, String)],
+) -> Vec<Result<Vec<String>, AwsLogError>> {
+    runtime().block_on(fetch_log_groups_many(contexts))
+}
+
+impl App {
+    /// Console log-events link for the selected group, the Start/End window
+    /// and the normalized Query.
+    pub fn query_console_link(&self) -> Result<String, String> {
+        let entry = self
+            .state
+            .groups
+            .get(self.state.selected_group)
+            .filter(|g| !g.starts_with('('))
+            .ok_or_else(|| "No log group selected".to_string())?;
+        let (_, region, group) = self.group_target(entry);
+        let (start, end) = resolve_time_window(
+            &self.search_start(),
+            &self.state.filter_end,
+            Utc::now().timestamp_millis(),
+        )
+        .map_err(|e| e.to_string())?;
+
+        Ok(log_events_url(
+            &region,
+            &group,
+            None,
+            start,
+            end,
+            &normalize_filter_pattern(&self.state.filter_query),
+            None,
+        ))
+    }
+
+    pub fn copy_query_console_link(&mut self) {
+        match self.query_console_link() {
+            Ok(url) => self.copy_to_clipboard(&url, "console link"),
+            Err(e) => {
+                self.state.status_message = Some(e);
+                self.state.status_set_at = Some(Instant::now());
+            }
+        }
+    }
+
+    /// Open the "Import console URL" popup, pre-filled from the clipboard when
+    /// it holds something that looks like a console link.
+    pub fn open_import_url_popup(&mut self) {
+        self.state.import_url_input = Clipboard::new()
+            .and_then(|mut clipboard| clipboard.get_text())
+            .ok()
+            .map(|text| text.trim().to_string())
+            .filter(|text| text.contains("console.aws") && text.contains("#logsV2:"))
+            .unwrap_or_default();
+        self.state.import_url_popup_open = true;
+    }
+
+    pub fn handle_import_url_popup_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Esc => {
+                self.state.import_url_popup_open = false;
+            }
+            KeyCode::Enter => {
+                let url = std::mem::take(&mut self.state.import_url_input);
+                self.state.import_url_popup_open = false;
+                self.import_console_url(&url);
+            }
+            KeyCode::Backspace => {
+                self.state.import_url_input.pop();
+            }
+            KeyCode::Char(c) if !c.is_control() => {
+                self.state.import_url_input.push(c);
+            }
+            _ => {}
+        }
+    }
+
+    /// Apply a console link to the filter fields. A different region switches
+    /// region and reloads the groups; the link's group is selected once they
+    /// arrive.
+    pub fn import_console_url(&mut self, url: &str) {
+        let link = match parse_console_url(url) {
+            Ok(link) => link,
+            Err(e) => {
+                self.state.status_message = Some(format!("Can't import link: {e}"));
+                self.state.status_set_at = Some(Instant::now());
+                return;
+            }
+        };
+
+        if let Some(start) = link.start {
+            self.state.filter_start = start;
+        }
+        if let Some(end) = link.end {
+            self.state.filter_end = end;
+        }
+        // A link without a pattern (Logs Insights, or no filter) shows every
+        // event, so the old query would no longer match it
+        self.state.filter_query = link.filter_pattern.unwrap_or_default();
+        self.state.filter_field = FilterField::Query;
+
+        let group = link.group.unwrap_or_default();
+        // With several contexts listed, the link's group may already be there
+        let tagged = self
+            .state
+            .all_groups
+            .iter()
+            .find(|entry| {
+                split_context_tag(entry).is_some_and(|(_, region, name)| {
+                    name == group && link.region.as_deref().is_none_or(|r| r == region)
+                })
+            })
+            .cloned();
+        let message = match link.region {
+            _ if let Some(entry) = tagged => {
+                self.select_group_by_name(&entry);
+                format!("Imported console link for {entry}")
+            }
+            Some(region) if region != self.state.region => {
+                self.state.pending_group_select = Some(group).filter(|g| !g.is_empty());
+                let profile = self.state.profile.clone();
+                self.switch_context(&profile, &region);
+                format!("Imported link; switched region to {region}, loading groups...")
+            }
+            _ if group.is_empty() => "Imported console link".to_string(),
+            _ if self.select_group_by_name(&group) => {
+                format!("Imported console link for {group}")
+            }
+            _ => format!(
+                "Imported link, but group {group} is not in {}",
+                self.state.region
+            ),
+        };
+        self.state.status_message = Some(message);
+        self.state.status_set_at = Some(Instant::now());
+    }
+
+    /// Select `name` in the Groups pane, clearing any group search so it is
+    /// visible. Returns false when the group is unknown.
+    pub fn select_group_by_name(&mut self, name: &str) -> bool {
+        let Some(idx) = self.state.all_groups.iter().position(|g| g == name) else {
+            return false;
+        };
+        self.state.group_search_active = false;
+        self.state.group_search_input.clear();
+        self.state.groups = self.state.all_groups.clone();
+        self.state.selected_group = idx;
+        let visible_rows = self.visible_group_rows();
+        self.clamp_groups_scroll(visible_rows);
+        true
+    }
+
+    /// Fetch the log groups for the current region/profile (or every
+    /// configured context) in the background with `state.group_fetcher`;
+    /// the result arrives as `SearchMsg::Groups`.
+    pub fn reload_groups(&mut self) {
+        let tx = self.search_tx.clone();
+        let fetch = self.state.group_fetcher;
+
+        self.state.groups = vec!["(loading log groups...)".to_string()];
+        self.state.all_groups = self.state.groups.clone();
+        self.state.selected_group = 0;
+        self.state.groups_scroll = 0;
+
+        let tagged = self.multi_context();
+        let contexts = if tagged {
+            self.state.settings.contexts.clone()
+        } else {
+            vec![(self.state.profile.clone(), self.state.region.clone())]
+        };
+        std::thread::spawn(move || {
+            let mut results = fetch(&contexts);
+            // The first auth failure gets the banner; all show in the list
+            if let Some(failure) = results
+                .iter()
+                .find_map(|r| r.as_ref().err().and_then(|e| e.auth_failure()))
+            {
+                let _ = tx.send(SearchMsg::AuthFailed(failure));
+            }
+            let res = if tagged {
+                Ok(tagged_groups(&contexts, results))
+            } else {
+                results
+                    .pop()
+                    .unwrap_or_else(|| Ok(Vec::new()))
+                    .map_err(|e| e.to_string())
+            };
+            let _ = tx.send(SearchMsg::Groups(res));
+        });
+    }
+
+    pub fn apply_loaded_groups(&mut self, res: Result<Vec<String>, String>) {
+        let groups = match res {
+            Ok(g) if !g.is_empty() => g,
+            Ok(_) => vec![format!(
+                "(no log groups found in region {} for profile {})",
+                self.state.region, self.state.profile
+            )],
+            Err(e) => vec![format!("(error fetching log groups: {e})")],
+        };
+        self.state.all_groups = groups.clone();
+        self.state.groups = groups;
+        self.state.selected_group = 0;
+        self.state.groups_scroll = 0;
+
+        if let Some(group) = self.state.pending_group_select.take()
+            && !self.select_group_by_name(&group)
+        {
+            self.state.status_message =
+                Some(format!("Group {group} not found in {}", self.state.region));
+            self.state.status_set_at = Some(Instant::now());
+        }
+        if let Some(load) = self.state.pending_filter_load.take() {
+            self.finish_filter_load(load);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::App;
+    use crate::app::state::AppState;
+    use crate::aws::AwsLogError;
+
+    fn app_with_groups(groups: Vec<&str>) -> App {
+        let groups: Vec<String> = groups.into_iter().map(String::from).collect();
+
+        let state = AppState {
+            all_groups: groups.clone(),
+            groups,
+            filter_field: FilterField::Start,
+            ..AppState::for_test()
+        };
+
+        App::for_test(state)
+    }
+
+    #[test]
+    fn query_link_round_trips_through_import() {
+        let mut app = app_with_groups(vec!["/aws/lambda/api", "/aws/lambda/worker"]);
+        app.state.selected_group = 1;
+        app.state.filter_start = "2025-01-01T00:00:00Z".to_string();
+        app.state.filter_end = "2025-01-01T01:00:00Z".to_string();
+        app.state.filter_query = "level=\"error\"".to_string();
+
+        let url = app.query_console_link().unwrap();
+        assert!(url.contains("log-group/$252Faws$252Flambda$252Fworker/log-events"));
+
+        let mut other = app_with_groups(vec!["/aws/lambda/api", "/aws/lambda/worker"]);
+        other.import_console_url(&url);
+
+        assert_eq!(other.state.selected_group, 1);
+        assert_eq!(other.state.filter_start, "2025-01-01T00:00:00+00:00");
+        assert_eq!(other.state.filter_end, "2025-01-01T01:00:00+00:00");
+        // The normalized pattern comes back, which searches the same thing
+        assert_eq!(other.state.filter_query, "{ $.level = \"error\" }");
+        assert_eq!(other.state.filter_field, FilterField::Query);
+        assert_eq!(other.state.region, "eu-west-1");
+    }
+
+    #[test]
+    fn insights_link_clears_the_query() {
+        let mut app = app_with_groups(vec!["/aws/lambda/api"]);
+        app.state.filter_query = "level=\"error\"".to_string();
+
+        app.import_console_url(
+            "https://eu-west-1.console.aws.amazon.com/cloudwatch/home?region=eu-west-1\
+             #logsV2:logs-insights$3FqueryDetail$3D~(end~0~start~-900~timeType~'RELATIVE\
+             ~unit~'seconds~editorString~'fields*20*40timestamp~source~(~'*2faws*2flambda*2fapi))",
+        );
+
+        assert_eq!(app.state.filter_start, "-15m");
+        assert_eq!(app.state.filter_query, "");
+        assert_eq!(
+            app.state.status_message.as_deref(),
+            Some("Imported console link for /aws/lambda/api")
+        );
+    }
+
+    #[test]
+    fn import_with_other_region_waits_for_groups() {
+        let mut app = app_with_groups(vec!["/aws/lambda/api"]);
+        app.import_console_url(
+            "https://us-east-1.console.aws.amazon.com/cloudwatch/home?region=us-east-1\
+             #logsV2:log-groups/log-group/orders/log-events$3Fstart$3D-900000",
+        );
+
+        assert_eq!(app.state.region, "us-east-1");
+        assert_eq!(app.state.filter_start, "-15m");
+        assert_eq!(app.state.pending_group_select.as_deref(), Some("orders"));
+        // Switched like the region picker does: a running tail stops
+        assert!(app.tail_stop.load(std::sync::atomic::Ordering::Relaxed));
+
+        // Groups for the new region arrive
+        app.apply_loaded_groups(Ok(vec!["billing".to_string(), "orders".to_string()]));
+        assert_eq!(app.state.groups.len(), 2);
+        assert_eq!(app.state.selected_group, 1);
+        assert!(app.state.pending_group_select.is_none());
+    }
+
+    #[test]
+    fn import_rejects_non_console_urls() {
+        let mut app = app_with_groups(vec!["/aws/lambda/api"]);
+        app.state.filter_start = "-5m".to_string();
+        app.import_console_url("https://example.com/");
+
+        assert_eq!(app.state.filter_start, "-5m");
+        assert!(
+            app.state
+                .status_message
+                .as_deref()
+                .unwrap()
+                .starts_with("Can't import link")
+        );
+    }
+
+    #[test]
+    fn tagged_groups_use_their_own_context() {
+        let contexts = vec![
+            ("prod".to_string(), "eu-west-1".to_string()),
+            ("prod".to_string(), "us-east-1".to_string()),
+        ];
+        let groups = tagged_groups(
+            &contexts,
+            vec![
+                Ok(vec!["orders".to_string()]),
+                Err(AwsLogError::ClientInit("expired".to_string())),
+            ],
+        );
+        assert_eq!(groups[0], "[prod/eu-west-1] orders");
+        assert!(groups[1].starts_with("(error fetching log groups for prod/us-east-1"));
+
+        let mut app = app_with_groups(vec!["[prod/us-east-1] orders", "[dev/eu-west-1] api"]);
+        let targets = app.search_targets(&[
+            "[prod/us-east-1] orders".to_string(),
+            "[dev/eu-west-1] api".to_string(),
+            "[prod/us-east-1] billing".to_string(),
+        ]);
+        assert_eq!(targets.len(), 2);
+        assert_eq!(targets[0].profile, "prod");
+        assert_eq!(targets[0].region, "us-east-1");
+        assert_eq!(targets[0].groups, vec!["orders", "billing"]);
+        assert_eq!(targets[1].groups, vec!["api"]);
+
+        // The console link is for the group's region, not the app's
+        app.state.filter_start = "-15m".to_string();
+        let url = app.query_console_link().unwrap();
+        assert!(url.starts_with("https://us-east-1.console"), "{url}");
+        assert!(url.contains("log-group/orders/log-events"), "{url}");
+
+        // Importing it finds the tagged entry instead of switching region
+        app.state.selected_group = 1;
+        app.import_console_url(&url);
+        assert_eq!(app.state.selected_group, 0);
+        assert_eq!(app.state.region, "eu-west-1");
+    }
+
+    /// Drain until the background group reload has answered.
+    fn wait_for_groups(app: &mut App) {
+        while app.state.groups == vec!["(loading log groups...)"] {
+            app.drain_search_messages();
+        }
+    }
+
+    #[test]
+    fn reload_lists_groups_through_the_fetcher() {
+        let mut app = app_with_groups(vec!["/old"]);
+        app.state.group_fetcher = |contexts| {
+            contexts
+                .iter()
+                .map(|(_, region)| Ok(vec![format!("/app/{region}"), "/shared".to_string()]))
+                .collect()
+        };
+        app.state.pending_group_select = Some("/shared".to_string());
+        app.reload_groups();
+        wait_for_groups(&mut app);
+        assert_eq!(app.state.groups, vec!["/app/eu-west-1", "/shared"]);
+        assert_eq!(app.state.selected_group, 1);
+
+        // With contexts configured every one is listed, tagged
+        app.state.settings.contexts = vec![
+            ("prod".to_string(), "eu-west-1".to_string()),
+            ("dr".to_string(), "us-east-1".to_string()),
+        ];
+        app.reload_groups();
+        wait_for_groups(&mut app);
+        assert_eq!(app.state.groups.len(), 4);
+        assert_eq!(app.state.groups[2], "[dr/us-east-1] /app/us-east-1");
+
+        // Picking a context lists just that one, keeping the selected group
+        app.state.selected_group = 3;
+        app.switch_context("dr", "us-east-1");
+        wait_for_groups(&mut app);
+        assert_eq!(app.state.groups, vec!["/app/us-east-1", "/shared"]);
+        assert_eq!(app.state.selected_group, 1);
+
+        // Failures show as a row (and a banner when they're about auth)
+        app.state.settings.contexts.clear();
+        app.state.group_fetcher = |contexts| {
+            contexts
+                .iter()
+                .map(|_| Err(AwsLogError::ClientInit("no network".to_string())))
+                .collect()
+        };
+        app.reload_groups();
+        wait_for_groups(&mut app);
+        assert!(app.state.groups[0].starts_with("(error fetching log groups:"));
+        assert!(app.state.auth_banner.is_none());
+    }
+}
@@ -1,14 +1,27 @@
-mod clipboard;
-mod filters;
+pub mod auth;
+pub mod clipboard;
+pub mod config;
+pub mod context;
+pub mod export;
+pub mod external;
+pub mod fields;
+pub mod filter_library;
+pub mod filter_vars;
+pub mod filters;
+pub mod histogram;
+pub mod history;
 mod keymap;
+pub mod links;
+pub mod session;
 pub mod state;
 
 use crate::app::state::AppState;
+use crate::ui::styles::{self, Theme};
 use chrono::Utc;
 use ratatui::crossterm::event;
 use ratatui::prelude::Rect;
-use ratatui::style::{Color, Style};
 use ratatui::{DefaultTerminal, Frame};
+use std::collections::BTreeMap;
 use std::io;
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
@@ -16,10 +29,14 @@ use std::sync::atomic::Ordering;
 use std::sync::mpsc::{Receiver, Sender};
 use std::time::{Duration, Instant};
 
-use crate::aws::fetch_log_events;
+use crate::aws::{
+    AuthFailure, AwsLogError, GroupTarget, LogEvent, fetch_log_events_targets, resolve_time_window,
+    runtime,
+};
 use serde::{Deserialize, Serialize};
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Focus {
     Groups,
     Filter,
@@ -34,21 +51,62 @@ pub enum FilterField {
     Search,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SavedFilter {
     pub name: String,
     #[serde(default)]
     pub group: String,
+    /// More groups to search together with `group`; `*` matches any run of
+    /// characters, e.g. `/aws/lambda/orders-*`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<String>,
+    /// Only search streams whose names start with one of these.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stream_prefixes: Vec<String>,
+    /// Profile and region the filter was saved in; empty means "any".
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub profile: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub region: String,
     pub start: String,
     pub end: String,
     pub query: String,
+    /// When the filter was last loaded (epoch ms).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_used_ms: Option<i64>,
+    #[serde(default)]
+    pub use_count: u32,
+    /// Last values given for its `${name}` placeholders.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub defaults: BTreeMap<String, String>,
+    /// Label of the shared, read-only file it came from; `None` for the
+    /// user's own `filters.json`.
+    #[serde(skip)]
+    pub source: Option<String>,
+}
+
+/// Messages sent from the search worker thread back to the UI loop.
+#[derive(Debug)]
+pub enum SearchMsg {
+    /// A status/info line with no event behind it (headers, errors).
+    Line(String),
+    Event(LogEvent),
+    /// The initial fetch finished with this many events (for the history).
+    Fetched(usize),
+    Done,
+    /// Result of a background log group reload (e.g. after a region switch).
+    Groups(Result<Vec<String>, String>),
+    /// A fetch failed on credentials or permissions.
+    AuthFailed(AuthFailure),
+    /// Assuming a role needs an MFA code.
+    MfaPrompt(auth::MfaCodePrompt),
 }
 
 pub struct App {
     pub state: AppState,
     pub exit: bool,
-    pub search_tx: Sender<String>,
-    pub search_rx: Receiver<String>,
+    pub search_tx: Sender<SearchMsg>,
+    pub search_rx: Receiver<SearchMsg>,
     pub tail_stop: Arc<AtomicBool>,
 }
 
@@ -65,23 +123,7 @@ impl App {
                 self.state.last_blink = Instant::now();
             }
 
-            while let Ok(msg) = self.search_rx.try_recv() {
-                let total = self.results_total_lines();
-                self.state.results_scroll = self.state.results_scroll.min(total.saturating_sub(1));
-
-                if msg == "__SEARCH_DONE__" {
-                    self.state.searching = false;
-                    // when done, move focus to results so arrows can scroll later etc.
-                    self.state.focus = Focus::Results;
-                    continue;
-                }
-
-                self.state.lines.push(msg);
-                // optional cap
-                if self.state.lines.len() > 2000 {
-                    self.state.lines.drain(0..500);
-                }
-            }
+            self.drain_search_messages();
 
             if self.state.searching && self.state.last_dots.elapsed() >= Duration::from_millis(250)
             {
@@ -94,21 +136,52 @@ impl App {
 
             terminal.draw(|frame| self.draw(frame))?;
 
-            if event::poll(Duration::from_millis(50))? {
-                if let event::Event::Key(key_event) = event::read()? {
-                    self.handle_key_event(key_event)?;
-                }
+            if event::poll(Duration::from_millis(50))?
+                && let event::Event::Key(key_event) = event::read()?
+            {
+                self.handle_key_event(key_event)?;
+            }
+
+            if let Some(cmd) = self.state.pending_external.take() {
+                self.run_external(terminal, cmd)?;
             }
         }
         Ok(())
     }
 
+    /// Apply everything the worker threads have sent since the last frame.
+    pub fn drain_search_messages(&mut self) {
+        while let Ok(msg) = self.search_rx.try_recv() {
+            let total = self.results_total_lines();
+            self.state.results_scroll = self.state.results_scroll.min(total.saturating_sub(1));
+
+            match msg {
+                SearchMsg::Done => {
+                    self.state.searching = false;
+                    // when done, move focus to results so arrows can scroll later etc.
+                    self.state.focus = Focus::Results;
+                    continue;
+                }
+                SearchMsg::Line(line) => self.push_result_entry(line, None),
+                SearchMsg::Event(ev) => self.push_result_entry(ev.formatted(), Some(ev)),
+                SearchMsg::Fetched(count) => {
+                    self.state.auth_banner = None;
+                    self.record_history(count);
+                }
+                SearchMsg::Groups(res) => self.apply_loaded_groups(res),
+                SearchMsg::AuthFailed(failure) => self.show_auth_failure(failure),
+                SearchMsg::MfaPrompt(prompt) => self.state.mfa_prompt = Some(prompt),
+            }
+        }
+    }
+
     fn draw(&self, frame: &mut Frame) {
-        frame.render_widget(&*self, frame.area());
+        frame.render_widget(self, frame.area());
     }
 
     pub fn draw_scrollbar(
         buf: &mut ratatui::buffer::Buffer,
+        theme: &Theme,
         area: Rect,
         scroll: usize,
         total: usize,
@@ -121,24 +194,9 @@ impl App {
         // x column for scrollbar (rightmost column inside results block)
         let x = area.x + area.width - 1;
 
-        // Style: subtle when unfocused, brighter and more colorful when focused
-        let track_style = if focus {
-            Style::default()
-                .fg(Color::Rgb(130, 130, 130))
-                .bg(Color::Rgb(5, 5, 5))
-        } else {
-            Style::default()
-                .fg(Color::Rgb(80, 80, 80))
-                .bg(Color::Rgb(14, 14, 14))
-        };
-
-        let thumb_style = if focus {
-            Style::default().fg(Color::Cyan).bg(Color::Rgb(5, 5, 5))
-        } else {
-            Style::default()
-                .fg(Color::Rgb(200, 200, 200))
-                .bg(Color::Rgb(14, 14, 14))
-        };
+        // Subtle when unfocused, brighter when focused
+        let track_style = styles::scrollbar_track(theme, focus);
+        let thumb_style = styles::scrollbar_thumb(theme, focus);
 
         // draw track
         for dy in 0..area.height {
@@ -167,11 +225,9 @@ impl App {
         let scroll = scroll.min(max_scroll);
 
         // thumb position
-        let thumb_top = if max_scroll == 0 {
-            0
-        } else {
-            (scroll * (view - thumb_h)) / max_scroll
-        };
+        let thumb_top = (scroll * (view - thumb_h))
+            .checked_div(max_scroll)
+            .unwrap_or(0);
 
         for i in 0..thumb_h {
             let y = area.y + (thumb_top + i) as u16;
@@ -181,6 +237,47 @@ impl App {
         }
     }
 
+    fn push_result_entry(&mut self, line: String, event: Option<LogEvent>) {
+        // Keep `line_events` index-aligned with `lines`.
+        self.state.line_events.resize(self.state.lines.len(), None);
+        self.state.lines.push(line);
+        self.state.line_events.push(event);
+
+        // Cap memory: drop the oldest quarter once over the limit
+        if self.state.lines.len() > self.state.settings.max_lines {
+            let dropped = self.state.settings.max_lines / 4;
+            self.state.lines.drain(0..dropped);
+            self.state.line_events.drain(0..dropped);
+            self.state.selection_anchor = self
+                .state
+                .selection_anchor
+                .map(|anchor| anchor.saturating_sub(dropped));
+        }
+    }
+
+    /// Index into `lines` of the entry containing the top visible Results row.
+    pub fn current_entry_index(&self) -> Option<usize> {
+        let mut seen = 0;
+        for (idx, entry) in self.state.lines.iter().enumerate() {
+            seen += entry.lines().count();
+            if self.state.results_scroll < seen {
+                return Some(idx);
+            }
+        }
+        None
+    }
+
+    /// The event under the Results cursor, or the next event below it when the
+    /// cursor sits on a status line.
+    pub fn current_event(&self) -> Option<&LogEvent> {
+        let idx = self.current_entry_index()?;
+        self.state
+            .line_events
+            .iter()
+            .skip(idx)
+            .find_map(|ev| ev.as_ref())
+    }
+
     fn results_up(&mut self) {
         self.state.results_scroll = self.state.results_scroll.saturating_sub(1);
     }
@@ -227,12 +324,14 @@ impl App {
     }
 
     fn groups_up(&mut self) {
+        self.clear_group_binding();
         if !self.state.groups.is_empty() {
             self.state.selected_group = self.state.selected_group.saturating_sub(1);
             self.clamp_groups_scroll(self.visible_group_rows());
         }
     }
     fn groups_down(&mut self) {
+        self.clear_group_binding();
         if !self.state.groups.is_empty() {
             self.state.selected_group =
                 (self.state.selected_group + 1).min(self.state.groups.len() - 1);
@@ -260,69 +359,88 @@ impl App {
         };
     }
 
-    fn start_search(&mut self) {
+    pub fn start_search(&mut self) {
         self.state.searching = true;
 
         self.state.last_dots = Instant::now();
         self.state.focus = Focus::Results; // lose focus from form
         self.state.editing = false;
         self.state.lines.clear(); // optional
+        self.state.line_events.clear();
         self.state.results_scroll = 0;
+        self.state.histogram_selected = None;
+        self.state.selection_anchor = None;
         self.tail_stop.store(false, Ordering::Relaxed);
 
-        let group = match self.state.groups.get(self.state.selected_group) {
-            Some(g) => g.clone(),
-            None => return,
+        // A saved filter can bind several groups; otherwise it's the selected one
+        let groups = if self.state.filter_groups.is_empty() {
+            match self.state.groups.get(self.state.selected_group) {
+                Some(g) => vec![g.clone()],
+                None => return,
+            }
+        } else {
+            self.state.filter_groups.clone()
         };
-
-        let region = self.state.region.clone();
-        let profile = self.state.profile.clone();
-        let start = self.state.filter_start.clone();
+        let group = groups[0].clone();
+        let streams = self.state.filter_streams.clone();
+        let targets = self.search_targets(&groups);
+        let start = self.search_start();
         let end = self.state.filter_end.clone();
         let pattern = self.state.filter_query.clone();
 
+        self.begin_history_entry(&group, &start, &end, &pattern);
+        self.state.search_group = group.clone();
+        self.state.search_query = pattern.clone();
+        self.state.search_window =
+            resolve_time_window(&start, &end, Utc::now().timestamp_millis()).ok();
+
         let tx = self.search_tx.clone();
 
         // show immediate feedback
-        let _ = tx.send(format!("Searching {} ...", group));
+        let _ = tx.send(SearchMsg::Line(format!(
+            "Searching {} ...",
+            groups.join(", ")
+        )));
 
         let tail_mode = self.state.tail_mode;
+        let tail_interval = self.state.settings.tail_interval;
         let tail_stop = self.tail_stop.clone();
 
         std::thread::spawn(move || {
-            let rt = tokio::runtime::Runtime::new().expect("tokio runtime");
-            let res = rt.block_on(fetch_log_events(
-                &region,
-                &profile,
-                &group,
+            let mut targets = targets;
+            let rt = runtime();
+            let fetch = rt.block_on(fetch_log_events_targets(
+                &targets,
+                &streams,
                 start.as_str(),
                 end.as_str(),
                 pattern.as_str(),
             ));
 
-            let mut last_ts: Option<i64> = None;
-
-            match res {
-                Ok((lines, last)) => {
-                    let _ = tx.send(format!("--- {} results ---", lines.len()));
-                    for line in lines {
-                        let _ = tx.send(line);
-                    }
-                    last_ts = last;
-                }
-                Err(e) => {
-                    let _ = tx.send(format!("[search error] {e}"));
-                }
+            // Nothing answered: just the errors, no result count
+            if fetch.failures.len() < targets.len() {
+                let _ = tx.send(SearchMsg::Fetched(fetch.events.len()));
+                let _ = tx.send(SearchMsg::Line(format!(
+                    "--- {} results ---",
+                    fetch.events.len()
+                )));
             }
+            for ev in fetch.events {
+                let _ = tx.send(SearchMsg::Event(ev));
+            }
+            let mut last_ts = fetch.last_ts;
+            let several = targets.len() > 1;
+            let logged_out = report_fetch_failures(&tx, "search", several, fetch.failures);
+            targets.retain(|t| !logged_out.contains(t));
 
             // If not tailing, we're done
             if !tail_mode {
-                let _ = tx.send("__SEARCH_DONE__".to_string());
+                let _ = tx.send(SearchMsg::Done);
                 return;
             }
 
             // Tail mode: repeatedly fetch new events
-            loop {
+            while !targets.is_empty() {
                 if tail_stop.load(std::sync::atomic::Ordering::Relaxed) {
                     break;
                 }
@@ -338,42 +456,45 @@ impl App {
                     start.clone()
                 };
 
-                // Empty end = "now" (fetch_log_events treats empty end as now)
+                // Empty end = "now" (resolve_time_window treats empty end as now)
                 let tail_end = String::new();
 
-                let res = rt.block_on(fetch_log_events(
-                    &region,
-                    &profile,
-                    &group,
+                let fetch = rt.block_on(fetch_log_events_targets(
+                    &targets,
+                    &streams,
                     tail_start.as_str(),
                     tail_end.as_str(),
                     pattern.as_str(),
                 ));
 
-                match res {
-                    Ok((lines, new_last)) => {
-                        // Don’t re-print a header every poll; just append lines
-                        for line in lines {
-                            let _ = tx.send(line);
-                        }
-                        if let Some(ts) = new_last {
-                            last_ts = Some(last_ts.map_or(ts, |prev| prev.max(ts)));
-                        }
-                    }
-                    Err(e) => {
-                        let _ = tx.send(format!("[tail error] {e}"));
-                        // optional: break on repeated errors
-                    }
+                // Don’t re-print a header every poll; just append lines
+                for ev in fetch.events {
+                    let _ = tx.send(SearchMsg::Event(ev));
+                }
+                if let Some(ts) = fetch.last_ts {
+                    last_ts = Some(last_ts.map_or(ts, |prev| prev.max(ts)));
                 }
+                // Polling a context again won't help until the user logs in
+                let logged_out = report_fetch_failures(&tx, "tail", several, fetch.failures);
+                targets.retain(|t| !logged_out.contains(t));
 
                 // Simple tail interval
-                std::thread::sleep(std::time::Duration::from_secs(3));
+                std::thread::sleep(tail_interval);
             }
 
-            let _ = tx.send("__SEARCH_DONE__".to_string());
+            let _ = tx.send(SearchMsg::Done);
         });
     }
 
+    /// The Start field, or the configured default window when it's empty.
+    pub fn search_start(&self) -> String {
+        if self.state.filter_start.trim().is_empty() {
+            self.state.settings.default_start.clone()
+        } else {
+            self.state.filter_start.clone()
+        }
+    }
+
     pub fn active_field_len(&self) -> usize {
         match self.state.filter_field {
             FilterField::Start => self.state.filter_start.len(),
@@ -393,7 +514,7 @@ impl App {
         let mut it = haystack.chars();
 
         for c in needle.chars() {
-            if let Some(_) = it.by_ref().find(|&hc| hc == c) {
+            if it.by_ref().find(|&hc| hc == c).is_some() {
                 continue;
             } else {
                 return false;
@@ -440,75 +561,128 @@ impl App {
     }
 
     fn maybe_clear_status(&mut self) {
-        if let Some(set_at) = self.state.status_set_at {
-            if set_at.elapsed() >= Duration::from_secs(2) {
-                self.state.status_message = None;
-                self.state.status_set_at = None;
+        if let Some(set_at) = self.state.status_set_at
+            && set_at.elapsed() >= self.state.settings.status_timeout
+        {
+            self.state.status_message = None;
+            self.state.status_set_at = None;
+        }
+    }
+}
+
+#[cfg(test)]
+impl App {
+    /// An app around `state` with its own search channel, for unit tests.
+    pub fn for_test(state: AppState) -> App {
+        let (search_tx, search_rx) = std::sync::mpsc::channel();
+        App {
+            state,
+            exit: false,
+            search_tx,
+            search_rx,
+            tail_stop: Arc::new(AtomicBool::new(false)),
+        }
+    }
+}
+
+/// One `[search error]` / `[tail error]` line per failed context (named
+/// when the search spans `several`), plus the first auth failure for the
+/// banner. Returns the contexts whose credentials failed.
+fn report_fetch_failures(
+    tx: &Sender<SearchMsg>,
+    kind: &str,
+    several: bool,
+    failures: Vec<(GroupTarget, AwsLogError)>,
+) -> Vec<GroupTarget> {
+    let mut logged_out = Vec::new();
+    for (target, e) in failures {
+        let line = if several {
+            let label = context::context_label(&target.profile, &target.region);
+            format!("[{kind} error] {label}: {e}")
+        } else {
+            format!("[{kind} error] {e}")
+        };
+        let _ = tx.send(SearchMsg::Line(line));
+        if let Some(failure) = e.auth_failure() {
+            if logged_out.is_empty() {
+                let _ = tx.send(SearchMsg::AuthFailed(failure));
             }
+            logged_out.push(target);
         }
     }
+    logged_out
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::Theme;
 
     fn app_with_groups(groups: Vec<&str>) -> App {
         let groups_owned: Vec<String> = groups.iter().map(|s| s.to_string()).collect();
-        let (tx, rx) = std::sync::mpsc::channel();
 
         let state = AppState {
-            app_title: "Test".to_string(),
-            theme: Theme::default_dark(),
-            theme_name: "dark".to_string(),
-            lines: Vec::new(),
-            filter_cursor_pos: 0,
-
             all_groups: groups_owned.clone(),
             groups: groups_owned,
-            selected_group: 0,
-            groups_scroll: 0,
+            ..AppState::for_test()
+        };
 
-            profile: "test-profile".to_string(),
+        App::for_test(state)
+    }
+
+    #[test]
+    fn failed_contexts_are_reported_one_line_each() {
+        let target = |profile: &str| GroupTarget {
+            profile: profile.to_string(),
             region: "eu-west-1".to_string(),
-            focus: Focus::Groups,
-
-            filter_start: String::new(),
-            filter_end: String::new(),
-            filter_query: String::new(),
-            filter_field: FilterField::Query,
-            editing: false,
-            cursor_on: true,
-            last_blink: Instant::now(),
-
-            group_search_active: false,
-            group_search_input: String::new(),
-
-            searching: false,
-            dots: 0,
-            last_dots: Instant::now(),
-            results_scroll: 0,
-
-            tail_mode: false,
-
-            status_message: None,
-            status_set_at: None,
-
-            saved_filters: Vec::new(),
-            save_filter_popup_open: false,
-            save_filter_name: String::new(),
-            load_filter_popup_open: false,
-            load_filter_selected: 0,
+            groups: vec!["/app".to_string()],
         };
+        let expired = |profile: &str| AwsLogError::ExpiredCredentials {
+            profile: profile.to_string(),
+            region: "eu-west-1".to_string(),
+            sso_profile: None,
+            source: "expired".into(),
+        };
+        let (tx, rx) = std::sync::mpsc::channel();
 
-        App {
-            state,
-            exit: false,
-            search_tx: tx,
-            search_rx: rx,
-            tail_stop: Arc::new(AtomicBool::new(false)),
-        }
+        let logged_out = report_fetch_failures(
+            &tx,
+            "search",
+            true,
+            vec![
+                (
+                    target("dev"),
+                    AwsLogError::FetchLogEvents {
+                        group: "/app".to_string(),
+                        source: "timed out".into(),
+                    },
+                ),
+                (target("prod"), expired("prod")),
+                (target("ops"), expired("ops")),
+            ],
+        );
+        assert_eq!(logged_out, vec![target("prod"), target("ops")]);
+
+        let msgs: Vec<SearchMsg> = rx.try_iter().collect();
+        let lines: Vec<&str> = msgs
+            .iter()
+            .filter_map(|m| match m {
+                SearchMsg::Line(l) => Some(l.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("[search error] dev / eu-west-1: "));
+        assert!(lines[1].starts_with("[search error] prod / eu-west-1: "));
+        // One banner, for the first context that needs a login
+        let banners: Vec<&AuthFailure> = msgs
+            .iter()
+            .filter_map(|m| match m {
+                SearchMsg::AuthFailed(f) => Some(f),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(banners.len(), 1);
+        assert_eq!(banners[0].profile, "prod");
     }
 
     // --- fuzzy_match tests ---
@@ -613,6 +787,59 @@ mod tests {
         assert!(!app.state.editing);
     }
 
+    fn event_at(ts: i64) -> LogEvent {
+        LogEvent {
+            timestamp_ms: ts,
+            message: format!("event at {ts}"),
+            log_stream_name: "stream".to_string(),
+            event_id: ts.to_string(),
+        }
+    }
+
+    #[test]
+    fn push_result_entry_keeps_events_aligned_with_lines() {
+        let mut app = app_with_groups(vec!["/aws/lambda/api"]);
+        app.push_result_entry("--- 1 results ---".to_string(), None);
+        let ev = event_at(0);
+        app.push_result_entry(ev.formatted(), Some(ev.clone()));
+
+        assert_eq!(app.state.lines.len(), 2);
+        assert_eq!(app.state.line_events.len(), 2);
+        assert!(app.state.line_events[0].is_none());
+        assert_eq!(app.state.line_events[1].as_ref(), Some(&ev));
+
+        // Cursor on the header line resolves to the first event below it
+        app.state.results_scroll = 0;
+        assert_eq!(app.current_entry_index(), Some(0));
+        assert_eq!(app.current_event(), Some(&ev));
+    }
+
+    #[test]
+    fn zoom_to_histogram_bucket_narrows_time_filter() {
+        let mut app = app_with_groups(vec!["/aws/lambda/api"]);
+        app.state.search_window = Some((0, 60_000));
+        for ts in [0, 1_500, 30_000] {
+            let ev = event_at(ts);
+            app.push_result_entry(ev.formatted(), Some(ev));
+        }
+
+        // Cursor starts on the first event (bucket 0); move one bucket right.
+        app.histogram_move(1);
+        assert_eq!(app.state.histogram_selected, Some(1));
+
+        assert!(app.zoom_to_histogram_bucket());
+        assert_eq!(app.state.filter_start, "1970-01-01T00:00:01+00:00");
+        assert_eq!(app.state.filter_end, "1970-01-01T00:00:02+00:00");
+        assert_eq!(app.state.histogram_selected, None);
+    }
+
+    #[test]
+    fn zoom_without_events_does_nothing() {
+        let mut app = app_with_groups(vec!["/aws/lambda/api"]);
+        assert!(!app.zoom_to_histogram_bucket());
+        assert_eq!(app.state.filter_start, "");
+    }
+
     #[test]
     fn maybe_clear_status_clears_after_timeout() {
         let mut app = app_with_groups(vec!["/aws/lambda/api"]);
@@ -0,0 +1,303 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+use super::filters::config_file_path;
+use super::{App, Focus};
+use crate::ui::styles::theme_by_name;
+
+/// What's restored on the next launch for the same profile and region.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct Session {
+    pub theme_name: String,
+    pub group: String,
+    /// Context `group` is listed under; empty in sessions saved before it
+    /// was recorded.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub profile: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub region: String,
+    pub start: String,
+    pub end: String,
+    pub query: String,
+    pub tail: bool,
+    pub focus: Option<Focus>,
+}
+
+/// `session-<profile>-<region>.json`, with anything odd in the names
+/// replaced so it's always a plain file name.
+pub fn session_file_name(profile: &str, region: &str) -> String {
+    let clean = |s: &str| -> String {
+        s.chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect()
+    };
+    let profile = if profile.is_empty() {
+        "default"
+    } else {
+        profile
+    };
+    format!("session-{}-{}.json", clean(profile), clean(region))
+}
+
+fn session_path(profile: &str, region: &str) -> Result<PathBuf, String> {
+    config_file_path(&session_file_name(profile, region))
+}
+
+/// Write via a uniquely named temp file in the same directory, synced to
+/// disk before it is renamed into place, so a crash mid-write (or a second
+/// instance saving at the same time) leaves the old file intact.
+pub fn write_atomic(path: &Path, data: &str) -> Result<(), String> {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(format!(
+        ".{}-{}.tmp",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let tmp = PathBuf::from(tmp);
+
+    let written = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&tmp)
+        .and_then(|mut file| {
+            file.write_all(data.as_bytes())?;
+            file.sync_all()
+        })
+        .map_err(|e| format!("write {}: {e}", tmp.display()))
+        .and_then(|()| {
+            std::fs::rename(&tmp, path).map_err(|e| format!("rename to {}: {e}", path.display()))
+        });
+    if written.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+        return written;
+    }
+    sync_parent_dir(path)
+}
+
+/// Make the rename itself durable.
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) -> Result<(), String> {
+    let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) else {
+        return Ok(());
+    };
+    std::fs::File::open(dir)
+        .and_then(|d| d.sync_all())
+        .map_err(|e| format!("sync {}: {e}", dir.display()))
+}
+
+/// Directories can't be opened for syncing here; the rename is as durable
+/// as the platform makes it.
+#[cfg(not(unix))]
+fn sync_parent_dir(_path: &Path) -> Result<(), String> {
+    Ok(())
+}
+
+pub fn load_session(profile: &str, region: &str) -> Result<Option<Session>, String> {
+    let path = session_path(profile, region)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let data = std::fs::read_to_string(&path)
+        .map_err(|e| format!("read_to_string {}: {e}", path.display()))?;
+    serde_json::from_str(&data)
+        .map(Some)
+        .map_err(|e| format!("decode {}: {e}", path.display()))
+}
+
+impl App {
+    pub fn capture_session(&self) -> Session {
+        let (profile, region, group) = self
+            .state
+            .groups
+            .get(self.state.selected_group)
+            .filter(|g| !g.starts_with('('))
+            .map(|entry| self.group_target(entry))
+            .unwrap_or_default();
+
+        Session {
+            theme_name: self.state.theme_name.clone(),
+            group,
+            profile,
+            region,
+            start: self.state.filter_start.clone(),
+            end: self.state.filter_end.clone(),
+            query: self.state.filter_query.clone(),
+            tail: self.state.tail_mode,
+            focus: Some(self.state.focus),
+        }
+    }
+
+    /// Put a saved session back. The theme is only touched when
+    /// `restore_theme` (no --theme flag, colors allowed) and it still exists.
+    pub fn apply_session(&mut self, session: &Session, restore_theme: bool) {
+        if restore_theme
+            && let Some(theme) = theme_by_name(&session.theme_name, &self.state.user_themes)
+        {
+            self.state.theme = theme;
+            self.state.theme_name = session.theme_name.clone();
+        }
+        if !session.group.is_empty() {
+            let (profile, region) = self.session_context(session);
+            self.select_group_in(&profile, &region, &session.group);
+        }
+        self.state.filter_start = session.start.clone();
+        self.state.filter_end = session.end.clone();
+        self.state.filter_query = session.query.clone();
+        self.state.filter_cursor_pos = self.state.filter_query.len();
+        self.state.tail_mode = session.tail;
+        if let Some(focus) = session.focus {
+            self.state.focus = focus;
+        }
+    }
+
+    fn session_context(&self, session: &Session) -> (String, String) {
+        if session.profile.is_empty() && session.region.is_empty() {
+            (self.state.profile.clone(), self.state.region.clone())
+        } else {
+            (session.profile.clone(), session.region.clone())
+        }
+    }
+
+    pub fn save_session(&self) -> Result<(), String> {
+        let path = session_path(&self.state.profile, &self.state.region)?;
+        let data = serde_json::to_string_pretty(&self.capture_session())
+            .map_err(|e| format!("encode: {e}"))?;
+        write_atomic(&path, &data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::state::AppState;
+    use crate::app::{App, FilterField};
+
+    fn app_with_groups(groups: Vec<&str>) -> App {
+        let groups: Vec<String> = groups.into_iter().map(String::from).collect();
+
+        let state = AppState {
+            all_groups: groups.clone(),
+            groups,
+            filter_field: FilterField::Start,
+            ..AppState::for_test()
+        };
+
+        App::for_test(state)
+    }
+
+    #[test]
+    fn tagged_group_is_restored_in_its_context() {
+        let tagged = vec!["[prod/eu-west-1] api", "[dr/us-east-1] api"];
+        let contexts = vec![
+            ("prod".to_string(), "eu-west-1".to_string()),
+            ("dr".to_string(), "us-east-1".to_string()),
+        ];
+        let mut app = app_with_groups(tagged.clone());
+        app.state.settings.contexts = contexts.clone();
+        app.state.selected_group = 1;
+
+        let session = app.capture_session();
+        assert_eq!(
+            (
+                session.group.as_str(),
+                session.profile.as_str(),
+                session.region.as_str()
+            ),
+            ("api", "dr", "us-east-1")
+        );
+
+        let mut next = app_with_groups(tagged);
+        next.state.settings.contexts = contexts;
+        next.apply_session(&session, false);
+        assert_eq!(next.state.selected_group, 1);
+    }
+
+    #[test]
+    fn session_round_trips_between_apps() {
+        let mut app = app_with_groups(vec!["api", "worker"]);
+        app.state.selected_group = 1;
+        app.state.theme_name = "green".to_string();
+        app.state.filter_start = "-1h".to_string();
+        app.state.filter_query = "level=error".to_string();
+        app.state.tail_mode = true;
+        app.state.focus = Focus::Results;
+
+        let json = serde_json::to_string(&app.capture_session()).unwrap();
+        let session: Session = serde_json::from_str(&json).unwrap();
+
+        let mut next = app_with_groups(vec!["api", "worker"]);
+        next.apply_session(&session, true);
+        assert_eq!(next.state.selected_group, 1);
+        assert_eq!(next.state.theme_name, "green");
+        assert_eq!(next.state.filter_start, "-1h");
+        assert_eq!(next.state.filter_query, "level=error");
+        assert!(next.state.tail_mode);
+        assert_eq!(next.state.focus, Focus::Results);
+    }
+
+    #[test]
+    fn stale_group_and_theme_are_ignored() {
+        let session = Session {
+            theme_name: "gone".to_string(),
+            group: "deleted-group".to_string(),
+            ..Session::default()
+        };
+        let mut app = app_with_groups(vec!["api", "worker"]);
+        app.apply_session(&session, true);
+        assert_eq!(app.state.theme_name, "dark");
+        assert_eq!(app.state.selected_group, 0);
+
+        // --theme given: the saved theme doesn't override it
+        let session = Session {
+            theme_name: "light".to_string(),
+            ..Session::default()
+        };
+        app.apply_session(&session, false);
+        assert_eq!(app.state.theme_name, "dark");
+    }
+
+    #[test]
+    fn file_names_are_per_profile_and_region() {
+        assert_eq!(
+            session_file_name("", "eu-west-1"),
+            "session-default-eu-west-1.json"
+        );
+        assert_eq!(
+            session_file_name("team/prod", "us-east-1"),
+            "session-team_prod-us-east-1.json"
+        );
+    }
+
+    #[test]
+    fn atomic_write_replaces_the_file() {
+        let path = config_file_path("session-atomic-test.json").unwrap();
+        write_atomic(&path, "{\"a\":1}").unwrap();
+        write_atomic(&path, "{\"a\":2}").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "{\"a\":2}");
+
+        // No temp files are left behind
+        let dir = path.parent().unwrap();
+        let leftovers: Vec<_> = std::fs::read_dir(dir)
+            .unwrap()
+            .flatten()
+            .filter(|e| {
+                let name = e.file_name().to_string_lossy().into_owned();
+                name.starts_with("session-atomic-test.json.") && name.ends_with(".tmp")
+            })
+            .collect();
+        assert!(leftovers.is_empty(), "{leftovers:?}");
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -1,11 +1,27 @@
+use super::auth::{AuthBanner, MfaCodePrompt};
+use super::clipboard::ClipboardMode;
+use super::config::Settings;
+use super::context::{ContextPicker, FilterLoad};
+use super::export::ExportFormat;
+use super::external::{ExternalCommand, PipeCommand};
+use super::fields::FieldStats;
+use super::filter_vars::FilterVarsPrompt;
+use super::filters::{FilterPopupMode, FilterSort};
+use super::history::HistoryEntry;
+use super::links::GroupFetcher;
 use super::{FilterField, Focus, SavedFilter};
+use crate::aws::LogEvent;
 use crate::ui::styles::Theme;
+use std::collections::BTreeSet;
+use std::path::PathBuf;
 use std::time::Instant;
 
 pub struct AppState {
     pub app_title: String,
     pub theme: Theme,
     pub theme_name: String,
+    /// Themes from `themes.json`, after the built-ins in the `T` cycle.
+    pub user_themes: Vec<(String, Theme)>,
 
     pub lines: Vec<String>,
     pub filter_cursor_pos: usize,
@@ -44,5 +60,211 @@ pub struct AppState {
     pub save_filter_popup_open: bool,
     pub save_filter_name: String,
     pub load_filter_popup_open: bool,
+    /// Row in the popup's (searched, sorted) list, not in `saved_filters`.
     pub load_filter_selected: usize,
+    pub load_filter_search: String,
+    pub load_filter_sort: FilterSort,
+    pub load_filter_mode: FilterPopupMode,
+    /// Filters picked with Space for export (indices into `saved_filters`).
+    pub load_filter_marked: BTreeSet<usize>,
+    /// Asking for a saved filter's placeholder values before loading it.
+    pub filter_vars_prompt: Option<FilterVarsPrompt>,
+    /// Groups bound by the loaded filter, searched instead of the selected
+    /// one until another group is picked.
+    pub filter_groups: Vec<String>,
+    /// Stream name prefixes bound by the loaded filter.
+    pub filter_streams: Vec<String>,
+    /// Offering to switch to the profile/region a saved filter was made in.
+    pub context_switch_prompt: Option<FilterLoad>,
+    /// Filter to finish loading once the switched context's groups arrive.
+    pub pending_filter_load: Option<FilterLoad>,
+    /// Open profile/region picker (`P` / `R`).
+    pub context_picker: Option<ContextPicker>,
+    /// Credentials/permissions problem from the last fetch, with its fix.
+    pub auth_banner: Option<AuthBanner>,
+    /// An SSO login is running; retry what failed once it succeeds.
+    pub auth_retry: bool,
+    /// Code asked for while assuming a role with `mfa_serial`.
+    pub mfa_prompt: Option<MfaCodePrompt>,
+
+    /// Structured event behind each entry in `lines` (None for status lines).
+    pub line_events: Vec<Option<LogEvent>>,
+    /// Absolute (start_ms, end_ms) window of the last search, for the histogram.
+    pub search_window: Option<(i64, i64)>,
+    pub histogram_selected: Option<usize>,
+
+    pub field_panel_open: bool,
+    pub field_panel_fields: Vec<FieldStats>,
+    pub field_panel_selected: usize,
+    /// Some(i) while browsing the values of the selected field.
+    pub field_panel_value_selected: Option<usize>,
+    /// Re-run the search right after a value is added to the query.
+    pub field_panel_auto_run: bool,
+
+    /// "Filter by this value" popup over the current event's JSON fields.
+    pub value_picker_open: bool,
+    /// (path, shorthand value) pairs of the event under the cursor.
+    pub value_picker_fields: Vec<(String, String)>,
+    pub value_picker_selected: usize,
+
+    /// Entry index where a Results selection started (`V`).
+    pub selection_anchor: Option<usize>,
+    pub export_popup_open: bool,
+    pub export_path: String,
+    pub export_format: ExportFormat,
+    /// Comma separated JSON field paths used as CSV columns.
+    pub export_columns: String,
+    pub export_editing_columns: bool,
+
+    /// External command to run with the TUI suspended, picked up by the main loop.
+    pub pending_external: Option<ExternalCommand>,
+    pub pipe_commands: Vec<PipeCommand>,
+    pub pipe_menu_open: bool,
+    pub pipe_menu_selected: usize,
+    /// Pipe the event under the cursor instead of the results.
+    pub pipe_menu_event: bool,
+
+    pub clipboard_mode: ClipboardMode,
+    pub copy_menu_open: bool,
+    pub copy_menu_selected: usize,
+
+    /// Group and raw query of the last search, for links to its events.
+    pub search_group: String,
+    pub search_query: String,
+
+    pub import_url_popup_open: bool,
+    pub import_url_input: String,
+    /// Group to select once a background group reload finishes.
+    pub pending_group_select: Option<String>,
+    /// Set once a context is picked (`P`/`R`, a filter or a link): the
+    /// Groups pane then lists just that one instead of `settings.contexts`.
+    pub single_context: bool,
+
+    /// Defaults and behaviour knobs from the config file.
+    pub settings: Settings,
+    /// `config.json`, re-read for the new profile on a switch; `None` keeps
+    /// the current settings.
+    pub config_path: Option<PathBuf>,
+    /// How `reload_groups` lists groups (AWS, or canned lists in tests).
+    pub group_fetcher: GroupFetcher,
+    /// Whether a profile signs in through SSO (the AWS config, or a stub in
+    /// tests), so the auth banner can offer `aws sso login`.
+    pub sso_check: fn(&str) -> bool,
+
+    /// Executed searches, oldest first.
+    pub history: Vec<HistoryEntry>,
+    /// Search waiting for its result count before it's recorded.
+    pub history_pending: Option<HistoryEntry>,
+    /// Position in the recalled queries (0 = newest) while using Up/Down.
+    pub history_recall: Option<usize>,
+    /// What was typed in Query before recall started.
+    pub history_draft: String,
+    pub history_popup_open: bool,
+    pub history_popup_input: String,
+    pub history_popup_selected: usize,
+    /// Where searches are recorded; `None` keeps history in memory only.
+    pub history_path: Option<PathBuf>,
+    /// The user's `filters.json`; `None` keeps saved filters in memory only.
+    pub filters_path: Option<PathBuf>,
+}
+
+#[cfg(test)]
+impl AppState {
+    /// A fresh state for unit tests: no groups, no results, nothing open.
+    /// Tests override what they care about with `..AppState::for_test()`.
+    pub fn for_test() -> AppState {
+        AppState {
+            app_title: "Test".to_string(),
+            theme: Theme::default_dark(),
+            theme_name: "dark".to_string(),
+            user_themes: Vec::new(),
+            lines: Vec::new(),
+            filter_cursor_pos: 0,
+            all_groups: Vec::new(),
+            groups: Vec::new(),
+            selected_group: 0,
+            groups_scroll: 0,
+            profile: "test-profile".to_string(),
+            region: "eu-west-1".to_string(),
+            focus: Focus::Groups,
+            filter_start: String::new(),
+            filter_end: String::new(),
+            filter_query: String::new(),
+            filter_field: FilterField::Query,
+            editing: false,
+            cursor_on: true,
+            last_blink: Instant::now(),
+            group_search_active: false,
+            group_search_input: String::new(),
+            searching: false,
+            dots: 0,
+            last_dots: Instant::now(),
+            results_scroll: 0,
+            tail_mode: false,
+            status_message: None,
+            status_set_at: None,
+            saved_filters: Vec::new(),
+            save_filter_popup_open: false,
+            save_filter_name: String::new(),
+            load_filter_popup_open: false,
+            load_filter_selected: 0,
+            load_filter_search: String::new(),
+            load_filter_sort: FilterSort::Name,
+            load_filter_mode: FilterPopupMode::List,
+            load_filter_marked: BTreeSet::new(),
+            filter_vars_prompt: None,
+            filter_groups: Vec::new(),
+            filter_streams: Vec::new(),
+            context_switch_prompt: None,
+            pending_filter_load: None,
+            context_picker: None,
+            auth_banner: None,
+            auth_retry: false,
+            mfa_prompt: None,
+            line_events: Vec::new(),
+            search_window: None,
+            histogram_selected: None,
+            field_panel_open: false,
+            field_panel_fields: Vec::new(),
+            field_panel_selected: 0,
+            field_panel_value_selected: None,
+            field_panel_auto_run: false,
+            value_picker_open: false,
+            value_picker_fields: Vec::new(),
+            value_picker_selected: 0,
+            selection_anchor: None,
+            export_popup_open: false,
+            export_path: String::new(),
+            export_format: ExportFormat::Ndjson,
+            export_columns: String::new(),
+            export_editing_columns: false,
+            pending_external: None,
+            pipe_commands: Vec::new(),
+            pipe_menu_open: false,
+            pipe_menu_selected: 0,
+            pipe_menu_event: false,
+            clipboard_mode: ClipboardMode::Auto,
+            copy_menu_open: false,
+            copy_menu_selected: 0,
+            search_group: String::new(),
+            search_query: String::new(),
+            import_url_popup_open: false,
+            import_url_input: String::new(),
+            pending_group_select: None,
+            single_context: false,
+            settings: Settings::default(),
+            config_path: None,
+            group_fetcher: |contexts| contexts.iter().map(|_| Ok(Vec::new())).collect(),
+            sso_check: |_| false,
+            history: Vec::new(),
+            history_pending: None,
+            history_recall: None,
+            history_draft: String::new(),
+            history_popup_open: false,
+            history_popup_input: String::new(),
+            history_popup_selected: 0,
+            history_path: None,
+            filters_path: None,
+        }
+    }
 }
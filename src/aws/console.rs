@@ -0,0 +1,469 @@
+// CloudWatch console deep links.
+//
+// The console keeps its state in the URL fragment and escapes it in its own
+// way: components are percent-encoded as by JavaScript's
+// `encodeURIComponent`, then every `%` is replaced by `$`. Values inside the
+// fragment's own query string are percent-encoded twice before that, so a
+// `/` in a log group name ends up as `$252F`.
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+/// JavaScript `encodeURIComponent`.
+pub fn encode_uri_component(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'-'
+            | b'_'
+            | b'.'
+            | b'!'
+            | b'~'
+            | b'*'
+            | b'\''
+            | b'('
+            | b')' => out.push(b as char),
+            _ => out.push_str(&format!("%{b:02X}")),
+        }
+    }
+    out
+}
+
+/// The console's fragment escaping: `encodeURIComponent`, then `%` -> `$`.
+pub fn console_escape(s: &str) -> String {
+    encode_uri_component(s).replace('%', "$")
+}
+
+fn console_base(region: &str) -> String {
+    format!("https://{region}.console.aws.amazon.com/cloudwatch/home?region={region}")
+}
+
+/// Link to the log-events view of `group` (optionally a single stream),
+/// limited to `[start_ms, end_ms]` and filtered by `filter_pattern`.
+/// `ref_event_id` scrolls the console to that event.
+pub fn log_events_url(
+    region: &str,
+    group: &str,
+    stream: Option<&str>,
+    start_ms: i64,
+    end_ms: i64,
+    filter_pattern: &str,
+    ref_event_id: Option<&str>,
+) -> String {
+    let mut fragment = format!(
+        "#logsV2:log-groups/log-group/{}",
+        console_escape(&encode_uri_component(group))
+    );
+    match stream {
+        Some(stream) => fragment.push_str(&format!(
+            "/log-events/{}",
+            console_escape(&encode_uri_component(stream))
+        )),
+        None => fragment.push_str("/log-events"),
+    }
+
+    let mut params = Vec::new();
+    if !filter_pattern.is_empty() {
+        params.push(format!(
+            "filterPattern={}",
+            encode_uri_component(filter_pattern)
+        ));
+    }
+    params.push(format!("start={start_ms}"));
+    params.push(format!("end={end_ms}"));
+    if let Some(id) = ref_event_id {
+        params.push(format!("refEventId={}", encode_uri_component(id)));
+    }
+    fragment.push_str(&console_escape(&format!("?{}", params.join("&"))));
+
+    format!("{}{fragment}", console_base(region))
+}
+
+/// Filter settings recovered from a console URL, in the same syntax as the
+/// Filter pane fields (empty end = now, `-1h` style relative starts).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConsoleLink {
+    pub region: Option<String>,
+    pub group: Option<String>,
+    pub start: Option<String>,
+    pub end: Option<String>,
+    /// Only log-events links carry a filter pattern; Logs Insights queries
+    /// have no equivalent.
+    pub filter_pattern: Option<String>,
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && let Some(hex) = s.get(i + 1..i + 3)
+            && let Ok(b) = u8::from_str_radix(hex, 16)
+        {
+            out.push(b);
+            i += 3;
+            continue;
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Undo `console_escape`.
+pub fn console_unescape(s: &str) -> String {
+    percent_decode(&s.replace('$', "%"))
+}
+
+/// `-3600000` -> `-1h`, in the largest unit that divides evenly.
+fn relative_from_ms(ms: i64) -> String {
+    let secs = ms.abs() / 1_000;
+    if secs > 0 && secs % 86_400 == 0 {
+        format!("-{}d", secs / 86_400)
+    } else if secs > 0 && secs % 3_600 == 0 {
+        format!("-{}h", secs / 3_600)
+    } else if secs > 0 && secs % 60 == 0 {
+        format!("-{}m", secs / 60)
+    } else {
+        format!("-{secs}s")
+    }
+}
+
+/// Console times are epoch millis, or negative offsets from now.
+fn time_from_console_ms(ms: i64) -> String {
+    if ms < 0 {
+        relative_from_ms(ms)
+    } else {
+        DateTime::<Utc>::from_timestamp_millis(ms)
+            .map(|dt| dt.to_rfc3339())
+            .unwrap_or_default()
+    }
+}
+
+/// Group name from a `source` entry, which may be a full log group ARN.
+fn group_from_source(source: &str) -> String {
+    match source.split_once(":log-group:") {
+        Some((_, name)) => name.trim_end_matches(":*").to_string(),
+        None => source.to_string(),
+    }
+}
+
+/// Parse a CloudWatch console log-events or Logs Insights URL.
+pub fn parse_console_url(url: &str) -> Result<ConsoleLink, String> {
+    let url = url.trim();
+    let (base, fragment) = url
+        .split_once('#')
+        .ok_or_else(|| "not a CloudWatch console link (no #fragment)".to_string())?;
+    if !base.contains("console.aws") {
+        return Err("not an AWS console URL".to_string());
+    }
+    let fragment = fragment
+        .strip_prefix("logsV2:")
+        .ok_or_else(|| "not a CloudWatch Logs console link".to_string())?;
+
+    let mut link = ConsoleLink {
+        region: region_from_base(base),
+        ..ConsoleLink::default()
+    };
+
+    let fragment = console_unescape(fragment);
+    if let Some(rest) = fragment.strip_prefix("log-groups/log-group/") {
+        let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let group = path.split('/').next().unwrap_or_default();
+        link.group = Some(percent_decode(group));
+        link.filter_pattern = Some(String::new());
+
+        for (key, value) in query.split('&').filter_map(|kv| kv.split_once('=')) {
+            let value = percent_decode(value);
+            match key {
+                "filterPattern" => link.filter_pattern = Some(value),
+                "start" => link.start = value.parse().ok().map(time_from_console_ms),
+                "end" => link.end = value.parse().ok().map(time_from_console_ms),
+                _ => {}
+            }
+        }
+        Ok(link)
+    } else if let Some(rest) = fragment.strip_prefix("logs-insights") {
+        let detail = rest
+            .split_once("queryDetail=")
+            .map(|(_, d)| d)
+            .ok_or_else(|| "Logs Insights link has no queryDetail".to_string())?;
+        let detail = Jsurl::new(detail)
+            .parse()
+            .ok_or_else(|| "could not parse Logs Insights queryDetail".to_string())?;
+
+        link.group = detail["source"]
+            .as_array()
+            .and_then(|sources| sources.first())
+            .and_then(|s| s.as_str())
+            .map(group_from_source);
+
+        if detail["timeType"].as_str() == Some("ABSOLUTE") {
+            link.start = detail["start"].as_str().map(str::to_string);
+            link.end = detail["end"].as_str().map(str::to_string);
+        } else {
+            // Relative windows are offsets in `unit` (seconds unless told otherwise)
+            let unit_ms = match detail["unit"].as_str() {
+                Some("minutes") => 60_000,
+                Some("hours") => 3_600_000,
+                Some("days") => 86_400_000,
+                _ => 1_000,
+            };
+            link.start = detail["start"]
+                .as_f64()
+                .map(|v| relative_from_ms(v as i64 * unit_ms));
+            link.end = Some(String::new());
+        }
+        Ok(link)
+    } else {
+        Err("unsupported console page (expected log events or Logs Insights)".to_string())
+    }
+}
+
+fn region_from_base(base: &str) -> Option<String> {
+    if let Some((_, query)) = base.split_once('?') {
+        for (key, value) in query.split('&').filter_map(|kv| kv.split_once('=')) {
+            if key == "region" && !value.is_empty() {
+                return Some(value.to_string());
+            }
+        }
+    }
+    // https://eu-west-1.console.aws.amazon.com/...
+    let host = base.split("://").nth(1)?.split('/').next()?;
+    host.split_once(".console.")
+        .map(|(region, _)| region.to_string())
+        .filter(|region| region.contains('-'))
+}
+
+/// Just enough of a JSURL decoder for the console's Logs Insights state,
+/// e.g. `~(end~0~start~-3600~source~(~'group))`.
+struct Jsurl<'a> {
+    s: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Jsurl<'a> {
+    fn new(s: &'a str) -> Self {
+        Jsurl {
+            s: s.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.s.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, b: u8) -> Option<()> {
+        (self.peek()? == b).then(|| self.pos += 1)
+    }
+
+    /// Characters up to the next `~` or `)`, with JSURL escapes decoded:
+    /// `*XX` and `**XXXX` are hex code points, `!` is a quote.
+    fn token(&mut self) -> Option<String> {
+        let mut out = String::new();
+        while let Some(b) = self.peek() {
+            match b {
+                b'~' | b')' => break,
+                b'*' => {
+                    self.pos += 1;
+                    let width = if self.eat(b'*').is_some() { 4 } else { 2 };
+                    let hex = std::str::from_utf8(self.s.get(self.pos..self.pos + width)?).ok()?;
+                    out.push(char::from_u32(u32::from_str_radix(hex, 16).ok()?)?);
+                    self.pos += width;
+                }
+                b'!' => {
+                    out.push('\'');
+                    self.pos += 1;
+                }
+                _ => {
+                    // Multi-byte UTF-8 passes through untouched
+                    let len = match b {
+                        0xF0.. => 4,
+                        0xE0.. => 3,
+                        0xC0.. => 2,
+                        _ => 1,
+                    };
+                    out.push_str(std::str::from_utf8(self.s.get(self.pos..self.pos + len)?).ok()?);
+                    self.pos += len;
+                }
+            }
+        }
+        Some(out)
+    }
+
+    fn parse(&mut self) -> Option<Value> {
+        self.eat(b'~')?;
+        match self.peek()? {
+            b'(' => {
+                self.pos += 1;
+                if self.peek() == Some(b'~') {
+                    let mut items = Vec::new();
+                    while self.peek() == Some(b'~') {
+                        items.push(self.parse()?);
+                    }
+                    self.eat(b')')?;
+                    Some(Value::Array(items))
+                } else {
+                    let mut map = serde_json::Map::new();
+                    if self.eat(b')').is_some() {
+                        return Some(Value::Object(map));
+                    }
+                    loop {
+                        let key = self.token()?;
+                        let value = self.parse()?;
+                        map.insert(key, value);
+                        if self.eat(b')').is_some() {
+                            return Some(Value::Object(map));
+                        }
+                        self.eat(b'~')?;
+                    }
+                }
+            }
+            b'\'' => {
+                self.pos += 1;
+                self.token().map(Value::String)
+            }
+            _ => {
+                let tok = self.token()?;
+                Some(match tok.as_str() {
+                    "true" => Value::Bool(true),
+                    "false" => Value::Bool(false),
+                    "null" => Value::Null,
+                    _ => serde_json::from_str::<serde_json::Number>(&tok)
+                        .map(Value::Number)
+                        .unwrap_or(Value::String(tok)),
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_uri_component_matches_javascript() {
+        assert_eq!(
+            encode_uri_component("a-b_c.d!e~f*g'h(i)"),
+            "a-b_c.d!e~f*g'h(i)"
+        );
+        assert_eq!(encode_uri_component("/aws/lambda x"), "%2Faws%2Flambda%20x");
+        assert_eq!(
+            encode_uri_component("{ $.a = 1 }"),
+            "%7B%20%24.a%20%3D%201%20%7D"
+        );
+        assert_eq!(encode_uri_component("é"), "%C3%A9");
+    }
+
+    #[test]
+    fn group_and_stream_are_double_escaped() {
+        let url = log_events_url(
+            "eu-west-1",
+            "/aws/lambda/api",
+            Some("2025/01/01/[$LATEST]abc"),
+            1_000,
+            2_000,
+            "",
+            None,
+        );
+        assert_eq!(
+            url,
+            "https://eu-west-1.console.aws.amazon.com/cloudwatch/home?region=eu-west-1\
+             #logsV2:log-groups/log-group/$252Faws$252Flambda$252Fapi\
+             /log-events/2025$252F01$252F01$252F$255B$2524LATEST$255Dabc\
+             $3Fstart$3D1000$26end$3D2000"
+        );
+    }
+
+    #[test]
+    fn filter_pattern_and_event_id_are_included() {
+        let url = log_events_url(
+            "us-east-1",
+            "app",
+            None,
+            0,
+            5,
+            "{ $.level = \"error\" }",
+            Some("123"),
+        );
+        assert!(url.contains("#logsV2:log-groups/log-group/app/log-events$3F"));
+        assert!(url.contains(
+            "filterPattern$3D$257B$2520$2524.level$2520$253D$2520$2522error$2522$2520$257D"
+        ));
+        assert!(url.ends_with("$26refEventId$3D123"));
+    }
+
+    #[test]
+    fn parses_our_own_log_events_links() {
+        let url = log_events_url(
+            "us-east-1",
+            "/aws/lambda/api",
+            None,
+            1_700_000_000_000,
+            1_700_000_060_000,
+            "{ $.level = \"error\" }",
+            None,
+        );
+        let link = parse_console_url(&url).unwrap();
+        assert_eq!(link.region.as_deref(), Some("us-east-1"));
+        assert_eq!(link.group.as_deref(), Some("/aws/lambda/api"));
+        assert_eq!(link.start.as_deref(), Some("2023-11-14T22:13:20+00:00"));
+        assert_eq!(link.end.as_deref(), Some("2023-11-14T22:14:20+00:00"));
+        assert_eq!(
+            link.filter_pattern.as_deref(),
+            Some("{ $.level = \"error\" }")
+        );
+    }
+
+    #[test]
+    fn parses_relative_log_events_link_without_pattern() {
+        let link = parse_console_url(
+            "https://console.aws.amazon.com/cloudwatch/home?region=ap-southeast-2\
+             #logsV2:log-groups/log-group/api-gw/log-events$3Fstart$3D-3600000",
+        )
+        .unwrap();
+        assert_eq!(link.region.as_deref(), Some("ap-southeast-2"));
+        assert_eq!(link.group.as_deref(), Some("api-gw"));
+        assert_eq!(link.start.as_deref(), Some("-1h"));
+        assert_eq!(link.end, None);
+        assert_eq!(link.filter_pattern.as_deref(), Some(""));
+    }
+
+    #[test]
+    fn parses_logs_insights_links() {
+        let relative = "https://eu-central-1.console.aws.amazon.com/cloudwatch/home?region=eu-central-1\
+             #logsV2:logs-insights$3FqueryDetail$3D~(end~0~start~-900~timeType~'RELATIVE\
+             ~unit~'seconds~editorString~'fields*20*40timestamp~source~(~'*2faws*2flambda*2fapi))";
+        let link = parse_console_url(relative).unwrap();
+        assert_eq!(link.region.as_deref(), Some("eu-central-1"));
+        assert_eq!(link.group.as_deref(), Some("/aws/lambda/api"));
+        assert_eq!(link.start.as_deref(), Some("-15m"));
+        assert_eq!(link.end.as_deref(), Some(""));
+        assert_eq!(link.filter_pattern, None);
+
+        let absolute = "https://us-west-2.console.aws.amazon.com/cloudwatch/home?region=us-west-2\
+             #logsV2:logs-insights$3FqueryDetail$3D~(end~'2025-01-01T01*3a00*3a00.000Z\
+             ~start~'2025-01-01T00*3a00*3a00.000Z~timeType~'ABSOLUTE~tz~'UTC\
+             ~source~(~'arn*3aaws*3alogs*3aus-west-2*3a123*3alog-group*3aorders))";
+        let link = parse_console_url(absolute).unwrap();
+        assert_eq!(link.group.as_deref(), Some("orders"));
+        assert_eq!(link.start.as_deref(), Some("2025-01-01T00:00:00.000Z"));
+        assert_eq!(link.end.as_deref(), Some("2025-01-01T01:00:00.000Z"));
+    }
+
+    #[test]
+    fn rejects_other_urls() {
+        assert!(parse_console_url("https://example.com/#logsV2:log-groups").is_err());
+        assert!(
+            parse_console_url("https://eu-west-1.console.aws.amazon.com/ec2/home#Instances")
+                .is_err()
+        );
+        assert!(parse_console_url("not a url").is_err());
+    }
+}
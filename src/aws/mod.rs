@@ -1,9 +1,16 @@
 use aws_config::Region;
 use aws_config::meta::region::RegionProviderChain;
+use aws_credential_types::provider::error::CredentialsError;
 use aws_sdk_cloudwatchlogs as cwl;
 use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
 use thiserror::Error;
 
+pub mod console;
+pub mod profiles;
+pub mod roles;
+
 #[derive(Debug, Error)]
 pub enum AwsLogError {
     #[error("Failed to create CloudWatch Logs client: {0}")]
@@ -24,26 +31,300 @@ pub enum AwsLogError {
         source: Box<dyn std::error::Error + Send + Sync>,
     },
 
+    #[error("Failed to fetch log streams for group '{group}': {source}")]
+    FetchLogStreams {
+        group: String,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    #[error(
+        "Credentials for profile '{}' have expired or were rejected; {}",
+        profile_name(profile),
+        renew_hint(sso_profile.as_deref())
+    )]
+    ExpiredCredentials {
+        profile: String,
+        region: String,
+        /// The profile to `aws sso login` with, when the credentials come
+        /// from IAM Identity Center (a role's base profile for roles).
+        sso_profile: Option<String>,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    #[error(
+        "AWS profile '{}' is not defined in ~/.aws/config or ~/.aws/credentials",
+        profile_name(profile)
+    )]
+    MissingProfile {
+        profile: String,
+        region: String,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    #[error(
+        "Profile '{}' is not allowed to read CloudWatch Logs in {region}; check its IAM permissions",
+        profile_name(profile)
+    )]
+    AccessDenied {
+        profile: String,
+        region: String,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
     #[error("Invalid time filter '{value}': {reason}")]
     TimeParse { value: String, reason: String },
 }
 
+/// Service error codes for credentials the service rejected.
+const EXPIRED_CODES: &[&str] = &["ExpiredTokenException", "UnrecognizedClientException"];
+
+/// Service error codes for valid credentials without permission.
+const DENIED_CODES: &[&str] = &["AccessDeniedException"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuthErrorKind {
+    /// Expired, missing or rejected credentials (e.g. an SSO session ran out).
+    Expired,
+    /// The profile isn't in the AWS config or credentials files.
+    MissingProfile,
+    /// Valid credentials without permission for the call.
+    AccessDenied,
+}
+
+/// What the UI needs to explain an auth failure and offer a fix.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuthFailure {
+    pub kind: AuthErrorKind,
+    pub profile: String,
+    pub region: String,
+    pub message: String,
+}
+
+fn profile_name(profile: &str) -> &str {
+    if profile.is_empty() {
+        "default"
+    } else {
+        profile
+    }
+}
+
+/// How to get fresh credentials: `aws sso login` only helps SSO profiles.
+fn renew_hint(sso_profile: Option<&str>) -> String {
+    match sso_profile {
+        Some(p) => format!(
+            "log in again (e.g. `aws sso login --profile {}`) and retry",
+            profile_name(p)
+        ),
+        None => "refresh them and retry".to_string(),
+    }
+}
+
+impl AwsLogError {
+    /// Set for the auth variants only.
+    pub fn auth_failure(&self) -> Option<AuthFailure> {
+        let (kind, profile, region) = match self {
+            AwsLogError::ExpiredCredentials {
+                profile, region, ..
+            } => (AuthErrorKind::Expired, profile, region),
+            AwsLogError::MissingProfile {
+                profile, region, ..
+            } => (AuthErrorKind::MissingProfile, profile, region),
+            AwsLogError::AccessDenied {
+                profile, region, ..
+            } => (AuthErrorKind::AccessDenied, profile, region),
+            _ => return None,
+        };
+        Some(AuthFailure {
+            kind,
+            profile: profile.clone(),
+            region: region.clone(),
+            message: self.to_string(),
+        })
+    }
+
+    /// True when the credentials themselves are the problem, so a cached
+    /// client (and the credentials it holds) shouldn't be reused.
+    pub fn is_credential_error(&self) -> bool {
+        matches!(
+            self,
+            AwsLogError::ExpiredCredentials { .. } | AwsLogError::MissingProfile { .. }
+        )
+    }
+
+    /// The matching auth variant when a fetch failed on authentication;
+    /// anything else is returned unchanged.
+    fn classify_auth(self, region: &str, profile: &str) -> AwsLogError {
+        let kind = match &self {
+            AwsLogError::FetchLogGroups { source, .. }
+            | AwsLogError::FetchLogEvents { source, .. }
+            | AwsLogError::FetchLogStreams { source, .. } => {
+                auth_error_kind(source.as_ref(), profile)
+            }
+            _ => None,
+        };
+        let Some(kind) = kind else {
+            return self;
+        };
+        let source = match self {
+            AwsLogError::FetchLogGroups { source, .. }
+            | AwsLogError::FetchLogEvents { source, .. }
+            | AwsLogError::FetchLogStreams { source, .. } => source,
+            other => return other,
+        };
+        let (profile, region) = (profile.to_string(), region.to_string());
+        match kind {
+            AuthErrorKind::Expired => {
+                let base = roles::base_profile(&profile);
+                let sso_profile =
+                    profiles::profile_uses_sso(profile_name(&base)).then(|| base.clone());
+                AwsLogError::ExpiredCredentials {
+                    profile,
+                    region,
+                    sso_profile,
+                    source,
+                }
+            }
+            AuthErrorKind::MissingProfile => AwsLogError::MissingProfile {
+                profile,
+                region,
+                source,
+            },
+            AuthErrorKind::AccessDenied => AwsLogError::AccessDenied {
+                profile,
+                region,
+                source,
+            },
+        }
+    }
+}
+
+/// The auth failure behind a failed call, found by walking its source
+/// chain: the service's error code, or credentials the SDK couldn't load
+/// (reported as a missing profile when `profile` isn't defined anywhere).
+fn auth_error_kind(
+    err: &(dyn std::error::Error + 'static),
+    profile: &str,
+) -> Option<AuthErrorKind> {
+    let mut next = Some(err);
+    while let Some(err) = next {
+        if let Some(code) = service_error_code(err) {
+            return if EXPIRED_CODES.contains(&code) {
+                Some(AuthErrorKind::Expired)
+            } else if DENIED_CODES.contains(&code) {
+                Some(AuthErrorKind::AccessDenied)
+            } else {
+                None
+            };
+        }
+        if let Some(err) = err.downcast_ref::<CredentialsError>() {
+            return match err {
+                _ if profile_missing(profile) => Some(AuthErrorKind::MissingProfile),
+                CredentialsError::CredentialsNotLoaded(_) | CredentialsError::ProviderError(_) => {
+                    Some(AuthErrorKind::Expired)
+                }
+                _ => None,
+            };
+        }
+        next = err.source();
+    }
+    None
+}
+
+/// The error code of a CloudWatch Logs service error.
+fn service_error_code<'a>(err: &'a (dyn std::error::Error + 'static)) -> Option<&'a str> {
+    use cwl::error::ProvideErrorMetadata;
+    use cwl::operation::describe_log_groups::DescribeLogGroupsError;
+    use cwl::operation::describe_log_streams::DescribeLogStreamsError;
+    use cwl::operation::filter_log_events::FilterLogEventsError;
+
+    if let Some(err) = err.downcast_ref::<DescribeLogGroupsError>() {
+        err.code()
+    } else if let Some(err) = err.downcast_ref::<DescribeLogStreamsError>() {
+        err.code()
+    } else if let Some(err) = err.downcast_ref::<FilterLogEventsError>() {
+        err.code()
+    } else {
+        None
+    }
+}
+
+/// A named profile (or a role's base profile) that neither AWS file defines.
+fn profile_missing(profile: &str) -> bool {
+    let base = roles::base_profile(profile);
+    !base.is_empty() && !profiles::list_profiles().contains(&base)
+}
+
 #[derive(Debug)]
 struct SimpleLogEvent<'a> {
     timestamp_ms: i64,
     message: &'a str,
 }
 
+/// A single CloudWatch log event as returned by `FilterLogEvents`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogEvent {
+    pub timestamp_ms: i64,
+    pub message: String,
+    pub log_stream_name: String,
+    pub event_id: String,
+}
+
+impl LogEvent {
+    /// The rendered form shown in the Results pane (timestamp + pretty JSON).
+    pub fn formatted(&self) -> String {
+        format_log_event(&SimpleLogEvent {
+            timestamp_ms: self.timestamp_ms,
+            message: &self.message,
+        })
+    }
+
+    /// The JSON object embedded in the message, if any (anything before the
+    /// first `{` is treated as a plain-text prefix, as in `formatted`).
+    pub fn json_payload(&self) -> Option<serde_json::Value> {
+        let msg = self.message.trim();
+        let idx = msg.find('{')?;
+        serde_json::from_str(&msg[idx..]).ok()
+    }
+}
+
 pub async fn fetch_log_groups(region: &str, profile: &str) -> Result<Vec<String>, AwsLogError> {
-    let client = build_cloudwatch_client(region, profile)
-        .await
-        .map_err(|e| AwsLogError::ClientInit(e.to_string()))?;
+    let groups = fetch_log_group_details(region, profile, "").await?;
+    Ok(groups.into_iter().map(|g| g.name).collect())
+}
+
+/// A log group with the metadata `DescribeLogGroups` returns for it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogGroupInfo {
+    pub name: String,
+    pub creation_time_ms: Option<i64>,
+    /// `None` means events never expire.
+    pub retention_days: Option<i32>,
+    pub stored_bytes: Option<i64>,
+    /// Newest event in any of its streams; only set by `fill_last_event_times`.
+    pub last_event_ms: Option<i64>,
+}
+
+/// Log groups whose names start with `prefix` (all of them when empty),
+/// sorted by name.
+pub async fn fetch_log_group_details(
+    region: &str,
+    profile: &str,
+    prefix: &str,
+) -> Result<Vec<LogGroupInfo>, AwsLogError> {
+    let client = cloudwatch_client(region, profile).await?;
 
     let mut out = Vec::new();
     let mut next_token: Option<String> = None;
 
     loop {
         let mut req = client.describe_log_groups();
+        if !prefix.is_empty() {
+            req = req.log_group_name_prefix(prefix);
+        }
         if let Some(token) = &next_token {
             req = req.next_token(token);
         }
@@ -52,11 +333,18 @@ pub async fn fetch_log_groups(region: &str, profile: &str) -> Result<Vec<String>
             region: region.to_string(),
             profile: profile.to_string(),
             source: Box::new(e),
-        })?;
+        });
+        let resp = check_auth(region, profile, resp)?;
 
         for g in resp.log_groups() {
             if let Some(name) = g.log_group_name() {
-                out.push(name.to_string());
+                out.push(LogGroupInfo {
+                    name: name.to_string(),
+                    creation_time_ms: g.creation_time(),
+                    retention_days: g.retention_in_days(),
+                    stored_bytes: g.stored_bytes(),
+                    last_event_ms: None,
+                });
             }
         }
 
@@ -66,10 +354,133 @@ pub async fn fetch_log_groups(region: &str, profile: &str) -> Result<Vec<String>
         }
     }
 
-    out.sort();
+    out.sort_by(|a, b| a.name.cmp(&b.name));
     Ok(out)
 }
 
+/// How many groups `fill_last_event_times` looks up at once, to stay well
+/// under the DescribeLogStreams rate limit.
+const LAST_EVENT_LOOKUPS: usize = 8;
+
+/// Set `last_event_ms` on each group from its most recently written stream
+/// (one DescribeLogStreams call per group, a few at a time).
+pub async fn fill_last_event_times(
+    region: &str,
+    profile: &str,
+    groups: &mut [LogGroupInfo],
+) -> Result<(), AwsLogError> {
+    let client = cloudwatch_client(region, profile).await?;
+    let permits = std::sync::Arc::new(tokio::sync::Semaphore::new(LAST_EVENT_LOOKUPS));
+
+    let handles: Vec<_> = groups
+        .iter()
+        .map(|g| {
+            let (client, permits, group) = (client.clone(), permits.clone(), g.name.clone());
+            tokio::spawn(async move {
+                let _permit = permits.acquire_owned().await;
+                let resp = client
+                    .describe_log_streams()
+                    .log_group_name(&group)
+                    .order_by(cwl::types::OrderBy::LastEventTime)
+                    .descending(true)
+                    .limit(1)
+                    .send()
+                    .await
+                    .map_err(|e| AwsLogError::FetchLogStreams {
+                        group,
+                        source: Box::new(e),
+                    })?;
+                Ok(resp
+                    .log_streams()
+                    .first()
+                    .and_then(|s| s.last_event_timestamp()))
+            })
+        })
+        .collect();
+
+    for (handle, g) in handles.into_iter().zip(groups.iter_mut()) {
+        let res = handle.await.unwrap_or_else(|e| {
+            Err(AwsLogError::FetchLogStreams {
+                group: g.name.clone(),
+                source: Box::new(e),
+            })
+        });
+        g.last_event_ms = check_auth(region, profile, res)?;
+    }
+    Ok(())
+}
+
+/// A log stream with the metadata `DescribeLogStreams` returns for it.
+/// (Per-stream stored bytes are always reported as zero, so they're left out.)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogStreamInfo {
+    pub name: String,
+    pub creation_time_ms: Option<i64>,
+    pub last_event_ms: Option<i64>,
+    pub last_ingestion_ms: Option<i64>,
+}
+
+/// Streams of `log_group`, most recently written first. With a `prefix`
+/// the API only allows name order, so they come back sorted by name.
+pub async fn fetch_log_streams(
+    region: &str,
+    profile: &str,
+    log_group: &str,
+    prefix: &str,
+    limit: Option<usize>,
+) -> Result<Vec<LogStreamInfo>, AwsLogError> {
+    let client = cloudwatch_client(region, profile).await?;
+
+    let mut out = Vec::new();
+    let mut next_token: Option<String> = None;
+
+    loop {
+        let mut req = client.describe_log_streams().log_group_name(log_group);
+        if prefix.is_empty() {
+            req = req
+                .order_by(cwl::types::OrderBy::LastEventTime)
+                .descending(true);
+        } else {
+            req = req.log_stream_name_prefix(prefix);
+        }
+        if let Some(token) = &next_token {
+            req = req.next_token(token);
+        }
+
+        let resp = req.send().await.map_err(|e| AwsLogError::FetchLogStreams {
+            group: log_group.to_string(),
+            source: Box::new(e),
+        });
+        let resp = check_auth(region, profile, resp)?;
+
+        for s in resp.log_streams() {
+            if let Some(name) = s.log_stream_name() {
+                out.push(LogStreamInfo {
+                    name: name.to_string(),
+                    creation_time_ms: s.creation_time(),
+                    last_event_ms: s.last_event_timestamp(),
+                    last_ingestion_ms: s.last_ingestion_time(),
+                });
+            }
+        }
+
+        if let Some(limit) = limit
+            && out.len() >= limit
+        {
+            out.truncate(limit);
+            break;
+        }
+        next_token = resp.next_token().map(|s| s.to_string());
+        if next_token.is_none() {
+            break;
+        }
+    }
+
+    Ok(out)
+}
+
+/// Events in one group; with a `limit`, paging stops once that many have
+/// been collected.
 pub async fn fetch_log_events(
     region: &str,
     profile: &str,
@@ -77,38 +488,176 @@ pub async fn fetch_log_events(
     start: &str,
     end: &str,
     pattern: &str,
-) -> Result<(Vec<String>, Option<i64>), AwsLogError> {
-    let client = build_cloudwatch_client(region, profile)
-        .await
-        .map_err(|e| AwsLogError::ClientInit(e.to_string()))?;
+    limit: Option<usize>,
+) -> Result<(Vec<LogEvent>, Option<i64>), AwsLogError> {
+    let groups = [log_group.to_string()];
+    fetch_log_events_multi(region, profile, &groups, &[], start, end, pattern, limit).await
+}
 
-    let now_ms = Utc::now().timestamp_millis();
-    let start_ms = if start.trim().is_empty() {
-        // default: last 15m
-        now_ms - 15 * 60 * 1_000
-    } else {
-        parse_relative_or_absolute_ms(start, now_ms).map_err(|reason| AwsLogError::TimeParse {
-            value: start.to_string(),
-            reason,
-        })?
-    };
+/// Events from several groups, optionally only from streams whose names
+/// start with one of `stream_prefixes`, merged into timestamp order.
+///
+/// With a `limit`, each group/prefix stops paging after `limit` events and
+/// the merged result is cut to the earliest `limit`.
+#[allow(clippy::too_many_arguments)]
+pub async fn fetch_log_events_multi(
+    region: &str,
+    profile: &str,
+    log_groups: &[String],
+    stream_prefixes: &[String],
+    start: &str,
+    end: &str,
+    pattern: &str,
+    limit: Option<usize>,
+) -> Result<(Vec<LogEvent>, Option<i64>), AwsLogError> {
+    let client = cloudwatch_client(region, profile).await?;
 
-    let end_ms = if end.trim().is_empty() {
-        now_ms
+    let window = resolve_time_window(start, end, Utc::now().timestamp_millis())?;
+    let normalized_pattern = normalize_filter_pattern(pattern);
+    let prefixes: Vec<Option<&str>> = if stream_prefixes.is_empty() {
+        vec![None]
     } else {
-        parse_relative_or_absolute_ms(end, now_ms).map_err(|reason| AwsLogError::TimeParse {
-            value: end.to_string(),
-            reason,
-        })?
+        stream_prefixes.iter().map(|p| Some(p.as_str())).collect()
     };
 
+    let mut out = Vec::new();
+    for group in log_groups {
+        for prefix in &prefixes {
+            let res =
+                filter_events(&client, group, *prefix, window, &normalized_pattern, limit).await;
+            let (events, _) = check_auth(region, profile, res)?;
+            out.extend(events);
+        }
+    }
+
+    let (mut events, mut last_ts) = merge_events(out);
+    if let Some(limit) = limit.filter(|&limit| events.len() > limit) {
+        events.truncate(limit);
+        last_ts = events.last().map(|ev| ev.timestamp_ms);
+    }
+    Ok((events, last_ts))
+}
+
+/// Groups to search in one profile/region.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GroupTarget {
+    pub profile: String,
+    pub region: String,
+    pub groups: Vec<String>,
+}
+
+/// What `fetch_log_events_targets` got: the merged events of every context
+/// that answered, and the error of each one that didn't.
+#[derive(Debug, Default)]
+pub struct TargetsFetch {
+    pub events: Vec<LogEvent>,
+    pub last_ts: Option<i64>,
+    pub failures: Vec<(GroupTarget, AwsLogError)>,
+}
+
+/// Events from groups spread over several profiles/regions, each context
+/// fetched concurrently with its own client, merged into timestamp order.
+/// A context that fails doesn't cost the others their events.
+pub async fn fetch_log_events_targets(
+    targets: &[GroupTarget],
+    stream_prefixes: &[String],
+    start: &str,
+    end: &str,
+    pattern: &str,
+) -> TargetsFetch {
+    let handles: Vec<_> = targets
+        .iter()
+        .cloned()
+        .map(|target| {
+            let streams = stream_prefixes.to_vec();
+            let (start, end, pattern) = (start.to_string(), end.to_string(), pattern.to_string());
+            tokio::spawn(async move {
+                fetch_log_events_multi(
+                    &target.region,
+                    &target.profile,
+                    &target.groups,
+                    &streams,
+                    &start,
+                    &end,
+                    &pattern,
+                    None,
+                )
+                .await
+            })
+        })
+        .collect();
+
+    let mut out = Vec::new();
+    let mut failures = Vec::new();
+    for (handle, target) in handles.into_iter().zip(targets) {
+        let res = handle.await.unwrap_or_else(|e| {
+            Err(AwsLogError::FetchLogEvents {
+                group: target.groups.join(", "),
+                source: Box::new(e),
+            })
+        });
+        match res {
+            Ok((events, _)) => out.extend(events),
+            Err(e) => failures.push((target.clone(), e)),
+        }
+    }
+    let (events, last_ts) = merge_events(out);
+    TargetsFetch {
+        events,
+        last_ts,
+        failures,
+    }
+}
+
+/// Group names for each `(profile, region)`, fetched concurrently; results
+/// come back in the order given.
+pub async fn fetch_log_groups_many(
+    contexts: &[(String, String)],
+) -> Vec<Result<Vec<String>, AwsLogError>> {
+    let handles: Vec<_> = contexts
+        .iter()
+        .cloned()
+        .map(|(profile, region)| {
+            tokio::spawn(async move { fetch_log_groups(&region, &profile).await })
+        })
+        .collect();
+
+    let mut out = Vec::new();
+    for (handle, (profile, region)) in handles.into_iter().zip(contexts) {
+        out.push(handle.await.unwrap_or_else(|e| {
+            Err(AwsLogError::FetchLogGroups {
+                region: region.clone(),
+                profile: profile.clone(),
+                source: Box::new(e),
+            })
+        }));
+    }
+    out
+}
+
+/// Sort by timestamp and drop repeats (overlapping stream prefixes return
+/// the same event twice); also returns the newest timestamp.
+pub fn merge_events(mut events: Vec<LogEvent>) -> (Vec<LogEvent>, Option<i64>) {
+    events.sort_by_key(|ev| ev.timestamp_ms);
+    let mut seen = std::collections::HashSet::new();
+    events.retain(|ev| ev.event_id.is_empty() || seen.insert(ev.event_id.clone()));
+    let last_ts = events.iter().map(|ev| ev.timestamp_ms).max();
+    (events, last_ts)
+}
+
+/// Page through `FilterLogEvents` for one group.
+async fn filter_events(
+    client: &cwl::Client,
+    log_group: &str,
+    stream_prefix: Option<&str>,
+    (start_ms, end_ms): (i64, i64),
+    normalized_pattern: &str,
+    limit: Option<usize>,
+) -> Result<(Vec<LogEvent>, Option<i64>), AwsLogError> {
     let mut out = Vec::new();
     let mut last_ts: Option<i64> = None;
     let mut next_token: Option<String> = None;
 
-    // normalize the pattern once up-front
-    let normalized_pattern = normalize_filter_pattern(pattern);
-
     loop {
         let mut req = client
             .filter_log_events()
@@ -117,11 +666,18 @@ pub async fn fetch_log_events(
             .end_time(end_ms);
 
         if !normalized_pattern.trim().is_empty() {
-            req = req.filter_pattern(&normalized_pattern);
+            req = req.filter_pattern(normalized_pattern);
+        }
+        if let Some(prefix) = stream_prefix {
+            req = req.log_stream_name_prefix(prefix);
         }
         if let Some(tok) = &next_token {
             req = req.next_token(tok);
         }
+        if let Some(limit) = limit {
+            // The API caps a page at 10,000 events
+            req = req.limit((limit - out.len()).min(10_000) as i32);
+        }
 
         let resp = req.send().await.map_err(|e| AwsLogError::FetchLogEvents {
             group: log_group.to_string(),
@@ -138,18 +694,19 @@ pub async fn fetch_log_events(
             } else {
                 last_ts = Some(ts);
             }
-            let msg = ev.message().unwrap_or("");
-
-            let simple = SimpleLogEvent {
+            out.push(LogEvent {
                 timestamp_ms: ts,
-                message: msg,
-            };
-
-            out.push(format_log_event(&simple));
+                message: ev.message().unwrap_or("").to_string(),
+                log_stream_name: ev.log_stream_name().unwrap_or("").to_string(),
+                event_id: ev.event_id().unwrap_or("").to_string(),
+            });
         }
 
         let new_token = resp.next_token().map(|s| s.to_string());
-        if new_token.is_none() || new_token == next_token {
+        if new_token.is_none()
+            || new_token == next_token
+            || limit.is_some_and(|limit| out.len() >= limit)
+        {
             break;
         }
         next_token = new_token;
@@ -158,6 +715,32 @@ pub async fn fetch_log_events(
     Ok((out, last_ts))
 }
 
+/// Resolve the Start/End filter fields into an absolute `(start_ms, end_ms)` window.
+///
+/// An empty start defaults to the last 15 minutes; an empty end means "now".
+pub fn resolve_time_window(start: &str, end: &str, now_ms: i64) -> Result<(i64, i64), AwsLogError> {
+    let start_ms = if start.trim().is_empty() {
+        // default: last 15m
+        now_ms - 15 * 60 * 1_000
+    } else {
+        parse_relative_or_absolute_ms(start, now_ms).map_err(|reason| AwsLogError::TimeParse {
+            value: start.to_string(),
+            reason,
+        })?
+    };
+
+    let end_ms = if end.trim().is_empty() {
+        now_ms
+    } else {
+        parse_relative_or_absolute_ms(end, now_ms).map_err(|reason| AwsLogError::TimeParse {
+            value: end.to_string(),
+            reason,
+        })?
+    };
+
+    Ok((start_ms, end_ms))
+}
+
 fn format_log_event(ev: &SimpleLogEvent<'_>) -> String {
     let ts_str = match chrono::DateTime::<Utc>::from_timestamp_millis(ev.timestamp_ms) {
         Some(dt) => dt.to_rfc3339(),
@@ -206,9 +789,8 @@ fn parse_relative_or_absolute_ms(s: &str, now_ms: i64) -> Result<i64, String> {
 
     // Relative syntax: -5m, -1h, -2d, -30s
     // Accept: optional leading '-', then number, then unit
-    if trimmed.starts_with('-') {
+    if let Some(rest) = trimmed.strip_prefix('-') {
         // strip leading '-'
-        let rest = &trimmed[1..];
         // split into numeric prefix and unit suffix
         let (num_str, unit) = rest
             .chars()
@@ -257,7 +839,7 @@ fn pretty_json_if_possible(s: &str) -> Option<String> {
     serde_json::to_string_pretty(&v).ok()
 }
 
-fn normalize_filter_pattern(raw: &str) -> String {
+pub fn normalize_filter_pattern(raw: &str) -> String {
     let trimmed = raw.trim();
     if trimmed.is_empty() {
         return String::new();
@@ -265,30 +847,25 @@ fn normalize_filter_pattern(raw: &str) -> String {
 
     // If it already looks like a CloudWatch filter expression, don't touch it.
     // Examples: "{ $.routing_id = 123 }", "ERROR", "[level = \"error\"]"
-    if trimmed.starts_with('{') || trimmed.starts_with('[') || trimmed.contains('$') {
+    // A `$` inside a quoted value (`version="$LATEST"`) is just text.
+    if trimmed.starts_with('{') || trimmed.starts_with('[') || has_unquoted_dollar(trimmed) {
         return trimmed.to_string();
     }
 
-    // Support multiple "field=value" or "field:value" pairs separated by whitespace:
+    // Support multiple "field=value", "field!=value" or "field:value" pairs
+    // separated by whitespace (quoted values may contain spaces):
     //   routing_id=123 task="batch-attendances"
     //     -> { $.routing_id = 123 && $.task = "batch-attendances" }
     //
     // If parsing fails, fall back to the original string.
     let mut conditions = Vec::new();
 
-    for token in trimmed.split_whitespace() {
-        if let Some((field, value)) = token.split_once('=') {
-            let field = field.trim();
-            let value = value.trim();
-            if !field.is_empty() && !value.is_empty() {
-                conditions.push(format!("$.{} = {}", field, value));
-                continue;
-            }
-        } else if let Some((field, value)) = token.split_once(':') {
+    for token in split_shorthand_tokens(trimmed) {
+        if let Some((field, op, value)) = split_shorthand_condition(token) {
             let field = field.trim();
             let value = value.trim();
             if !field.is_empty() && !value.is_empty() {
-                conditions.push(format!("$.{} = {}", field, value));
+                conditions.push(format!("$.{} {} {}", field, op, value));
                 continue;
             }
         }
@@ -306,18 +883,266 @@ fn normalize_filter_pattern(raw: &str) -> String {
     }
 }
 
-async fn build_cloudwatch_client(region: &str, profile: &str) -> Result<cwl::Client, AwsLogError> {
+/// Whether `s` has a `$` outside double-quoted values, i.e. a filter
+/// expression selector rather than part of a value.
+fn has_unquoted_dollar(s: &str) -> bool {
+    let mut in_quotes = false;
+    let mut escaped = false;
+
+    for c in s.chars() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' if in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            '$' if !in_quotes => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Split on whitespace, except inside double-quoted values (`msg="a b"`).
+fn split_shorthand_tokens(s: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start: Option<usize> = None;
+    let mut in_quotes = false;
+    let mut escaped = false;
+
+    for (i, c) in s.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' if in_quotes => escaped = true,
+            '"' => {
+                in_quotes = !in_quotes;
+                start.get_or_insert(i);
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if let Some(st) = start.take() {
+                    tokens.push(&s[st..i]);
+                }
+            }
+            _ => {
+                start.get_or_insert(i);
+            }
+        }
+    }
+    if let Some(st) = start {
+        tokens.push(&s[st..]);
+    }
+    tokens
+}
+
+/// Split `field=value`, `field!=value` or `field:value` at the first
+/// operator outside double quotes, so `msg="a != b"` keeps its value whole.
+/// Returns the field, the filter operator (`:` becomes `=`) and the value.
+fn split_shorthand_condition(token: &str) -> Option<(&str, &'static str, &str)> {
+    let mut in_quotes = false;
+    let mut escaped = false;
+
+    for (i, c) in token.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' if in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            _ if in_quotes => {}
+            '!' if token[i + 1..].starts_with('=') => {
+                return Some((&token[..i], "!=", &token[i + 2..]));
+            }
+            '=' | ':' => return Some((&token[..i], "=", &token[i + 1..])),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Region used when neither the CLI, the environment, the profile nor the
+/// config file name one.
+pub const FALLBACK_REGION: &str = "eu-west-1";
+
+/// Region from the standard AWS chain (`AWS_REGION`, the profile's config),
+/// falling back to `fallback`. An empty profile means the default one; a
+/// configured role uses the profile it is assumed from.
+pub async fn default_region(profile: &str, fallback: &str) -> String {
+    let profile = roles::base_profile(profile);
+    let mut chain = aws_config::default_provider::region::DefaultRegionChain::builder();
+    if !profile.is_empty() {
+        chain = chain.profile_name(&profile);
+    }
+    RegionProviderChain::first_try(chain.build())
+        .or_else(Region::new(fallback.to_string()))
+        .region()
+        .await
+        .map(|r| r.to_string())
+        .unwrap_or_else(|| fallback.to_string())
+}
+
+/// One runtime for every AWS call. Clients keep their connection pools on
+/// the runtime that first used them, so it has to outlive all of them.
+pub fn runtime() -> &'static tokio::runtime::Runtime {
+    static RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| tokio::runtime::Runtime::new().expect("tokio runtime"))
+}
+
+/// Environment variable pointing CloudWatch Logs calls elsewhere, e.g.
+/// LocalStack; `--endpoint-url` beats it and it beats the config file.
+pub const ENDPOINT_URL_ENV: &str = "LUMBERJACK_ENDPOINT_URL";
+
+/// Endpoint URLs must be absolute http(s) URLs.
+pub fn check_endpoint_url(url: &str) -> Result<(), String> {
+    match url.split_once("://") {
+        Some(("http" | "https", host)) if !host.is_empty() => Ok(()),
+        _ => Err(format!("'{url}' is not like http://localhost:4566")),
+    }
+}
+
+/// `--endpoint-url`, else `LUMBERJACK_ENDPOINT_URL` (if set and non-empty).
+/// An invalid URL is reported against whichever of the two it came from.
+pub fn endpoint_override(flag: Option<String>) -> Result<Option<String>, String> {
+    let url = match flag {
+        Some(url) => Some(("--endpoint-url", url)),
+        None => std::env::var(ENDPOINT_URL_ENV)
+            .ok()
+            .filter(|url| !url.trim().is_empty())
+            .map(|url| (ENDPOINT_URL_ENV, url)),
+    };
+    match url {
+        Some((source, url)) => match check_endpoint_url(&url) {
+            Ok(()) => Ok(Some(url)),
+            Err(e) => Err(format!("invalid {source} {e}")),
+        },
+        None => Ok(None),
+    }
+}
+
+/// The explicit endpoint (flag or environment) and the config file's.
+#[derive(Default)]
+struct Endpoints {
+    explicit: Option<String>,
+    config: Option<String>,
+}
+
+fn endpoints() -> &'static Mutex<Endpoints> {
+    static ENDPOINTS: OnceLock<Mutex<Endpoints>> = OnceLock::new();
+    ENDPOINTS.get_or_init(Default::default)
+}
+
+/// Where CloudWatch Logs calls go; None means the real AWS endpoint.
+pub fn endpoint_url() -> Option<String> {
+    let endpoints = endpoints().lock().unwrap();
+    endpoints
+        .explicit
+        .clone()
+        .or_else(|| endpoints.config.clone())
+}
+
+/// Set the endpoints (the config one again after a profile switch).
+/// Cached clients are dropped when the effective endpoint changes.
+pub fn set_endpoint_urls(explicit: Option<String>, config: Option<String>) {
+    let before = endpoint_url();
+    *endpoints().lock().unwrap() = Endpoints { explicit, config };
+    if endpoint_url() != before {
+        client_cache().lock().unwrap().clear();
+    }
+}
+
+/// Replace just the config file's endpoint.
+pub fn set_config_endpoint_url(config: Option<String>) {
+    let explicit = endpoints().lock().unwrap().explicit.clone();
+    set_endpoint_urls(explicit, config);
+}
+
+/// Clients by `(profile, region)`, shared by every worker thread.
+fn client_cache() -> &'static Mutex<HashMap<(String, String), cwl::Client>> {
+    static CLIENTS: OnceLock<Mutex<HashMap<(String, String), cwl::Client>>> = OnceLock::new();
+    CLIENTS.get_or_init(Default::default)
+}
+
+/// The cached client for `profile`/`region`, built (config and credential
+/// chain loaded) on first use only.
+async fn cloudwatch_client(region: &str, profile: &str) -> Result<cwl::Client, AwsLogError> {
+    let key = (profile.to_string(), region.to_string());
+    if let Some(client) = client_cache().lock().unwrap().get(&key) {
+        return Ok(client.clone());
+    }
+    let client = build_cloudwatch_client(region, profile)
+        .await
+        .map_err(|e| AwsLogError::ClientInit(e.to_string()))?;
+    Ok(client_cache()
+        .lock()
+        .unwrap()
+        .entry(key)
+        .or_insert(client)
+        .clone())
+}
+
+/// Forget the client for `profile`/`region`; the next call builds a new
+/// one and resolves credentials again (e.g. after `aws sso login`), which
+/// for a role means assuming it again.
+pub fn invalidate_client(region: &str, profile: &str) {
+    client_cache()
+        .lock()
+        .unwrap()
+        .remove(&(profile.to_string(), region.to_string()));
+    roles::forget_role_credentials(profile);
+}
+
+/// Classify auth failures, and forget the cached client when its
+/// credentials are the problem.
+fn check_auth<T>(
+    region: &str,
+    profile: &str,
+    res: Result<T, AwsLogError>,
+) -> Result<T, AwsLogError> {
+    res.map_err(|e| {
+        let e = e.classify_auth(region, profile);
+        if e.is_credential_error() {
+            invalidate_client(region, profile);
+        }
+        e
+    })
+}
+
+/// SDK config for `profile` in `region`, shared by the CloudWatch clients
+/// and the STS calls that assume roles.
+pub async fn load_sdk_config(profile: &str, region: &str) -> aws_config::SdkConfig {
     let region_provider = RegionProviderChain::first_try(Some(Region::new(region.to_string())))
         .or_default_provider()
-        .or_else(Region::new("eu-west-1"));
-
-    let cfg = aws_config::defaults(aws_config::BehaviorVersion::latest())
-        .region(region_provider)
-        .profile_name(profile)
-        .load()
-        .await;
+        .or_else(Region::new(FALLBACK_REGION));
+
+    // An empty profile leaves credentials to the standard chain (env vars,
+    // default profile, SSO, instance/container roles).
+    // A configured role assumes itself from its source profile instead.
+    let mut loader =
+        aws_config::defaults(aws_config::BehaviorVersion::latest()).region(region_provider);
+    if roles::role(profile).is_some() {
+        loader = loader.credentials_provider(roles::RoleProvider {
+            name: profile.to_string(),
+            region: region.to_string(),
+        });
+    } else if !profile.is_empty() {
+        loader = loader.profile_name(profile);
+    }
+    loader.load().await
+}
 
-    Ok(cwl::Client::new(&cfg))
+async fn build_cloudwatch_client(region: &str, profile: &str) -> Result<cwl::Client, AwsLogError> {
+    let cfg = load_sdk_config(profile, region).await;
+    // The endpoint override is for CloudWatch Logs only; role assumption
+    // keeps talking to the real STS
+    let mut conf = cwl::config::Builder::from(&cfg);
+    if let Some(url) = endpoint_url() {
+        conf = conf.endpoint_url(url);
+    }
+    Ok(cwl::Client::from_conf(conf.build()))
 }
 
 #[cfg(test)]
@@ -467,6 +1292,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn log_event_json_payload_skips_text_prefix() {
+        let ev = LogEvent {
+            timestamp_ms: 0,
+            message: "INFO {\"a\":1}".to_string(),
+            log_stream_name: String::new(),
+            event_id: String::new(),
+        };
+        assert_eq!(ev.json_payload(), Some(serde_json::json!({"a": 1})));
+
+        let plain = LogEvent {
+            message: "INFO nothing here".to_string(),
+            ..ev
+        };
+        assert!(plain.json_payload().is_none());
+    }
+
     #[test]
     fn normalize_filter_pattern_leaves_full_syntax_untouched() {
         let raw = "{ $.routing_id = 123 }";
@@ -662,6 +1504,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn normalize_filter_pattern_parses_not_equals_shorthand() {
+        let out = normalize_filter_pattern(r#"level!="debug" routing_id=1"#);
+        assert_eq!(out, r#"{ $.level != "debug" && $.routing_id = 1 }"#);
+    }
+
+    #[test]
+    fn normalize_filter_pattern_keeps_quoted_values_with_spaces() {
+        let out = normalize_filter_pattern(r#"msg="task \"x\" failed" env=prod"#);
+        assert_eq!(out, r#"{ $.msg = "task \"x\" failed" && $.env = prod }"#);
+    }
+
+    #[test]
+    fn normalize_filter_pattern_allows_dollar_inside_quoted_values() {
+        let out = normalize_filter_pattern(r#"version="$LATEST" env=prod"#);
+        assert_eq!(out, r#"{ $.version = "$LATEST" && $.env = prod }"#);
+        // Outside quotes it's still a selector and the pattern is left alone
+        assert_eq!(normalize_filter_pattern("$.a=1"), "$.a=1");
+    }
+
+    #[test]
+    fn normalize_filter_pattern_ignores_operators_inside_quotes() {
+        let out = normalize_filter_pattern(r#"msg="a != b" env=prod"#);
+        assert_eq!(out, r#"{ $.msg = "a != b" && $.env = prod }"#);
+        let out = normalize_filter_pattern(r#"msg!="x=y""#);
+        assert_eq!(out, r#"{ $.msg != "x=y" }"#);
+        let out = normalize_filter_pattern(r#"msg="x!=y" url:"a:b""#);
+        assert_eq!(out, r#"{ $.msg = "x!=y" && $.url = "a:b" }"#);
+    }
+
     #[test]
     fn normalize_filter_pattern_bails_out_on_mixed_tokens() {
         let input = "routing_id=123 weird-token";
@@ -669,4 +1541,141 @@ mod tests {
         // Should fall back to the original string when it can't interpret tokens
         assert_eq!(out, input);
     }
+
+    #[test]
+    fn merged_events_are_ordered_and_deduplicated() {
+        let ev = |ts: i64, id: &str| LogEvent {
+            timestamp_ms: ts,
+            message: String::new(),
+            log_stream_name: "s".to_string(),
+            event_id: id.to_string(),
+        };
+        let (events, last) = merge_events(vec![ev(30, "c"), ev(10, "a"), ev(20, "b"), ev(10, "a")]);
+        let ids: Vec<&str> = events.iter().map(|e| e.event_id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b", "c"]);
+        assert_eq!(last, Some(30));
+    }
+
+    /// A failed FilterLogEvents call the way the SDK reports it.
+    fn service_error(code: &str, message: &str) -> Box<dyn std::error::Error + Send + Sync> {
+        use cwl::error::{ErrorMetadata, SdkError};
+        use cwl::operation::filter_log_events::FilterLogEventsError;
+
+        let meta = ErrorMetadata::builder().code(code).message(message).build();
+        Box::new(SdkError::service_error(
+            FilterLogEventsError::generic(meta),
+            (),
+        ))
+    }
+
+    fn classified(source: Box<dyn std::error::Error + Send + Sync>, profile: &str) -> AwsLogError {
+        AwsLogError::FetchLogEvents {
+            group: "g".to_string(),
+            source,
+        }
+        .classify_auth("eu-west-1", profile)
+    }
+
+    #[test]
+    fn classifies_auth_errors() {
+        let expired = classified(
+            service_error("ExpiredTokenException", "The security token is expired"),
+            "prod",
+        );
+        assert!(matches!(expired, AwsLogError::ExpiredCredentials { .. }));
+        assert!(expired.is_credential_error());
+        let auth = expired.auth_failure().unwrap();
+        assert_eq!(auth.kind, AuthErrorKind::Expired);
+        assert_eq!(auth.profile, "prod");
+
+        let rejected = classified(service_error("UnrecognizedClientException", ""), "prod");
+        assert!(rejected.is_credential_error());
+
+        let denied = classified(service_error("AccessDeniedException", ""), "prod");
+        assert_eq!(
+            denied.auth_failure().map(|a| a.kind),
+            Some(AuthErrorKind::AccessDenied)
+        );
+        assert!(!denied.is_credential_error());
+
+        // Credentials the SDK couldn't load, behind the dispatch failure
+        let not_loaded = || Box::new(CredentialsError::provider_error("SSO session expired"));
+        let sso = classified(not_loaded(), "");
+        assert_eq!(
+            sso.auth_failure().map(|a| a.kind),
+            Some(AuthErrorKind::Expired)
+        );
+        let missing = classified(not_loaded(), "lumberjack-test-no-such-profile");
+        assert_eq!(
+            missing.auth_failure().map(|a| a.kind),
+            Some(AuthErrorKind::MissingProfile)
+        );
+    }
+
+    #[test]
+    fn expired_message_suggests_sso_login_only_for_sso_profiles() {
+        let expired = |sso_profile: Option<&str>| AwsLogError::ExpiredCredentials {
+            profile: "prod".to_string(),
+            region: "eu-west-1".to_string(),
+            sso_profile: sso_profile.map(str::to_string),
+            source: "expired".into(),
+        };
+        assert_eq!(
+            expired(Some("sso-hub")).to_string(),
+            "Credentials for profile 'prod' have expired or were rejected; \
+             log in again (e.g. `aws sso login --profile sso-hub`) and retry"
+        );
+        assert_eq!(
+            expired(None).to_string(),
+            "Credentials for profile 'prod' have expired or were rejected; refresh them and retry"
+        );
+    }
+
+    #[test]
+    fn other_errors_are_not_auth_failures() {
+        let not_auth = |source| {
+            let e = classified(source, "");
+            assert!(matches!(e, AwsLogError::FetchLogEvents { .. }), "{e}");
+        };
+        // Mentioning expiry or a credentials error by name isn't enough
+        not_auth(service_error(
+            "ResourceNotFoundException",
+            "The export task expired before the log group was created",
+        ));
+        not_auth(service_error(
+            "InvalidSignatureException",
+            "Signature expired",
+        ));
+        not_auth("ExpiredTokenException: ProviderError, token expired".into());
+        not_auth(Box::new(CredentialsError::provider_timed_out(
+            std::time::Duration::from_secs(5),
+        )));
+    }
+
+    #[test]
+    fn bad_endpoint_is_reported_against_its_source() {
+        assert_eq!(
+            endpoint_override(Some("http://localhost:4566".to_string())),
+            Ok(Some("http://localhost:4566".to_string()))
+        );
+        let err = endpoint_override(Some("localhost:4566".to_string())).unwrap_err();
+        assert!(err.starts_with("invalid --endpoint-url "), "{err}");
+    }
+
+    #[test]
+    fn clients_are_cached_until_invalidated() {
+        let key = ("cache-test".to_string(), "ap-south-2".to_string());
+        runtime()
+            .block_on(cloudwatch_client("ap-south-2", "cache-test"))
+            .unwrap();
+        assert!(client_cache().lock().unwrap().contains_key(&key));
+
+        let failed: Result<(), AwsLogError> = Err(AwsLogError::FetchLogGroups {
+            region: "ap-south-2".to_string(),
+            profile: "cache-test".to_string(),
+            source: service_error("UnrecognizedClientException", ""),
+        });
+        assert!(check_auth("ap-south-2", "cache-test", failed).is_err());
+        assert!(!client_cache().lock().unwrap().contains_key(&key));
+    }
 }
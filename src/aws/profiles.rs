@@ -0,0 +1,144 @@
+use std::path::PathBuf;
+
+/// Regions where CloudWatch Logs is available, in the console's order.
+pub const CLOUDWATCH_REGIONS: &[&str] = &[
+    "us-east-1",
+    "us-east-2",
+    "us-west-1",
+    "us-west-2",
+    "af-south-1",
+    "ap-east-1",
+    "ap-south-1",
+    "ap-south-2",
+    "ap-southeast-1",
+    "ap-southeast-2",
+    "ap-southeast-3",
+    "ap-southeast-4",
+    "ap-southeast-5",
+    "ap-southeast-7",
+    "ap-northeast-1",
+    "ap-northeast-2",
+    "ap-northeast-3",
+    "ca-central-1",
+    "ca-west-1",
+    "eu-central-1",
+    "eu-central-2",
+    "eu-west-1",
+    "eu-west-2",
+    "eu-west-3",
+    "eu-south-1",
+    "eu-south-2",
+    "eu-north-1",
+    "il-central-1",
+    "me-south-1",
+    "me-central-1",
+    "mx-central-1",
+    "sa-east-1",
+];
+
+/// Section names from an AWS ini file. In `config` profiles are written
+/// `[profile name]` (only `default` goes without the prefix) and other
+/// sections (`[sso-session x]`, `[services x]`) aren't profiles; in
+/// `credentials` every section is a profile.
+pub fn parse_profile_names(data: &str, is_config: bool) -> Vec<String> {
+    data.lines()
+        .filter_map(|line| {
+            let section = line.trim().strip_prefix('[')?.strip_suffix(']')?.trim();
+            if !is_config {
+                return Some(section.to_string());
+            }
+            match section.strip_prefix("profile ") {
+                Some(name) => Some(name.trim().to_string()),
+                None if section == "default" => Some(section.to_string()),
+                None => None,
+            }
+        })
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+fn aws_file(env_var: &str, default_name: &str) -> Option<PathBuf> {
+    if let Ok(path) = std::env::var(env_var)
+        && !path.is_empty()
+    {
+        return Some(PathBuf::from(path));
+    }
+    let home = std::env::var("HOME").ok()?;
+    Some(PathBuf::from(home).join(".aws").join(default_name))
+}
+
+/// Profiles from `~/.aws/config` and `~/.aws/credentials` (or the files
+/// named by `AWS_CONFIG_FILE` / `AWS_SHARED_CREDENTIALS_FILE`), sorted and
+/// without duplicates. Missing files just contribute nothing.
+pub fn list_profiles() -> Vec<String> {
+    let mut names = Vec::new();
+    for (env_var, file, is_config) in [
+        ("AWS_CONFIG_FILE", "config", true),
+        ("AWS_SHARED_CREDENTIALS_FILE", "credentials", false),
+    ] {
+        if let Some(data) = aws_file(env_var, file).and_then(|p| std::fs::read_to_string(p).ok()) {
+            names.extend(parse_profile_names(&data, is_config));
+        }
+    }
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// True when `profile`'s section in an AWS config file signs in through
+/// IAM Identity Center (any `sso_*` key), i.e. `aws sso login` renews it.
+pub fn section_uses_sso(config: &str, profile: &str) -> bool {
+    let mut in_section = false;
+    for line in config.lines().map(str::trim) {
+        if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            let section = section.trim();
+            in_section = section.strip_prefix("profile ").map(str::trim) == Some(profile)
+                || (profile == "default" && section == "default");
+        } else if in_section && line.starts_with("sso_") {
+            return true;
+        }
+    }
+    false
+}
+
+/// `section_uses_sso` against `~/.aws/config` (or `AWS_CONFIG_FILE`).
+pub fn profile_uses_sso(profile: &str) -> bool {
+    aws_file("AWS_CONFIG_FILE", "config")
+        .and_then(|p| std::fs::read_to_string(p).ok())
+        .is_some_and(|data| section_uses_sso(&data, profile))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_profiles_from_config_and_credentials() {
+        let config = "\
+[default]
+region = eu-west-1
+
+[profile prod]
+sso_session = corp
+
+[sso-session corp]
+sso_region = eu-west-1
+
+[ profile staging ]
+";
+        assert_eq!(
+            parse_profile_names(config, true),
+            vec!["default", "prod", "staging"]
+        );
+
+        let credentials = "[default]\naws_access_key_id = x\n\n[legacy]\n";
+        assert_eq!(
+            parse_profile_names(credentials, false),
+            vec!["default", "legacy"]
+        );
+
+        assert!(section_uses_sso(config, "prod"));
+        assert!(!section_uses_sso(config, "default"));
+        assert!(!section_uses_sso(config, "staging"));
+    }
+}
@@ -0,0 +1,257 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::{Duration, SystemTime};
+
+use aws_credential_types::Credentials;
+use aws_credential_types::provider::error::CredentialsError;
+use aws_credential_types::provider::{ProvideCredentials, future};
+use aws_sdk_sts as sts;
+use serde::Deserialize;
+
+/// Refresh temporary credentials this long before they expire.
+const EXPIRY_MARGIN: Duration = Duration::from_secs(5 * 60);
+
+/// A role reached by `AssumeRole` from another profile (or another role, for
+/// chaining). Named in the config's `roles` and then used anywhere a profile
+/// name goes: `--profile`, `contexts`, the `P` picker.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct RoleSpec {
+    /// Profile (or role) whose credentials assume this one; empty means the
+    /// default credential chain.
+    pub source_profile: String,
+    pub role_arn: String,
+    pub external_id: Option<String>,
+    /// Defaults to "lumberjack".
+    pub session_name: Option<String>,
+    /// MFA device ARN; the code is asked for whenever new credentials are
+    /// needed.
+    pub mfa_serial: Option<String>,
+    pub duration_secs: Option<i32>,
+}
+
+/// Check `roles` for missing ARNs and `source_profile` loops.
+pub fn validate_roles(roles: &BTreeMap<String, RoleSpec>) -> Result<(), String> {
+    for (name, role) in roles {
+        if !role.role_arn.starts_with("arn:") {
+            return Err(format!("roles.{name}: role_arn must be an ARN"));
+        }
+        let mut seen = vec![name.as_str()];
+        let mut source = role.source_profile.as_str();
+        while let Some(next) = roles.get(source) {
+            if seen.contains(&source) {
+                return Err(format!(
+                    "roles.{name}: source_profile loops through {}",
+                    seen.join(" -> ")
+                ));
+            }
+            seen.push(source);
+            source = next.source_profile.as_str();
+        }
+    }
+    Ok(())
+}
+
+/// Asks for an MFA code: `(role name, mfa serial)` in, the code (or None to
+/// give up) out. Called off the async workers, so it may block.
+pub type MfaPrompt = dyn Fn(&str, &str) -> Option<String> + Send + Sync;
+
+fn registry() -> &'static RwLock<BTreeMap<String, RoleSpec>> {
+    static ROLES: OnceLock<RwLock<BTreeMap<String, RoleSpec>>> = OnceLock::new();
+    ROLES.get_or_init(Default::default)
+}
+
+fn mfa_prompt() -> &'static RwLock<Option<Arc<MfaPrompt>>> {
+    static PROMPT: OnceLock<RwLock<Option<Arc<MfaPrompt>>>> = OnceLock::new();
+    PROMPT.get_or_init(Default::default)
+}
+
+/// Temporary credentials for one role; its lock is held while assuming it
+/// so concurrent fetches ask for one MFA code, not several.
+type CredentialSlot = Arc<tokio::sync::Mutex<Option<Credentials>>>;
+
+/// Slots by role name, shared by every client. One lock per role, so a
+/// chained role can assume its source while holding its own.
+fn credential_slots() -> &'static Mutex<HashMap<String, CredentialSlot>> {
+    static SLOTS: OnceLock<Mutex<HashMap<String, CredentialSlot>>> = OnceLock::new();
+    SLOTS.get_or_init(Default::default)
+}
+
+fn credential_slot(name: &str) -> CredentialSlot {
+    credential_slots()
+        .lock()
+        .unwrap()
+        .entry(name.to_string())
+        .or_default()
+        .clone()
+}
+
+/// Make the configured roles usable as profile names.
+pub fn set_roles(roles: BTreeMap<String, RoleSpec>) {
+    *registry().write().unwrap() = roles;
+}
+
+pub fn role_names() -> Vec<String> {
+    registry().read().unwrap().keys().cloned().collect()
+}
+
+pub fn role(name: &str) -> Option<RoleSpec> {
+    registry().read().unwrap().get(name).cloned()
+}
+
+/// The AWS profile at the bottom of a role chain (`profile` itself when it
+/// isn't a role).
+pub fn base_profile(profile: &str) -> String {
+    let roles = registry().read().unwrap();
+    let mut current = profile;
+    // Bounded in case the registry was set without validation
+    for _ in 0..=roles.len() {
+        match roles.get(current) {
+            Some(role) => current = &role.source_profile,
+            None => break,
+        }
+    }
+    current.to_string()
+}
+
+/// How MFA codes are asked for from now on: the terminal before the TUI
+/// starts (and for the CLI), the TUI's popup after.
+pub fn set_mfa_prompt(prompt: Arc<MfaPrompt>) {
+    *mfa_prompt().write().unwrap() = Some(prompt);
+}
+
+/// Ask on stderr and read the code from stdin, keeping stdout clean for
+/// results.
+pub fn read_mfa_code(role: &str, serial: &str) -> Option<String> {
+    eprint!("MFA code for role '{role}' ({serial}): ");
+    let _ = std::io::stderr().flush();
+    let mut code = String::new();
+    std::io::stdin().read_line(&mut code).ok()?;
+    let code = code.trim().to_string();
+    (!code.is_empty()).then_some(code)
+}
+
+/// Drop a role's cached credentials so the next call assumes it again.
+pub fn forget_role_credentials(name: &str) {
+    credential_slots().lock().unwrap().remove(name);
+}
+
+fn still_valid(creds: &Credentials) -> bool {
+    creds
+        .expiry()
+        .is_none_or(|expiry| SystemTime::now() + EXPIRY_MARGIN < expiry)
+}
+
+/// Credentials provider for a configured role, used in place of a profile.
+#[derive(Debug)]
+pub struct RoleProvider {
+    pub name: String,
+    /// STS is called in the same region as the logs.
+    pub region: String,
+}
+
+impl ProvideCredentials for RoleProvider {
+    fn provide_credentials<'a>(&'a self) -> future::ProvideCredentials<'a>
+    where
+        Self: 'a,
+    {
+        future::ProvideCredentials::new(role_credentials(self.name.clone(), self.region.clone()))
+    }
+}
+
+/// Cached credentials for `name`, or fresh ones from `AssumeRole`.
+async fn role_credentials(name: String, region: String) -> Result<Credentials, CredentialsError> {
+    let slot = credential_slot(&name);
+    let mut cached = slot.lock().await;
+    if let Some(creds) = cached.as_ref().filter(|c| still_valid(c)) {
+        return Ok(creds.clone());
+    }
+    let spec = role(&name)
+        .ok_or_else(|| CredentialsError::not_loaded(format!("role '{name}' is not configured")))?;
+
+    // Chained roles get their source credentials the same way
+    let source = Box::pin(super::load_sdk_config(&spec.source_profile, &region)).await;
+    let creds = assume_role(&name, &spec, &source).await?;
+    *cached = Some(creds.clone());
+    Ok(creds)
+}
+
+async fn assume_role(
+    name: &str,
+    spec: &RoleSpec,
+    source: &aws_config::SdkConfig,
+) -> Result<Credentials, CredentialsError> {
+    let mut req = sts::Client::new(source)
+        .assume_role()
+        .role_arn(&spec.role_arn)
+        .role_session_name(spec.session_name.as_deref().unwrap_or("lumberjack"))
+        .set_external_id(spec.external_id.clone())
+        .set_duration_seconds(spec.duration_secs);
+    if let Some(serial) = &spec.mfa_serial {
+        let prompt = mfa_prompt().read().unwrap().clone().ok_or_else(|| {
+            CredentialsError::not_loaded(format!("role '{name}' needs an MFA code"))
+        })?;
+        let (role_name, serial_arg) = (name.to_string(), serial.clone());
+        let code = tokio::task::spawn_blocking(move || prompt(&role_name, &serial_arg))
+            .await
+            .ok()
+            .flatten()
+            .ok_or_else(|| {
+                CredentialsError::not_loaded(format!("no MFA code given for role '{name}'"))
+            })?;
+        req = req.serial_number(serial).token_code(code.trim());
+    }
+
+    let resp = req.send().await.map_err(CredentialsError::provider_error)?;
+    let creds = resp.credentials().ok_or_else(|| {
+        CredentialsError::provider_error(format!("AssumeRole for '{name}' returned no credentials"))
+    })?;
+    Ok(Credentials::new(
+        creds.access_key_id(),
+        creds.secret_access_key(),
+        Some(creds.session_token().to_string()),
+        SystemTime::try_from(*creds.expiration()).ok(),
+        "lumberjack-assume-role",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(source: &str) -> RoleSpec {
+        RoleSpec {
+            source_profile: source.to_string(),
+            role_arn: "arn:aws:iam::123456789012:role/logs-reader".to_string(),
+            ..RoleSpec::default()
+        }
+    }
+
+    #[test]
+    fn validates_arns_and_chains() {
+        let mut roles = BTreeMap::new();
+        roles.insert("hub".to_string(), spec("sso-admin"));
+        roles.insert("app".to_string(), spec("hub"));
+        assert_eq!(validate_roles(&roles), Ok(()));
+
+        roles.insert("hub".to_string(), spec("app"));
+        assert!(validate_roles(&roles).unwrap_err().contains("loops"));
+
+        let mut bad = BTreeMap::new();
+        bad.insert("x".to_string(), RoleSpec::default());
+        assert!(validate_roles(&bad).unwrap_err().contains("role_arn"));
+    }
+
+    #[test]
+    fn credentials_are_reused_until_near_expiry() {
+        let creds = |expiry| Credentials::new("a", "s", None, expiry, "test");
+        assert!(still_valid(&creds(None)));
+        assert!(still_valid(&creds(Some(
+            SystemTime::now() + Duration::from_secs(3600)
+        ))));
+        assert!(!still_valid(&creds(Some(
+            SystemTime::now() + Duration::from_secs(60)
+        ))));
+    }
+}
@@ -0,0 +1,419 @@
+use std::io::{self, Write};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::{Value, json};
+
+use super::query::{EXIT_ERROR, EXIT_FOUND, EXIT_NO_RESULTS};
+use super::{ArgReader, CliError, Command, aws_target, endpoint_url_value, no_value};
+use crate::aws::{
+    AwsLogError, LogGroupInfo, LogStreamInfo, fetch_log_group_details, fetch_log_streams,
+    fill_last_event_times,
+};
+
+pub const GROUPS_USAGE: &str = "\
+List log groups with their metadata
+
+Usage: lumberjack groups [OPTIONS]
+
+Options:
+      --prefix <PREFIX>   Only groups whose names start with PREFIX
+      --no-last-event     Skip looking up each group's last event time
+                          (one extra API call per group)
+      --format <FORMAT>   text (default) or json
+      --profile <NAME>    AWS profile (default: the standard AWS credential chain)
+      --region <REGION>   AWS region (default: from the environment/profile, else eu-west-1)
+      --endpoint-url <URL>
+                          CloudWatch Logs endpoint, e.g. http://localhost:4566 for LocalStack
+                          (default: LUMBERJACK_ENDPOINT_URL, else the config file, else AWS)
+  -h, --help              Print help
+
+Text output is one group per line, tab-separated:
+  name, created, retention (days or 'never'), stored bytes, last event
+
+Exit status: 0 when groups were listed, 1 when none matched, 2 on errors.";
+
+pub const STREAMS_USAGE: &str = "\
+List the log streams of a group, most recently written first
+
+Usage: lumberjack streams <GROUP> [OPTIONS]
+
+Options:
+      --prefix <PREFIX>   Only streams whose names start with PREFIX (sorted by name)
+      --limit <N>         Stop after N streams
+      --format <FORMAT>   text (default) or json
+      --profile <NAME>    AWS profile (default: the standard AWS credential chain)
+      --region <REGION>   AWS region (default: from the environment/profile, else eu-west-1)
+      --endpoint-url <URL>
+                          CloudWatch Logs endpoint, e.g. http://localhost:4566 for LocalStack
+                          (default: LUMBERJACK_ENDPOINT_URL, else the config file, else AWS)
+  -h, --help              Print help
+
+Text output is one stream per line, tab-separated:
+  name, created, last event, last ingestion
+
+Exit status: 0 when streams were listed, 1 when none matched, 2 on errors.";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ListFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GroupsArgs {
+    pub profile: Option<String>,
+    pub region: Option<String>,
+    pub endpoint_url: Option<String>,
+    pub prefix: Option<String>,
+    pub no_last_event: bool,
+    pub format: ListFormat,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StreamsArgs {
+    pub profile: Option<String>,
+    pub region: Option<String>,
+    pub endpoint_url: Option<String>,
+    pub group: String,
+    pub prefix: Option<String>,
+    pub limit: Option<usize>,
+    pub format: ListFormat,
+}
+
+fn parse_format(flag: String, value: String) -> Result<ListFormat, CliError> {
+    match value.to_ascii_lowercase().as_str() {
+        "text" => Ok(ListFormat::Text),
+        "json" => Ok(ListFormat::Json),
+        _ => Err(CliError::InvalidValue {
+            flag,
+            value,
+            reason: "expected text or json".to_string(),
+        }),
+    }
+}
+
+pub(super) fn parse_groups_args<I: Iterator<Item = String>>(
+    reader: &mut ArgReader<I>,
+) -> Result<Command, CliError> {
+    let mut args = GroupsArgs::default();
+
+    while let Some((flag, inline)) = reader.next() {
+        match flag.as_str() {
+            "-h" | "--help" => {
+                no_value(&flag, inline)?;
+                return Ok(Command::Help(GROUPS_USAGE));
+            }
+            "--profile" => args.profile = Some(reader.value(&flag, inline)?),
+            "--region" => args.region = Some(reader.value(&flag, inline)?),
+            "--endpoint-url" => {
+                let value = reader.value(&flag, inline)?;
+                args.endpoint_url = Some(endpoint_url_value(flag, value)?);
+            }
+            "--prefix" => args.prefix = Some(reader.value(&flag, inline)?),
+            "--no-last-event" => {
+                no_value(&flag, inline)?;
+                args.no_last_event = true;
+            }
+            "--format" => {
+                let value = reader.value(&flag, inline)?;
+                args.format = parse_format(flag, value)?;
+            }
+            _ => return Err(CliError::UnknownArgument(flag)),
+        }
+    }
+
+    Ok(Command::Groups(args))
+}
+
+pub(super) fn parse_streams_args<I: Iterator<Item = String>>(
+    reader: &mut ArgReader<I>,
+) -> Result<Command, CliError> {
+    let mut args = StreamsArgs::default();
+    let mut group = None;
+
+    while let Some((flag, inline)) = reader.next() {
+        match flag.as_str() {
+            "-h" | "--help" => {
+                no_value(&flag, inline)?;
+                return Ok(Command::Help(STREAMS_USAGE));
+            }
+            "--profile" => args.profile = Some(reader.value(&flag, inline)?),
+            "--region" => args.region = Some(reader.value(&flag, inline)?),
+            "--endpoint-url" => {
+                let value = reader.value(&flag, inline)?;
+                args.endpoint_url = Some(endpoint_url_value(flag, value)?);
+            }
+            "--prefix" => args.prefix = Some(reader.value(&flag, inline)?),
+            "--format" => {
+                let value = reader.value(&flag, inline)?;
+                args.format = parse_format(flag, value)?;
+            }
+            "--limit" => {
+                let value = reader.value(&flag, inline)?;
+                match value.parse::<usize>() {
+                    Ok(n) if n > 0 => args.limit = Some(n),
+                    _ => {
+                        return Err(CliError::InvalidValue {
+                            flag,
+                            value,
+                            reason: "expected a positive number".to_string(),
+                        });
+                    }
+                }
+            }
+            // The group is the one positional argument
+            _ if !flag.starts_with('-') && group.is_none() => group = Some(flag),
+            _ => return Err(CliError::UnknownArgument(flag)),
+        }
+    }
+
+    args.group = group.ok_or_else(|| CliError::MissingValue("<GROUP>".to_string()))?;
+    Ok(Command::Streams(args))
+}
+
+fn format_time(ms: Option<i64>) -> Option<String> {
+    ms.and_then(DateTime::<Utc>::from_timestamp_millis)
+        .map(|dt| dt.to_rfc3339_opts(SecondsFormat::Secs, true))
+}
+
+fn text_time(ms: Option<i64>) -> String {
+    format_time(ms).unwrap_or_else(|| "-".to_string())
+}
+
+pub fn render_groups(groups: &[LogGroupInfo], format: ListFormat) -> String {
+    match format {
+        ListFormat::Text => groups
+            .iter()
+            .map(|g| {
+                format!(
+                    "{}\t{}\t{}\t{}\t{}\n",
+                    g.name,
+                    text_time(g.creation_time_ms),
+                    g.retention_days
+                        .map_or_else(|| "never".to_string(), |d| d.to_string()),
+                    g.stored_bytes
+                        .map_or_else(|| "-".to_string(), |b| b.to_string()),
+                    text_time(g.last_event_ms),
+                )
+            })
+            .collect(),
+        ListFormat::Json => {
+            let items: Vec<Value> = groups
+                .iter()
+                .map(|g| {
+                    json!({
+                        "name": g.name,
+                        "created": format_time(g.creation_time_ms),
+                        "retention_days": g.retention_days,
+                        "stored_bytes": g.stored_bytes,
+                        "last_event": format_time(g.last_event_ms),
+                    })
+                })
+                .collect();
+            serde_json::to_string_pretty(&items).unwrap_or_default() + "\n"
+        }
+    }
+}
+
+pub fn render_streams(streams: &[LogStreamInfo], format: ListFormat) -> String {
+    match format {
+        ListFormat::Text => streams
+            .iter()
+            .map(|s| {
+                format!(
+                    "{}\t{}\t{}\t{}\n",
+                    s.name,
+                    text_time(s.creation_time_ms),
+                    text_time(s.last_event_ms),
+                    text_time(s.last_ingestion_ms),
+                )
+            })
+            .collect(),
+        ListFormat::Json => {
+            let items: Vec<Value> = streams
+                .iter()
+                .map(|s| {
+                    json!({
+                        "name": s.name,
+                        "created": format_time(s.creation_time_ms),
+                        "last_event": format_time(s.last_event_ms),
+                        "last_ingestion": format_time(s.last_ingestion_ms),
+                    })
+                })
+                .collect();
+            serde_json::to_string_pretty(&items).unwrap_or_default() + "\n"
+        }
+    }
+}
+
+fn print_listing(text: &str, count: usize) -> i32 {
+    let mut stdout = io::stdout().lock();
+    // A closed pipe (e.g. `| head`) is a normal way to stop reading
+    let _ = stdout
+        .write_all(text.as_bytes())
+        .and_then(|_| stdout.flush());
+    if count > 0 {
+        EXIT_FOUND
+    } else {
+        EXIT_NO_RESULTS
+    }
+}
+
+/// Run `lumberjack groups`; returns the process exit code.
+pub fn run_groups(args: GroupsArgs) -> i32 {
+    let target = match aws_target(&args.profile, &args.region, &args.endpoint_url) {
+        Ok(target) => target,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return EXIT_ERROR;
+        }
+    };
+    let prefix = args.prefix.unwrap_or_default();
+
+    let fetched = target.rt.block_on(async {
+        let mut groups = fetch_log_group_details(&target.region, &target.profile, &prefix).await?;
+        if !args.no_last_event {
+            fill_last_event_times(&target.region, &target.profile, &mut groups).await?;
+        }
+        Ok::<_, AwsLogError>(groups)
+    });
+    match fetched {
+        Ok(groups) => print_listing(&render_groups(&groups, args.format), groups.len()),
+        Err(e) => {
+            eprintln!("error: {e}");
+            EXIT_ERROR
+        }
+    }
+}
+
+/// Run `lumberjack streams`; returns the process exit code.
+pub fn run_streams(args: StreamsArgs) -> i32 {
+    let target = match aws_target(&args.profile, &args.region, &args.endpoint_url) {
+        Ok(target) => target,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return EXIT_ERROR;
+        }
+    };
+    let prefix = args.prefix.unwrap_or_default();
+
+    match target.rt.block_on(fetch_log_streams(
+        &target.region,
+        &target.profile,
+        &args.group,
+        &prefix,
+        args.limit,
+    )) {
+        Ok(streams) => print_listing(&render_streams(&streams, args.format), streams.len()),
+        Err(e) => {
+            eprintln!("error: {e}");
+            EXIT_ERROR
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::parse_args;
+
+    fn parse(args: &[&str]) -> Result<Command, CliError> {
+        parse_args(args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn parses_groups_and_streams() {
+        assert_eq!(
+            parse(&[
+                "groups",
+                "--prefix=/aws/lambda",
+                "--no-last-event",
+                "--format",
+                "json"
+            ]),
+            Ok(Command::Groups(GroupsArgs {
+                prefix: Some("/aws/lambda".to_string()),
+                no_last_event: true,
+                format: ListFormat::Json,
+                ..GroupsArgs::default()
+            }))
+        );
+        assert_eq!(
+            parse(&["streams", "--limit", "5", "/aws/lambda/api"]),
+            Ok(Command::Streams(StreamsArgs {
+                group: "/aws/lambda/api".to_string(),
+                limit: Some(5),
+                ..StreamsArgs::default()
+            }))
+        );
+        assert_eq!(parse(&["groups", "-h"]), Ok(Command::Help(GROUPS_USAGE)));
+    }
+
+    #[test]
+    fn streams_needs_exactly_one_group() {
+        assert_eq!(
+            parse(&["streams"]),
+            Err(CliError::MissingValue("<GROUP>".to_string()))
+        );
+        assert_eq!(
+            parse(&["streams", "a", "b"]),
+            Err(CliError::UnknownArgument("b".to_string()))
+        );
+        assert!(matches!(
+            parse(&["groups", "--format", "csv"]),
+            Err(CliError::InvalidValue { .. })
+        ));
+    }
+
+    #[test]
+    fn renders_group_listing() {
+        let groups = vec![
+            LogGroupInfo {
+                name: "/aws/lambda/api".to_string(),
+                creation_time_ms: Some(1_700_000_000_000),
+                retention_days: Some(14),
+                stored_bytes: Some(2048),
+                last_event_ms: Some(1_700_000_060_000),
+            },
+            LogGroupInfo {
+                name: "orders".to_string(),
+                creation_time_ms: None,
+                retention_days: None,
+                stored_bytes: None,
+                last_event_ms: None,
+            },
+        ];
+
+        assert_eq!(
+            render_groups(&groups, ListFormat::Text),
+            "/aws/lambda/api\t2023-11-14T22:13:20Z\t14\t2048\t2023-11-14T22:14:20Z\n\
+             orders\t-\tnever\t-\t-\n"
+        );
+
+        let json: Value = serde_json::from_str(&render_groups(&groups, ListFormat::Json)).unwrap();
+        assert_eq!(json[0]["created"], "2023-11-14T22:13:20Z");
+        assert_eq!(json[0]["retention_days"], 14);
+        assert_eq!(json[1]["retention_days"], Value::Null);
+        assert_eq!(json[0]["last_event"], "2023-11-14T22:14:20Z");
+        assert_eq!(json[1]["last_event"], Value::Null);
+    }
+
+    #[test]
+    fn renders_stream_listing() {
+        let streams = vec![LogStreamInfo {
+            name: "2025/01/01/[$LATEST]abc".to_string(),
+            creation_time_ms: Some(0),
+            last_event_ms: Some(1_000),
+            last_ingestion_ms: None,
+        }];
+
+        assert_eq!(
+            render_streams(&streams, ListFormat::Text),
+            "2025/01/01/[$LATEST]abc\t1970-01-01T00:00:00Z\t1970-01-01T00:00:01Z\t-\n"
+        );
+        let json: Value =
+            serde_json::from_str(&render_streams(&streams, ListFormat::Json)).unwrap();
+        assert_eq!(json[0]["last_event"], "1970-01-01T00:00:01Z");
+    }
+}
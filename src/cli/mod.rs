@@ -0,0 +1,326 @@
+use std::sync::Arc;
+
+use thiserror::Error;
+
+use crate::app::config::{Settings, load_settings, profile_section};
+use crate::aws::roles::{read_mfa_code, set_mfa_prompt, set_roles};
+use crate::aws::{
+    check_endpoint_url, default_region, endpoint_override, runtime, set_endpoint_urls,
+};
+
+pub mod list;
+pub mod query;
+
+pub const USAGE: &str = "\
+Lumberjack - a terminal UI for AWS CloudWatch Logs
+
+Usage: lumberjack [OPTIONS]
+       lumberjack <COMMAND> [OPTIONS]
+
+Commands:
+  query    Print matching log events to stdout (no TUI)
+  groups   List log groups with their metadata
+  streams  List the log streams of a group
+
+Run 'lumberjack <COMMAND> --help' for a command's options.
+
+Options:
+      --profile <NAME>    AWS profile (default: the standard AWS credential chain)
+      --region <REGION>   AWS region (default: from the environment/profile, else eu-west-1)
+      --endpoint-url <URL>
+                          CloudWatch Logs endpoint, e.g. http://localhost:4566 for LocalStack
+                          (default: LUMBERJACK_ENDPOINT_URL, else the config file, else AWS)
+      --group <NAME>      Log group to select at startup (starts a search)
+      --start <TIME>      Start time, e.g. -15m, -1h, 2025-12-11T10:00:00Z
+      --end <TIME>        End time (default: now)
+      --query <PATTERN>   Filter pattern or field=value shorthand
+      --theme <NAME>      Color theme: dark, light, green or one from themes.json
+      --tail              Start in tail mode
+      --filter <NAME>     Load a saved filter by name (other flags override it)
+      --fresh             Don't restore the last session for this profile/region
+  -h, --help              Print help
+  -V, --version           Print version
+
+Flags accept both `--flag value` and `--flag=value`.";
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum CliError {
+    #[error("unknown argument '{0}'")]
+    UnknownArgument(String),
+
+    #[error("missing value for '{0}'")]
+    MissingValue(String),
+
+    #[error("'{0}' does not take a value")]
+    UnexpectedValue(String),
+
+    #[error("invalid value '{value}' for '{flag}': {reason}")]
+    InvalidValue {
+        flag: String,
+        value: String,
+        reason: String,
+    },
+}
+
+/// Options for the interactive TUI. `None` means "not given on the command line".
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TuiArgs {
+    pub profile: Option<String>,
+    pub region: Option<String>,
+    pub endpoint_url: Option<String>,
+    pub group: Option<String>,
+    pub start: Option<String>,
+    pub end: Option<String>,
+    pub query: Option<String>,
+    pub theme: Option<String>,
+    pub tail: bool,
+    pub filter: Option<String>,
+    pub fresh: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    Tui(TuiArgs),
+    Query(query::QueryArgs),
+    Groups(list::GroupsArgs),
+    Streams(list::StreamsArgs),
+    /// Print this usage text.
+    Help(&'static str),
+    Version,
+}
+
+/// Splits `--flag=value` and pulls the value for `--flag value` from `rest`.
+struct ArgReader<I: Iterator<Item = String>> {
+    rest: I,
+}
+
+impl<I: Iterator<Item = String>> ArgReader<I> {
+    fn next(&mut self) -> Option<(String, Option<String>)> {
+        let arg = self.rest.next()?;
+        if arg.starts_with("--")
+            && let Some((flag, value)) = arg.split_once('=')
+        {
+            return Some((flag.to_string(), Some(value.to_string())));
+        }
+        Some((arg, None))
+    }
+
+    fn value(&mut self, flag: &str, inline: Option<String>) -> Result<String, CliError> {
+        match inline {
+            Some(v) => Ok(v),
+            None => match self.rest.next() {
+                // A following flag means the value was forgotten
+                Some(v) if !v.starts_with("--") => Ok(v),
+                _ => Err(CliError::MissingValue(flag.to_string())),
+            },
+        }
+    }
+}
+
+/// `--endpoint-url` values are checked up front, like other bad values.
+fn endpoint_url_value(flag: String, value: String) -> Result<String, CliError> {
+    match check_endpoint_url(&value) {
+        Ok(()) => Ok(value),
+        Err(reason) => Err(CliError::InvalidValue {
+            flag,
+            value,
+            reason,
+        }),
+    }
+}
+
+fn no_value(flag: &str, inline: Option<String>) -> Result<(), CliError> {
+    match inline {
+        Some(_) => Err(CliError::UnexpectedValue(flag.to_string())),
+        None => Ok(()),
+    }
+}
+
+/// What a headless command (or the TUI at startup) needs to talk to AWS.
+pub(crate) struct AwsTarget {
+    pub(crate) rt: &'static tokio::runtime::Runtime,
+    pub(crate) profile: String,
+    pub(crate) region: String,
+    pub(crate) settings: Settings,
+}
+
+/// Runtime, profile, region and config settings, with the endpoint, roles
+/// and a terminal MFA prompt set up. A missing profile means the standard
+/// credential chain; a missing region comes from the environment/profile,
+/// else the config's fallback.
+pub(crate) fn aws_target(
+    profile: &Option<String>,
+    region: &Option<String>,
+    endpoint_url: &Option<String>,
+) -> Result<AwsTarget, String> {
+    let profile = profile.clone().unwrap_or_default();
+    let settings = load_settings(&profile_section(&profile))
+        .map_err(|e| format!("invalid config file {e}"))?;
+    let endpoint = endpoint_override(endpoint_url.clone())?;
+    set_endpoint_urls(endpoint, settings.endpoint_url.clone());
+    set_roles(settings.roles.clone());
+    set_mfa_prompt(Arc::new(read_mfa_code));
+    let rt = runtime();
+    let region = match region {
+        Some(region) => region.clone(),
+        None => rt.block_on(default_region(&profile, &settings.fallback_region)),
+    };
+    Ok(AwsTarget {
+        rt,
+        profile,
+        region,
+        settings,
+    })
+}
+
+/// Parse the arguments after the program name.
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Command, CliError> {
+    let mut args = args.into_iter().peekable();
+    let subcommand = args.next_if(|a| !a.starts_with('-'));
+    let mut reader = ArgReader { rest: args };
+
+    match subcommand.as_deref() {
+        None => parse_tui_args(&mut reader),
+        Some("query") => query::parse_query_args(&mut reader),
+        Some("groups") => list::parse_groups_args(&mut reader),
+        Some("streams") => list::parse_streams_args(&mut reader),
+        Some(other) => Err(CliError::UnknownArgument(other.to_string())),
+    }
+}
+
+fn parse_tui_args<I: Iterator<Item = String>>(
+    reader: &mut ArgReader<I>,
+) -> Result<Command, CliError> {
+    let mut tui = TuiArgs::default();
+
+    while let Some((flag, inline)) = reader.next() {
+        match flag.as_str() {
+            "-h" | "--help" => {
+                no_value(&flag, inline)?;
+                return Ok(Command::Help(USAGE));
+            }
+            "-V" | "--version" => {
+                no_value(&flag, inline)?;
+                return Ok(Command::Version);
+            }
+            "--profile" => tui.profile = Some(reader.value(&flag, inline)?),
+            "--region" => tui.region = Some(reader.value(&flag, inline)?),
+            "--endpoint-url" => {
+                let value = reader.value(&flag, inline)?;
+                tui.endpoint_url = Some(endpoint_url_value(flag, value)?);
+            }
+            "--group" => tui.group = Some(reader.value(&flag, inline)?),
+            "--start" => tui.start = Some(reader.value(&flag, inline)?),
+            "--end" => tui.end = Some(reader.value(&flag, inline)?),
+            "--query" => tui.query = Some(reader.value(&flag, inline)?),
+            "--filter" => tui.filter = Some(reader.value(&flag, inline)?),
+            // Validated at startup, once user themes are loaded
+            "--theme" => tui.theme = Some(reader.value(&flag, inline)?),
+            "--tail" => {
+                no_value(&flag, inline)?;
+                tui.tail = true;
+            }
+            "--fresh" => {
+                no_value(&flag, inline)?;
+                tui.fresh = true;
+            }
+            _ => return Err(CliError::UnknownArgument(flag)),
+        }
+    }
+
+    Ok(Command::Tui(tui))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, CliError> {
+        parse_args(args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn no_arguments_means_defaults() {
+        assert_eq!(parse(&[]), Ok(Command::Tui(TuiArgs::default())));
+    }
+
+    #[test]
+    fn accepts_both_flag_forms() {
+        let Ok(Command::Tui(args)) = parse(&[
+            "--profile=prod",
+            "--region",
+            "us-east-1",
+            "--query",
+            "level=error msg=\"a b\"",
+            "--start=-1h",
+            "--tail",
+            "--fresh",
+        ]) else {
+            panic!("expected TUI args");
+        };
+        assert_eq!(args.profile.as_deref(), Some("prod"));
+        assert_eq!(args.region.as_deref(), Some("us-east-1"));
+        assert_eq!(args.query.as_deref(), Some("level=error msg=\"a b\""));
+        // Relative times start with '-', which is fine for a value
+        assert_eq!(args.start.as_deref(), Some("-1h"));
+        assert!(args.tail);
+        assert!(args.fresh);
+    }
+
+    #[test]
+    fn help_and_version() {
+        assert_eq!(parse(&["--help"]), Ok(Command::Help(USAGE)));
+        assert_eq!(parse(&["-h"]), Ok(Command::Help(USAGE)));
+        assert_eq!(parse(&["--version"]), Ok(Command::Version));
+        assert_eq!(parse(&["-V"]), Ok(Command::Version));
+    }
+
+    #[test]
+    fn rejects_unknown_and_malformed_arguments() {
+        assert_eq!(
+            parse(&["--regoin=us-east-1"]),
+            Err(CliError::UnknownArgument("--regoin".to_string()))
+        );
+        assert_eq!(
+            parse(&["stray"]),
+            Err(CliError::UnknownArgument("stray".to_string()))
+        );
+        assert_eq!(
+            parse(&["--group"]),
+            Err(CliError::MissingValue("--group".to_string()))
+        );
+        assert_eq!(
+            parse(&["--group", "--tail"]),
+            Err(CliError::MissingValue("--group".to_string()))
+        );
+        assert_eq!(
+            parse(&["--tail=yes"]),
+            Err(CliError::UnexpectedValue("--tail".to_string()))
+        );
+        assert_eq!(
+            parse(&["--theme"]),
+            Err(CliError::MissingValue("--theme".to_string()))
+        );
+        assert!(matches!(
+            parse(&["groups", "--endpoint-url", "localhost:4566"]),
+            Err(CliError::InvalidValue { flag, .. }) if flag == "--endpoint-url"
+        ));
+    }
+
+    #[test]
+    fn endpoint_url_is_accepted_everywhere() {
+        let url = "--endpoint-url=http://localhost:4566";
+        let Ok(Command::Tui(tui)) = parse(&[url]) else {
+            panic!("expected TUI args");
+        };
+        assert_eq!(tui.endpoint_url.as_deref(), Some("http://localhost:4566"));
+        let Ok(Command::Query(query)) = parse(&["query", url]) else {
+            panic!("expected query args");
+        };
+        assert_eq!(query.endpoint_url, tui.endpoint_url);
+        let Ok(Command::Streams(streams)) = parse(&["streams", "/app", url]) else {
+            panic!("expected streams args");
+        };
+        assert_eq!(streams.endpoint_url, tui.endpoint_url);
+    }
+}
@@ -0,0 +1,495 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use chrono::{DateTime, Utc};
+
+use super::{ArgReader, AwsTarget, CliError, Command, aws_target, endpoint_url_value, no_value};
+use crate::app::config::Settings;
+use crate::app::context::{context_label, resolve_groups};
+use crate::app::export::{ExportFormat, parse_columns, render_csv, render_ndjson, render_raw};
+use crate::app::filter_library::load_filter_library;
+use crate::app::filter_vars::substitute;
+use crate::app::filters::filters_path;
+use crate::aws::{LogEvent, fetch_log_events, fetch_log_events_multi, fetch_log_groups};
+
+pub const QUERY_USAGE: &str = "\
+Print matching log events to stdout (no TUI)
+
+Usage: lumberjack query --group <NAME> [OPTIONS]
+
+Options:
+      --group <NAME>      Log group to search (required unless the saved filter has one)
+      --start <TIME>      Start time, e.g. -15m, -1h, 2025-12-11T10:00:00Z (default: config, else -15m)
+      --end <TIME>        End time (default: now; ignored with --follow)
+      --query <PATTERN>   Filter pattern or field=value shorthand
+      --filter <NAME>     Use a saved filter by name (other flags override it)
+      --var <NAME=VALUE>  Value for a ${NAME} placeholder in the saved filter (repeatable;
+                          defaults to the value last used in the TUI)
+      --format <FORMAT>   ndjson (default), text, raw or csv
+      --columns <FIELDS>  JSON fields to use as CSV columns, e.g. level,request.id
+      --limit <N>         Stop after N events
+      --follow            Keep polling for new events, like tail -f
+      --profile <NAME>    AWS profile (default: the standard AWS credential chain)
+      --region <REGION>   AWS region (default: from the environment/profile, else eu-west-1)
+      --endpoint-url <URL>
+                          CloudWatch Logs endpoint, e.g. http://localhost:4566 for LocalStack
+                          (default: LUMBERJACK_ENDPOINT_URL, else the config file, else AWS)
+  -h, --help              Print help
+
+Exit status: 0 when events were printed, 1 when nothing matched, 2 on errors.";
+
+/// Exit codes, so scripts can tell "nothing matched" from "something broke".
+pub const EXIT_FOUND: i32 = 0;
+pub const EXIT_NO_RESULTS: i32 = 1;
+pub const EXIT_ERROR: i32 = 2;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QueryArgs {
+    pub profile: Option<String>,
+    pub region: Option<String>,
+    pub endpoint_url: Option<String>,
+    pub group: Option<String>,
+    pub start: Option<String>,
+    pub end: Option<String>,
+    pub query: Option<String>,
+    pub filter: Option<String>,
+    /// Placeholder values for the saved filter, from `--var`.
+    pub vars: BTreeMap<String, String>,
+    /// More groups (names or `*` patterns) bound by the saved filter.
+    pub extra_groups: Vec<String>,
+    /// Stream name prefixes bound by the saved filter.
+    pub stream_prefixes: Vec<String>,
+    pub format: ExportFormat,
+    pub columns: Vec<String>,
+    pub limit: Option<usize>,
+    pub follow: bool,
+}
+
+impl Default for QueryArgs {
+    fn default() -> Self {
+        QueryArgs {
+            profile: None,
+            region: None,
+            endpoint_url: None,
+            group: None,
+            start: None,
+            end: None,
+            query: None,
+            filter: None,
+            vars: BTreeMap::new(),
+            extra_groups: Vec::new(),
+            stream_prefixes: Vec::new(),
+            format: ExportFormat::Ndjson,
+            columns: Vec::new(),
+            limit: None,
+            follow: false,
+        }
+    }
+}
+
+pub(super) fn parse_query_args<I: Iterator<Item = String>>(
+    reader: &mut ArgReader<I>,
+) -> Result<Command, CliError> {
+    let mut args = QueryArgs::default();
+
+    while let Some((flag, inline)) = reader.next() {
+        match flag.as_str() {
+            "-h" | "--help" => {
+                no_value(&flag, inline)?;
+                return Ok(Command::Help(QUERY_USAGE));
+            }
+            "--profile" => args.profile = Some(reader.value(&flag, inline)?),
+            "--region" => args.region = Some(reader.value(&flag, inline)?),
+            "--endpoint-url" => {
+                let value = reader.value(&flag, inline)?;
+                args.endpoint_url = Some(endpoint_url_value(flag, value)?);
+            }
+            "--group" => args.group = Some(reader.value(&flag, inline)?),
+            "--start" => args.start = Some(reader.value(&flag, inline)?),
+            "--end" => args.end = Some(reader.value(&flag, inline)?),
+            "--query" => args.query = Some(reader.value(&flag, inline)?),
+            "--filter" => args.filter = Some(reader.value(&flag, inline)?),
+            "--var" => {
+                let value = reader.value(&flag, inline)?;
+                let Some((name, var)) = value.split_once('=') else {
+                    return Err(CliError::InvalidValue {
+                        flag,
+                        value,
+                        reason: "expected NAME=VALUE".to_string(),
+                    });
+                };
+                args.vars.insert(name.to_string(), var.to_string());
+            }
+            "--columns" => args.columns = parse_columns(&reader.value(&flag, inline)?),
+            "--format" => {
+                let value = reader.value(&flag, inline)?;
+                args.format =
+                    ExportFormat::from_label(&value).ok_or_else(|| CliError::InvalidValue {
+                        flag,
+                        value,
+                        reason: "expected ndjson, text, raw or csv".to_string(),
+                    })?;
+            }
+            "--limit" => {
+                let value = reader.value(&flag, inline)?;
+                match value.parse::<usize>() {
+                    Ok(n) if n > 0 => args.limit = Some(n),
+                    _ => {
+                        return Err(CliError::InvalidValue {
+                            flag,
+                            value,
+                            reason: "expected a positive number".to_string(),
+                        });
+                    }
+                }
+            }
+            "--follow" => {
+                no_value(&flag, inline)?;
+                args.follow = true;
+            }
+            _ => return Err(CliError::UnknownArgument(flag)),
+        }
+    }
+
+    Ok(Command::Query(args))
+}
+
+/// Render one batch of events. `first` controls the CSV header, so a
+/// followed query prints it only once.
+pub fn render_batch(
+    format: ExportFormat,
+    columns: &[String],
+    events: &[LogEvent],
+    first: bool,
+) -> String {
+    let refs: Vec<&LogEvent> = events.iter().collect();
+    match format {
+        ExportFormat::Ndjson => render_ndjson(&refs),
+        ExportFormat::Raw => render_raw(&refs),
+        ExportFormat::Text => events.iter().map(|ev| ev.formatted() + "\n").collect(),
+        ExportFormat::Csv => {
+            let csv = render_csv(&refs, columns);
+            if first {
+                csv
+            } else {
+                csv.split_once('\n')
+                    .map(|(_, rows)| rows.to_string())
+                    .unwrap_or_default()
+            }
+        }
+    }
+}
+
+/// Fill in anything not given on the command line from the saved filter
+/// (the user's own, else one from a shared library). A filter saved in
+/// another profile/region still runs here, with a warning.
+fn apply_saved_filter(
+    args: &mut QueryArgs,
+    settings: &Settings,
+    profile: &str,
+    region: &str,
+) -> Result<(), String> {
+    let Some(name) = &args.filter else {
+        return Ok(());
+    };
+    let own = filters_path()?;
+    let (filters, problems) = load_filter_library(Some(&own), &settings.filter_files)?;
+    for problem in problems {
+        eprintln!("warning: {problem}");
+    }
+    let filter = filters
+        .iter()
+        .find(|f| &f.name == name)
+        .ok_or_else(|| format!("no saved filter named \"{name}\""))?;
+
+    // --var wins over the values remembered from the TUI
+    let mut values = filter.defaults.clone();
+    values.extend(args.vars.clone());
+    if let Some(missing) = filter
+        .placeholders()
+        .into_iter()
+        .find(|name| !values.contains_key(name))
+    {
+        return Err(format!(
+            "saved filter \"{name}\" needs a value for ${{{missing}}} (use --var {missing}=...)"
+        ));
+    }
+
+    let or_saved = |value: &mut Option<String>, saved: &str| {
+        if value.is_none() && !saved.is_empty() {
+            *value = Some(substitute(saved, &values));
+        }
+    };
+    // --group replaces every group the filter binds
+    if args.group.is_none() {
+        args.extra_groups = filter.groups.clone();
+    }
+    or_saved(&mut args.group, &filter.group);
+    args.stream_prefixes = filter.stream_prefixes.clone();
+    or_saved(&mut args.start, &filter.start);
+    or_saved(&mut args.end, &filter.end);
+    or_saved(&mut args.query, &filter.query);
+
+    let saved_profile = if filter.profile.is_empty() {
+        profile
+    } else {
+        &filter.profile
+    };
+    let saved_region = if filter.region.is_empty() {
+        region
+    } else {
+        &filter.region
+    };
+    if (saved_profile, saved_region) != (profile, region) {
+        eprintln!(
+            "warning: saved filter \"{name}\" was saved for {}; searching {} (use --profile/--region)",
+            context_label(saved_profile, saved_region),
+            context_label(profile, region),
+        );
+    }
+    Ok(())
+}
+
+/// Expand the filter's group patterns against the groups in this account,
+/// warning about any that match nothing.
+fn resolve_filter_groups(
+    rt: &tokio::runtime::Runtime,
+    region: &str,
+    profile: &str,
+    group: &str,
+    extra: &[String],
+) -> Result<Vec<String>, String> {
+    let patterns: Vec<String> = std::iter::once(group.to_string())
+        .chain(extra.iter().cloned())
+        .collect();
+    let available = rt
+        .block_on(fetch_log_groups(region, profile))
+        .map_err(|e| e.to_string())?;
+    let (found, missing) = resolve_groups(&patterns, &available);
+    for pattern in &missing {
+        eprintln!(
+            "warning: no log group matches \"{pattern}\" in {}",
+            context_label(profile, region)
+        );
+    }
+    if found.is_empty() {
+        return Err("none of the saved filter's groups exist here".to_string());
+    }
+    Ok(found)
+}
+
+fn write_out(out: &mut impl Write, text: &str) -> io::Result<()> {
+    out.write_all(text.as_bytes())?;
+    out.flush()
+}
+
+/// Run `lumberjack query`; returns the process exit code.
+pub fn run(mut args: QueryArgs) -> i32 {
+    let AwsTarget {
+        rt,
+        profile,
+        region,
+        settings,
+    } = match aws_target(&args.profile, &args.region, &args.endpoint_url) {
+        Ok(target) => target,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return EXIT_ERROR;
+        }
+    };
+    if let Err(e) = apply_saved_filter(&mut args, &settings, &profile, &region) {
+        eprintln!("error: {e}");
+        return EXIT_ERROR;
+    }
+    let Some(group) = args.group.clone() else {
+        eprintln!("error: --group is required (or a --filter with a group)");
+        return EXIT_ERROR;
+    };
+    let groups = if args.extra_groups.is_empty() {
+        vec![group]
+    } else {
+        match resolve_filter_groups(rt, &region, &profile, &group, &args.extra_groups) {
+            Ok(groups) => groups,
+            Err(e) => {
+                eprintln!("error: {e}");
+                return EXIT_ERROR;
+            }
+        }
+    };
+    let start = args.start.clone().unwrap_or(settings.default_start);
+    let end = if args.follow {
+        String::new()
+    } else {
+        args.end.clone().unwrap_or_default()
+    };
+    let pattern = args.query.clone().unwrap_or_default();
+
+    let mut stdout = io::stdout().lock();
+    let mut printed = 0usize;
+    let mut window_start = start;
+    let mut last_ts: Option<i64> = None;
+    let mut polled = false;
+
+    loop {
+        let remaining = args.limit.map(|limit| limit - printed);
+        let res = if groups.len() == 1 && args.stream_prefixes.is_empty() {
+            rt.block_on(fetch_log_events(
+                &region,
+                &profile,
+                &groups[0],
+                &window_start,
+                &end,
+                &pattern,
+                remaining,
+            ))
+        } else {
+            rt.block_on(fetch_log_events_multi(
+                &region,
+                &profile,
+                &groups,
+                &args.stream_prefixes,
+                &window_start,
+                &end,
+                &pattern,
+                remaining,
+            ))
+        };
+
+        match res {
+            Ok((events, new_last)) => {
+                let text = render_batch(args.format, &args.columns, &events, printed == 0);
+                if !events.is_empty() && write_out(&mut stdout, &text).is_err() {
+                    // Reader went away (e.g. `| head`): that's a normal end.
+                    return EXIT_FOUND;
+                }
+                printed += events.len();
+                polled = true;
+                if let Some(ts) = new_last {
+                    last_ts = Some(last_ts.map_or(ts, |prev| prev.max(ts)));
+                }
+            }
+            // Once a follow is running, only auth failures end it: those
+            // won't clear up by polling again
+            Err(e) if args.follow && polled && e.auth_failure().is_none() => {
+                eprintln!("warning: {e}; retrying");
+            }
+            Err(e) => {
+                eprintln!("error: {e}");
+                return EXIT_ERROR;
+            }
+        }
+
+        if !args.follow || args.limit.is_some_and(|limit| printed >= limit) {
+            break;
+        }
+
+        std::thread::sleep(settings.tail_interval);
+        // Next poll: everything after the newest event seen so far
+        if let Some(dt) = last_ts.and_then(|ts| DateTime::<Utc>::from_timestamp_millis(ts + 1)) {
+            window_start = dt.to_rfc3339();
+        }
+    }
+
+    if printed > 0 {
+        EXIT_FOUND
+    } else {
+        EXIT_NO_RESULTS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::parse_args;
+
+    fn parse(args: &[&str]) -> Result<Command, CliError> {
+        parse_args(args.iter().map(|s| s.to_string()))
+    }
+
+    fn event(ts: i64, message: &str) -> LogEvent {
+        LogEvent {
+            timestamp_ms: ts,
+            message: message.to_string(),
+            log_stream_name: "s".to_string(),
+            event_id: format!("id-{ts}"),
+        }
+    }
+
+    #[test]
+    fn parses_query_subcommand() {
+        let Ok(Command::Query(args)) = parse(&[
+            "query",
+            "--group",
+            "/aws/lambda/x",
+            "--start",
+            "-1h",
+            "--query=level=error",
+            "--format",
+            "csv",
+            "--columns",
+            "level,msg",
+            "--limit=50",
+            "--follow",
+        ]) else {
+            panic!("expected query args");
+        };
+        assert_eq!(args.group.as_deref(), Some("/aws/lambda/x"));
+        assert_eq!(args.start.as_deref(), Some("-1h"));
+        // Only the first '=' separates the flag from its value
+        assert_eq!(args.query.as_deref(), Some("level=error"));
+        assert_eq!(args.format, ExportFormat::Csv);
+        assert_eq!(args.columns, vec!["level", "msg"]);
+        assert_eq!(args.limit, Some(50));
+        assert!(args.follow);
+
+        let Ok(Command::Query(args)) = parse(&["query", "--var", "tenant=a=b", "--var=id=7"])
+        else {
+            panic!("expected query args");
+        };
+        assert_eq!(args.vars["tenant"], "a=b");
+        assert_eq!(args.vars["id"], "7");
+        assert!(matches!(
+            parse(&["query", "--var", "tenant"]),
+            Err(CliError::InvalidValue { .. })
+        ));
+    }
+
+    #[test]
+    fn query_defaults_and_validation() {
+        assert_eq!(parse(&["query"]), Ok(Command::Query(QueryArgs::default())));
+        assert_eq!(parse(&["query", "--help"]), Ok(Command::Help(QUERY_USAGE)));
+        assert!(matches!(
+            parse(&["query", "--format", "xml"]),
+            Err(CliError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            parse(&["query", "--limit", "0"]),
+            Err(CliError::InvalidValue { .. })
+        ));
+        // TUI-only flags are not accepted here
+        assert_eq!(
+            parse(&["query", "--tail"]),
+            Err(CliError::UnknownArgument("--tail".to_string()))
+        );
+    }
+
+    #[test]
+    fn csv_header_only_on_first_batch() {
+        let events = vec![event(0, r#"{"level":"error"}"#)];
+        let cols = vec!["level".to_string()];
+
+        let first = render_batch(ExportFormat::Csv, &cols, &events, true);
+        let later = render_batch(ExportFormat::Csv, &cols, &events, false);
+        assert_eq!(first, "timestamp,level\n1970-01-01T00:00:00+00:00,error\n");
+        assert_eq!(later, "1970-01-01T00:00:00+00:00,error\n");
+    }
+
+    #[test]
+    fn text_batch_matches_results_pane() {
+        let events = vec![event(0, "hello")];
+        assert_eq!(
+            render_batch(ExportFormat::Text, &[], &events, true),
+            format!("{}\n", events[0].formatted())
+        );
+    }
+}
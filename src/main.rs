@@ -1,3 +1,4 @@
+use std::collections::BTreeSet;
 use std::env;
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
@@ -5,45 +6,136 @@ use std::time::Instant;
 
 mod app;
 mod aws;
+mod cli;
 mod ui;
 
-use crate::{app::state::AppState, ui::styles::Theme};
-use app::{App, FilterField, Focus};
-use aws::fetch_log_groups;
+use crate::app::state::AppState;
+use crate::ui::styles::{BUILTIN_THEMES, NO_COLOR_THEME, Theme, theme_by_name};
+use app::auth::tui_mfa_prompt;
+use app::clipboard::ClipboardMode;
+use app::config::{config_path, load_user_themes, no_color_requested};
+use app::context::tagged_groups;
+use app::export::ExportFormat;
+use app::filters::{FilterPopupMode, FilterSort, filters_path};
+use app::history::{history_path, load_history};
+use app::links::fetch_groups_from_aws;
+use app::session::load_session;
+use app::{App, FilterField, Focus, SearchMsg};
+use aws::profiles::profile_uses_sso;
+use aws::roles::set_mfa_prompt;
+use aws::{fetch_log_groups, fetch_log_groups_many};
+use cli::{AwsTarget, Command};
 
 const APP_TITLE: &str = "Lumberjack";
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let mut terminal = ratatui::init();
+    let args = match cli::parse_args(env::args().skip(1)) {
+        Ok(Command::Tui(args)) => args,
+        Ok(Command::Query(args)) => std::process::exit(cli::query::run(args)),
+        Ok(Command::Groups(args)) => std::process::exit(cli::list::run_groups(args)),
+        Ok(Command::Streams(args)) => std::process::exit(cli::list::run_streams(args)),
+        Ok(Command::Help(usage)) => {
+            println!("{usage}");
+            return Ok(());
+        }
+        Ok(Command::Version) => {
+            println!("lumberjack {}", env!("CARGO_PKG_VERSION"));
+            return Ok(());
+        }
+        Err(e) => {
+            eprintln!("error: {e}\n\nRun 'lumberjack --help' for usage.");
+            std::process::exit(2);
+        }
+    };
 
-    let region = env::args()
-        .find_map(|arg| arg.strip_prefix("--region=").map(String::from))
-        .unwrap_or_else(|| "eu-west-1".to_string());
+    // Config and endpoint problems are reported before the TUI takes over
+    // the terminal. Until then an MFA code is read from the terminal.
+    let AwsTarget {
+        rt,
+        profile,
+        region,
+        settings,
+    } = match cli::aws_target(&args.profile, &args.region, &args.endpoint_url) {
+        Ok(target) => target,
+        Err(e) => {
+            eprintln!("error: {e}");
+            std::process::exit(2);
+        }
+    };
 
-    let profile = env::args()
-        .find_map(|arg| arg.strip_prefix("--profile=").map(String::from))
-        .unwrap_or_else(|| "No Profile Provided".to_string());
+    let user_themes = match load_user_themes() {
+        Ok(themes) => themes,
+        Err(e) => {
+            eprintln!("error: invalid themes file {e}");
+            std::process::exit(2);
+        }
+    };
 
-    let rt = tokio::runtime::Runtime::new()?;
+    // NO_COLOR beats any theme choice
+    let (theme_name, theme) = if no_color_requested() {
+        (NO_COLOR_THEME.to_string(), Theme::no_color())
+    } else {
+        let name = args.theme.clone().unwrap_or_else(|| settings.theme.clone());
+        match theme_by_name(&name, &user_themes) {
+            Some(theme) => (name, theme),
+            None => {
+                let available: Vec<&str> = BUILTIN_THEMES
+                    .iter()
+                    .copied()
+                    .chain(user_themes.iter().map(|(n, _)| n.as_str()))
+                    .collect();
+                eprintln!(
+                    "error: unknown theme '{name}' (available: {})",
+                    available.join(", ")
+                );
+                std::process::exit(2);
+            }
+        }
+    };
 
-    let groups = match rt.block_on(fetch_log_groups(&region, &profile)) {
-        Ok(g) if !g.is_empty() => g,
-        Ok(_) => vec![format!(
-            "(no log groups found in region {} for profile {})",
-            region, profile
-        )],
-        Err(e) => {
-            eprintln!("Error fetching log groups: {e}");
-            vec![format!("(error fetching log groups: {e})")]
+    // Clipboard backend: auto (default), system, or osc52 for SSH/tmux
+    // sessions; the environment variable beats the config file
+    let clipboard_mode = env::var("LUMBERJACK_CLIPBOARD")
+        .ok()
+        .and_then(|mode| ClipboardMode::from_label(&mode))
+        .unwrap_or(settings.clipboard);
+    let history_path = history_path().ok();
+
+    // With `contexts` configured, every listed profile/region at once. An
+    // auth failure is kept for the banner.
+    let mut startup_auth = None;
+    let groups = if !settings.contexts.is_empty() {
+        let results = rt.block_on(fetch_log_groups_many(&settings.contexts));
+        startup_auth = results
+            .iter()
+            .find_map(|r| r.as_ref().err().and_then(|e| e.auth_failure()));
+        tagged_groups(&settings.contexts, results)
+    } else {
+        match rt.block_on(fetch_log_groups(&region, &profile)) {
+            Ok(g) if !g.is_empty() => g,
+            Ok(_) => vec![format!(
+                "(no log groups found in region {} for profile {})",
+                region,
+                display_profile(&profile)
+            )],
+            Err(e) => {
+                eprintln!("Error fetching log groups: {e}");
+                startup_auth = e.auth_failure();
+                vec![format!("(error fetching log groups: {e})")]
+            }
         }
     };
 
-    let (search_tx, search_rx) = std::sync::mpsc::channel::<String>();
+    let mut terminal = ratatui::init();
+
+    let (search_tx, search_rx) = std::sync::mpsc::channel::<SearchMsg>();
+    set_mfa_prompt(tui_mfa_prompt(search_tx.clone()));
 
     let state = AppState {
         app_title: APP_TITLE.to_string(),
-        theme: Theme::default_dark(),
-        theme_name: "dark".to_string(),
+        theme,
+        theme_name,
+        user_themes,
         lines: Vec::new(),
         filter_cursor_pos: 0,
         all_groups: groups.clone(),
@@ -78,6 +170,66 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         save_filter_name: String::new(),
         load_filter_popup_open: false,
         load_filter_selected: 0,
+        load_filter_search: String::new(),
+        load_filter_sort: FilterSort::Name,
+        load_filter_mode: FilterPopupMode::List,
+        load_filter_marked: BTreeSet::new(),
+        filter_vars_prompt: None,
+        filter_groups: Vec::new(),
+        filter_streams: Vec::new(),
+        context_switch_prompt: None,
+        pending_filter_load: None,
+        context_picker: None,
+        auth_banner: None,
+        auth_retry: false,
+        mfa_prompt: None,
+        line_events: Vec::new(),
+        search_window: None,
+        histogram_selected: None,
+        field_panel_open: false,
+        field_panel_fields: Vec::new(),
+        field_panel_selected: 0,
+        field_panel_value_selected: None,
+        field_panel_auto_run: false,
+        value_picker_open: false,
+        value_picker_fields: Vec::new(),
+        value_picker_selected: 0,
+        selection_anchor: None,
+        export_popup_open: false,
+        export_path: String::new(),
+        export_format: ExportFormat::Ndjson,
+        export_columns: String::new(),
+        export_editing_columns: false,
+        pending_external: None,
+        pipe_commands: Vec::new(),
+        pipe_menu_open: false,
+        pipe_menu_selected: 0,
+        pipe_menu_event: false,
+        clipboard_mode,
+        copy_menu_open: false,
+        copy_menu_selected: 0,
+        search_group: String::new(),
+        search_query: String::new(),
+        import_url_popup_open: false,
+        import_url_input: String::new(),
+        pending_group_select: None,
+        single_context: false,
+        settings,
+        config_path: config_path().ok(),
+        group_fetcher: fetch_groups_from_aws,
+        sso_check: profile_uses_sso,
+        history: history_path
+            .as_deref()
+            .and_then(|path| load_history(path).ok())
+            .unwrap_or_default(),
+        history_pending: None,
+        history_recall: None,
+        history_draft: String::new(),
+        history_popup_open: false,
+        history_popup_input: String::new(),
+        history_popup_selected: 0,
+        history_path,
+        filters_path: filters_path().ok(),
     };
 
     let mut app = App {
@@ -88,8 +240,89 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         tail_stop: Arc::new(AtomicBool::new(false)),
     };
 
+    if !args.fresh {
+        match load_session(&app.state.profile, &app.state.region) {
+            Ok(Some(session)) => {
+                let restore_theme = args.theme.is_none() && !no_color_requested();
+                app.apply_session(&session, restore_theme);
+            }
+            Ok(None) => {}
+            Err(e) => {
+                app.state.status_message = Some(format!("Couldn't restore session: {e}"));
+                app.state.status_set_at = Some(Instant::now());
+            }
+        }
+    }
+    if let Some(failure) = startup_auth {
+        app.show_auth_failure(failure);
+    }
+    apply_startup_args(&mut app, &args);
+
     let app_result = app.run(&mut terminal);
 
     ratatui::restore();
+    if let Err(e) = app.save_session() {
+        eprintln!("warning: couldn't save session: {e}");
+    }
     app_result.map_err(Into::into)
 }
+
+/// Profile name for messages; empty means the default credential chain.
+fn display_profile(profile: &str) -> &str {
+    if profile.is_empty() {
+        "default"
+    } else {
+        profile
+    }
+}
+
+/// Apply --filter, then the individual filter flags on top of it. A known
+/// group starts a search right away.
+fn apply_startup_args(app: &mut App, args: &cli::TuiArgs) {
+    let mut problems = Vec::new();
+    let mut run_search = false;
+
+    if let Some(name) = &args.filter {
+        match app.apply_saved_filter_by_name(name) {
+            // Only search straight away if the filter's group was found and
+            // it isn't waiting for placeholder values or a context switch
+            Ok(()) => {
+                let selected = app.state.groups.get(app.state.selected_group);
+                run_search = app.state.filter_vars_prompt.is_none()
+                    && app.state.context_switch_prompt.is_none()
+                    && app.state.saved_filters.iter().any(|f| {
+                        &f.name == name && !f.group.is_empty() && Some(&f.group) == selected
+                    });
+            }
+            Err(e) => problems.push(e),
+        }
+    }
+    if let Some(start) = &args.start {
+        app.state.filter_start = start.clone();
+    }
+    if let Some(end) = &args.end {
+        app.state.filter_end = end.clone();
+    }
+    if let Some(query) = &args.query {
+        app.state.filter_query = query.clone();
+    }
+    if args.tail {
+        app.state.tail_mode = true;
+    }
+
+    if let Some(group) = &args.group {
+        if app.select_group_by_name(group) {
+            run_search = true;
+        } else {
+            problems.push(format!("log group {group} not found"));
+            run_search = false;
+        }
+    }
+
+    if !problems.is_empty() {
+        app.state.status_message = Some(problems.join("; "));
+        app.state.status_set_at = Some(Instant::now());
+    } else if run_search {
+        app.start_search();
+    }
+}
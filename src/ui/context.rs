@@ -0,0 +1,129 @@
+use ratatui::prelude::{Buffer, Rect};
+use ratatui::style::Style;
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Widget};
+
+use crate::app::App;
+use crate::app::context::ContextPickerKind;
+use crate::ui::styles;
+
+impl App {
+    /// Centered profile or region list, filtered as you type; the current
+    /// one is marked with `*`.
+    pub fn render_context_picker(&self, area: Rect, buf: &mut Buffer) {
+        let Some(picker) = &self.state.context_picker else {
+            return;
+        };
+        let theme = &self.state.theme;
+        let entries = self.context_picker_entries();
+        let (title, current) = match picker.kind {
+            ContextPickerKind::Profile => ("Switch profile", &self.state.profile),
+            ContextPickerKind::Region => ("Switch region", &self.state.region),
+        };
+
+        let popup_width = 50u16.min(area.width);
+        let needed_height = entries.len() as u16 + 4;
+        let popup_height = needed_height.min(20).min(area.height);
+        let popup_area = Rect {
+            x: area.x + (area.width.saturating_sub(popup_width)) / 2,
+            y: area.y + (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        let block = Block::bordered()
+            .title(title)
+            .style(styles::popup_block(theme))
+            .border_style(styles::popup_border(theme));
+        let inner = block.inner(popup_area);
+        block.render(popup_area, buf);
+
+        if inner.height < 3 {
+            return;
+        }
+
+        let row_rect = |row: u16| Rect {
+            x: inner.x,
+            y: inner.y + row,
+            width: inner.width,
+            height: 1,
+        };
+
+        Line::from(format!("Search: {}", picker.input)).render(row_rect(0), buf);
+
+        let list_h = (inner.height - 2) as usize;
+        let offset = (picker.selected + 1).saturating_sub(list_h);
+
+        for (row, (idx, item)) in entries
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .take(list_h)
+            .enumerate()
+        {
+            let is_selected = idx == picker.selected;
+            let marker = if is_selected { ">" } else { " " };
+            let active = if *item == current.as_str() { "*" } else { " " };
+            let style = if is_selected {
+                styles::popup_border(theme)
+            } else {
+                Style::default()
+            };
+            Line::from(format!("{marker}{active} {item}"))
+                .style(style)
+                .render(row_rect(row as u16 + 1), buf);
+        }
+
+        Line::from("Enter Switch  Type to filter  Esc Cancel")
+            .style(styles::default_gray(theme))
+            .render(row_rect(inner.height - 1), buf);
+    }
+}
+
+impl App {
+    /// The MFA code for the role being assumed; drawn over everything else
+    /// since a fetch is waiting on it.
+    pub fn render_mfa_prompt(&self, area: Rect, buf: &mut Buffer) {
+        let Some(prompt) = &self.state.mfa_prompt else {
+            return;
+        };
+        let theme = &self.state.theme;
+
+        let popup_width = 60u16.min(area.width);
+        let popup_height = 6u16.min(area.height);
+        let popup_area = Rect {
+            x: area.x + (area.width.saturating_sub(popup_width)) / 2,
+            y: area.y + (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        let block = Block::bordered()
+            .title(format!("MFA code for {}", prompt.role))
+            .style(styles::popup_block(theme))
+            .border_style(styles::popup_border(theme));
+        let inner = block.inner(popup_area);
+        block.render(popup_area, buf);
+
+        if inner.height < 3 {
+            return;
+        }
+
+        let row_rect = |row: u16| Rect {
+            x: inner.x,
+            y: inner.y + row,
+            width: inner.width,
+            height: 1,
+        };
+
+        Line::from(prompt.serial.as_str())
+            .style(styles::default_gray(theme))
+            .render(row_rect(0), buf);
+        Line::from(format!("Code: {}_", prompt.code))
+            .style(styles::popup_border(theme))
+            .render(row_rect(1), buf);
+        Line::from("Enter Assume role  Esc Cancel")
+            .style(styles::default_gray(theme))
+            .render(row_rect(inner.height - 1), buf);
+    }
+}
@@ -0,0 +1,69 @@
+use crate::app::App;
+use crate::app::clipboard::CopyFormat;
+use crate::ui::styles;
+use ratatui::prelude::{Buffer, Rect};
+use ratatui::style::Style;
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Widget};
+
+impl App {
+    /// Centered popup with the copy variants for the event under the cursor.
+    pub fn render_copy_menu(&self, area: Rect, buf: &mut Buffer) {
+        let theme = &self.state.theme;
+
+        let popup_width = 36u16.min(area.width);
+        let popup_height = (CopyFormat::ALL.len() as u16 + 3).min(area.height);
+        let popup_area = Rect {
+            x: area.x + (area.width.saturating_sub(popup_width)) / 2,
+            y: area.y + (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        let block = Block::bordered()
+            .title("Copy event")
+            .style(styles::popup_block(theme))
+            .border_style(styles::popup_border(theme));
+        let inner = block.inner(popup_area);
+        block.render(popup_area, buf);
+
+        if inner.height < 2 {
+            return;
+        }
+
+        for (y, (idx, format)) in
+            (inner.y..inner.y + inner.height - 1).zip(CopyFormat::ALL.iter().enumerate())
+        {
+            let is_selected = idx == self.state.copy_menu_selected;
+            let marker = if is_selected { ">" } else { " " };
+            let style = if is_selected {
+                styles::popup_border(theme)
+            } else {
+                Style::default()
+            };
+            Line::from(format!("{marker} {} {}", idx + 1, format.label()))
+                .style(style)
+                .render(
+                    Rect {
+                        x: inner.x,
+                        y,
+                        width: inner.width,
+                        height: 1,
+                    },
+                    buf,
+                );
+        }
+
+        Line::from("Enter/1-6 Copy  Esc Cancel")
+            .style(styles::default_gray(theme))
+            .render(
+                Rect {
+                    x: inner.x,
+                    y: inner.y + inner.height - 1,
+                    width: inner.width,
+                    height: 1,
+                },
+                buf,
+            );
+    }
+}
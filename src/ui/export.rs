@@ -0,0 +1,92 @@
+use crate::app::App;
+use crate::app::export::ExportFormat;
+use crate::ui::styles;
+use ratatui::prelude::{Buffer, Rect};
+use ratatui::style::Style;
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Widget};
+
+impl App {
+    /// Centered popup for exporting results: format, target path and, for
+    /// CSV, the JSON fields to use as columns.
+    pub fn render_export_popup(&self, area: Rect, buf: &mut Buffer) {
+        let theme = &self.state.theme;
+
+        let popup_width = 60u16.min(area.width);
+        let popup_height = 8u16.min(area.height);
+        let popup_area = Rect {
+            x: area.x + (area.width.saturating_sub(popup_width)) / 2,
+            y: area.y + (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        let (_, events) = self.export_scope();
+        let title = match self.selected_entry_range() {
+            Some(_) => format!("Export selection ({} events)", events.len()),
+            None => format!("Export results ({} events)", events.len()),
+        };
+        let block = Block::bordered()
+            .title(title)
+            .style(styles::popup_block(theme))
+            .border_style(styles::popup_border(theme));
+        let inner = block.inner(popup_area);
+        block.render(popup_area, buf);
+
+        let editing_columns = self.state.export_editing_columns;
+        let active = styles::popup_border(theme);
+        let is_csv = self.state.export_format == ExportFormat::Csv;
+
+        let mut rows = vec![
+            (
+                format!("Format:  {}", self.state.export_format.label()),
+                Style::default(),
+            ),
+            (
+                format!("Path:    {}", self.state.export_path),
+                if editing_columns {
+                    Style::default()
+                } else {
+                    active
+                },
+            ),
+        ];
+        if is_csv {
+            rows.push((
+                format!("Columns: {}", self.state.export_columns),
+                if editing_columns {
+                    active
+                } else {
+                    Style::default()
+                },
+            ));
+        }
+
+        for (y, (text, style)) in (inner.y..inner.y + inner.height).zip(rows) {
+            Line::from(text).style(style).render(
+                Rect {
+                    x: inner.x,
+                    y,
+                    width: inner.width,
+                    height: 1,
+                },
+                buf,
+            );
+        }
+
+        let hint = if is_csv {
+            "Tab Format  ↑↓ Path/Columns  Enter Export  Esc Cancel"
+        } else {
+            "Tab Format  Enter Export  Esc Cancel"
+        };
+        Line::from(hint).style(styles::default_gray(theme)).render(
+            Rect {
+                x: inner.x,
+                y: inner.y + inner.height.saturating_sub(1),
+                width: inner.width,
+                height: 1,
+            },
+            buf,
+        );
+    }
+}
@@ -0,0 +1,169 @@
+use crate::app::App;
+use crate::ui::styles;
+use ratatui::prelude::{Buffer, Rect};
+use ratatui::style::Style;
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Widget};
+
+impl App {
+    /// Side panel listing discovered JSON fields; the selected field is
+    /// expanded to show its top values.
+    pub fn render_field_panel(&self, area: Rect, buf: &mut Buffer) {
+        let theme = &self.state.theme;
+
+        let title = if self.state.field_panel_auto_run {
+            "Fields [auto-run]"
+        } else {
+            "Fields"
+        };
+        let block = Block::bordered()
+            .title(title)
+            .style(styles::popup_block(theme))
+            .border_style(styles::popup_border(theme));
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        if inner.height < 2 || inner.width == 0 {
+            return;
+        }
+
+        let highlight = styles::popup_border(theme);
+        let value_cursor = self.state.field_panel_value_selected;
+
+        // Build every row first so we can scroll the cursor into view.
+        let mut rows: Vec<(String, Style)> = Vec::new();
+        let mut cursor_row = 0;
+        for (idx, field) in self.state.field_panel_fields.iter().enumerate() {
+            let selected = idx == self.state.field_panel_selected;
+            if selected && value_cursor.is_none() {
+                cursor_row = rows.len();
+            }
+            let marker = if selected { ">" } else { " " };
+            rows.push((
+                format!("{marker} {} ({})", field.name, field.count),
+                if selected && value_cursor.is_none() {
+                    highlight
+                } else {
+                    Style::default()
+                },
+            ));
+
+            if selected {
+                for (v_idx, (value, count)) in field.top_values.iter().enumerate() {
+                    let on_value = value_cursor == Some(v_idx);
+                    if on_value {
+                        cursor_row = rows.len();
+                    }
+                    let marker = if on_value { ">" } else { " " };
+                    rows.push((
+                        format!("   {marker} {value} ×{count}"),
+                        if on_value {
+                            highlight
+                        } else {
+                            styles::default_gray(theme)
+                        },
+                    ));
+                }
+            }
+        }
+
+        // Last row is reserved for the key hint.
+        let list_h = (inner.height - 1) as usize;
+        let offset = (cursor_row + 1).saturating_sub(list_h);
+
+        for (row, (text, style)) in rows.iter().skip(offset).take(list_h).enumerate() {
+            Line::from(text.as_str()).style(*style).render(
+                Rect {
+                    x: inner.x,
+                    y: inner.y + row as u16,
+                    width: inner.width,
+                    height: 1,
+                },
+                buf,
+            );
+        }
+
+        Line::from("Enter Add  ! Exclude  a Auto-run  Esc Close")
+            .style(styles::default_gray(theme))
+            .render(
+                Rect {
+                    x: inner.x,
+                    y: inner.y + inner.height - 1,
+                    width: inner.width,
+                    height: 1,
+                },
+                buf,
+            );
+    }
+
+    /// Centered popup listing the current event's fields for "filter by value".
+    pub fn render_value_picker(&self, area: Rect, buf: &mut Buffer) {
+        let theme = &self.state.theme;
+
+        let popup_width = 60u16.min(area.width);
+        let needed_height = self.state.value_picker_fields.len() as u16 + 3;
+        let popup_height = needed_height.min(16).min(area.height);
+        let popup_area = Rect {
+            x: area.x + (area.width.saturating_sub(popup_width)) / 2,
+            y: area.y + (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        let block = Block::bordered()
+            .title("Filter by value")
+            .style(styles::popup_block(theme))
+            .border_style(styles::popup_border(theme));
+        let inner = block.inner(popup_area);
+        block.render(popup_area, buf);
+
+        if inner.height < 2 {
+            return;
+        }
+
+        let list_h = (inner.height - 1) as usize;
+        let selected = self.state.value_picker_selected;
+        let offset = (selected + 1).saturating_sub(list_h);
+
+        for (row, (idx, (field, value))) in self
+            .state
+            .value_picker_fields
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .take(list_h)
+            .enumerate()
+        {
+            let is_selected = idx == selected;
+            let marker = if is_selected { ">" } else { " " };
+            let style = if is_selected {
+                styles::popup_border(theme)
+            } else {
+                Style::default()
+            };
+            Line::from(format!("{marker} {field} = {value}"))
+                .style(style)
+                .render(
+                    Rect {
+                        x: inner.x,
+                        y: inner.y + row as u16,
+                        width: inner.width,
+                        height: 1,
+                    },
+                    buf,
+                );
+        }
+
+        Line::from("Enter Add  ! Exclude  r Replace query  Esc Cancel")
+            .style(styles::default_gray(theme))
+            .render(
+                Rect {
+                    x: inner.x,
+                    y: inner.y + inner.height - 1,
+                    width: inner.width,
+                    height: 1,
+                },
+                buf,
+            );
+    }
+}
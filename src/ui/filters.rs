@@ -0,0 +1,294 @@
+use chrono::{DateTime, Utc};
+use ratatui::prelude::{Buffer, Rect};
+use ratatui::style::Style;
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Widget};
+
+use crate::app::App;
+use crate::app::context::context_label;
+use crate::app::filters::{EDIT_FIELDS, FilterPopupMode};
+use crate::ui::styles;
+
+impl App {
+    /// Centered "Load filter" popup: search box, the saved filters in the
+    /// chosen order, and a prompt line for rename/delete.
+    pub fn render_load_filter_popup(&self, area: Rect, buf: &mut Buffer) {
+        let theme = &self.state.theme;
+        let entries = self.load_filter_entries();
+        let mode = &self.state.load_filter_mode;
+
+        let rows = match mode {
+            FilterPopupMode::Edit { .. } => EDIT_FIELDS.len(),
+            _ => entries.len().max(1),
+        };
+        let popup_width = 100u16.min(area.width);
+        let popup_height = (rows as u16 + 4).min(18).min(area.height);
+        let popup_area = Rect {
+            x: area.x + (area.width.saturating_sub(popup_width)) / 2,
+            y: area.y + (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        let title = match mode {
+            FilterPopupMode::Edit { .. } => "Edit filter".to_string(),
+            _ => format!("Load filter (by {})", self.state.load_filter_sort.label()),
+        };
+        let block = Block::bordered()
+            .title(title)
+            .style(styles::popup_block(theme))
+            .border_style(styles::popup_border(theme));
+        let inner = block.inner(popup_area);
+        block.render(popup_area, buf);
+
+        if inner.height < 3 {
+            return;
+        }
+
+        let row_rect = |row: u16| Rect {
+            x: inner.x,
+            y: inner.y + row,
+            width: inner.width,
+            height: 1,
+        };
+        let cursor = if self.state.cursor_on { "_" } else { " " };
+
+        // Top line: the search box, or the name of the filter being edited
+        let top = match mode {
+            FilterPopupMode::Search => format!("Search: {}{cursor}", self.state.load_filter_search),
+            FilterPopupMode::Edit { .. } => self
+                .selected_filter_index()
+                .map(|idx| format!("Name: {}", self.state.saved_filters[idx].name))
+                .unwrap_or_default(),
+            _ => format!("Search: {}", self.state.load_filter_search),
+        };
+        Line::from(top).render(row_rect(0), buf);
+
+        let list_h = (inner.height - 2) as usize;
+
+        if let FilterPopupMode::Edit { field, values } = mode {
+            for (row, (label, value)) in EDIT_FIELDS.iter().zip(values).enumerate().take(list_h) {
+                let is_active = row == *field;
+                let marker = if is_active { ">" } else { " " };
+                let tail = if is_active { cursor } else { "" };
+                let style = if is_active {
+                    styles::popup_border(theme)
+                } else {
+                    Style::default()
+                };
+                Line::from(format!("{marker} {label:<6} {value}{tail}"))
+                    .style(style)
+                    .render(row_rect(row as u16 + 1), buf);
+            }
+        } else {
+            let selected = self.state.load_filter_selected;
+            let offset = (selected + 1).saturating_sub(list_h);
+
+            if entries.is_empty() {
+                let empty = if self.state.saved_filters.is_empty() {
+                    "  (no saved filters; i to import)"
+                } else {
+                    "  (no matches)"
+                };
+                Line::from(empty)
+                    .style(styles::default_gray(theme))
+                    .render(row_rect(1), buf);
+            }
+
+            for (row, (pos, &idx)) in entries
+                .iter()
+                .enumerate()
+                .skip(offset)
+                .take(list_h)
+                .enumerate()
+            {
+                let f = &self.state.saved_filters[idx];
+                let is_selected = pos == selected;
+                let marker = if is_selected { ">" } else { " " };
+                let mark = if self.state.load_filter_marked.contains(&idx) {
+                    "*"
+                } else {
+                    " "
+                };
+                let name = match &f.source {
+                    Some(source) => format!("{} [{source}]", f.name),
+                    None => f.name.clone(),
+                };
+                let used = f
+                    .last_used_ms
+                    .and_then(DateTime::<Utc>::from_timestamp_millis)
+                    .map(|dt| format!("{}x, {}", f.use_count, dt.format("%m-%d %H:%M")))
+                    .unwrap_or_else(|| "unused".to_string());
+                let style = if is_selected {
+                    styles::popup_border(theme)
+                } else {
+                    styles::popup_text(theme)
+                };
+                Line::from(format!("{marker}{mark}{name:<32} {:<32} {used}", f.group))
+                    .style(style)
+                    .render(row_rect(row as u16 + 1), buf);
+            }
+        }
+
+        // Bottom line: what the keys do in this mode, or the pending prompt
+        let selected_name = self
+            .selected_filter_index()
+            .map(|idx| self.state.saved_filters[idx].name.as_str())
+            .unwrap_or_default();
+        let bottom = match mode {
+            FilterPopupMode::List => {
+                "Enter Load  / Search  s Sort  e Edit  r Rename  c Copy  d Delete  \
+                 Space Mark  x Export  i Import  Esc"
+                    .to_string()
+            }
+            FilterPopupMode::Search => "Type to filter  Enter Done  Esc Clear".to_string(),
+            FilterPopupMode::ConfirmDelete => format!("Delete \"{selected_name}\"? y/n"),
+            FilterPopupMode::Rename(input) => format!("New name: {input}{cursor}"),
+            FilterPopupMode::Edit { .. } => "Tab Next field  Enter Save  Esc Cancel".to_string(),
+            FilterPopupMode::Export(input) => {
+                let count = self.state.load_filter_marked.len().max(1);
+                format!("Export {count} filter(s) to: {input}{cursor}")
+            }
+            FilterPopupMode::Import(input) => format!("Import from: {input}{cursor}"),
+            FilterPopupMode::ImportConflict { pending, .. } => format!(
+                "\"{}\" exists: o Overwrite  k Keep  b Both  O/K All  Esc Stop",
+                pending.first().map(|f| f.name.as_str()).unwrap_or_default()
+            ),
+        };
+        let bottom_style = match mode {
+            FilterPopupMode::List | FilterPopupMode::Search | FilterPopupMode::Edit { .. } => {
+                styles::default_gray(theme)
+            }
+            _ => styles::popup_border(theme),
+        };
+        Line::from(bottom)
+            .style(bottom_style)
+            .render(row_rect(inner.height - 1), buf);
+    }
+
+    /// Small form asking for a saved filter's `${name}` values.
+    pub fn render_filter_vars_prompt(&self, area: Rect, buf: &mut Buffer) {
+        let Some(prompt) = &self.state.filter_vars_prompt else {
+            return;
+        };
+        let theme = &self.state.theme;
+
+        let popup_width = 60u16.min(area.width);
+        let popup_height = (prompt.names.len() as u16 + 3).min(area.height);
+        let popup_area = Rect {
+            x: area.x + (area.width.saturating_sub(popup_width)) / 2,
+            y: area.y + (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        let name = self
+            .state
+            .saved_filters
+            .get(prompt.filter)
+            .map(|f| f.name.as_str())
+            .unwrap_or_default();
+        let block = Block::bordered()
+            .title(format!("Values for \"{name}\""))
+            .style(styles::popup_block(theme))
+            .border_style(styles::popup_border(theme));
+        let inner = block.inner(popup_area);
+        block.render(popup_area, buf);
+
+        if inner.height < 2 {
+            return;
+        }
+
+        let row_rect = |row: u16| Rect {
+            x: inner.x,
+            y: inner.y + row,
+            width: inner.width,
+            height: 1,
+        };
+        let cursor = if self.state.cursor_on { "_" } else { " " };
+        let label_w = prompt.names.iter().map(|n| n.len()).max().unwrap_or(0);
+
+        for (row, (name, value)) in prompt
+            .names
+            .iter()
+            .zip(&prompt.values)
+            .enumerate()
+            .take((inner.height - 1) as usize)
+        {
+            let is_active = row == prompt.field;
+            let marker = if is_active { ">" } else { " " };
+            let tail = if is_active { cursor } else { "" };
+            let style = if is_active {
+                styles::popup_border(theme)
+            } else {
+                Style::default()
+            };
+            Line::from(format!("{marker} {name:<label_w$}  {value}{tail}"))
+                .style(style)
+                .render(row_rect(row as u16), buf);
+        }
+
+        Line::from("Tab Next  Enter Next/Load  Esc Cancel")
+            .style(styles::default_gray(theme))
+            .render(row_rect(inner.height - 1), buf);
+    }
+
+    /// "Switch to the filter's profile/region?" prompt.
+    pub fn render_context_switch_prompt(&self, area: Rect, buf: &mut Buffer) {
+        let Some(load) = &self.state.context_switch_prompt else {
+            return;
+        };
+        let Some(f) = self.state.saved_filters.get(load.filter) else {
+            return;
+        };
+        let Some((profile, region)) = self.foreign_context(f) else {
+            return;
+        };
+        let theme = &self.state.theme;
+
+        let popup_width = 64u16.min(area.width);
+        let popup_height = 5u16.min(area.height);
+        let popup_area = Rect {
+            x: area.x + (area.width.saturating_sub(popup_width)) / 2,
+            y: area.y + (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        let block = Block::bordered()
+            .title(format!("Load \"{}\"", f.name))
+            .style(styles::popup_block(theme))
+            .border_style(styles::popup_border(theme));
+        let inner = block.inner(popup_area);
+        block.render(popup_area, buf);
+
+        let lines = [
+            (
+                format!("Saved for {}", context_label(&profile, &region)),
+                styles::popup_border(theme),
+            ),
+            (
+                format!(
+                    "Now in    {}",
+                    context_label(&self.state.profile, &self.state.region)
+                ),
+                Style::default(),
+            ),
+            (
+                "y Switch and load  n Load here  Esc Cancel".to_string(),
+                styles::default_gray(theme),
+            ),
+        ];
+        for (row, (text, style)) in lines.into_iter().enumerate().take(inner.height as usize) {
+            Line::from(text).style(style).render(
+                Rect {
+                    x: inner.x,
+                    y: inner.y + row as u16,
+                    width: inner.width,
+                    height: 1,
+                },
+                buf,
+            );
+        }
+    }
+}
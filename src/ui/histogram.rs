@@ -0,0 +1,122 @@
+use crate::app::App;
+use crate::app::histogram::Histogram;
+use crate::ui::styles;
+use ratatui::prelude::{Buffer, Rect};
+
+// Eighth-block glyphs, from empty to a full cell.
+const BAR_GLYPHS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+impl App {
+    /// Draw the event-count bar chart; one bar per bucket, scaled to the peak.
+    pub fn render_histogram(&self, hist: &Histogram, area: Rect, buf: &mut Buffer) {
+        if area.width == 0 || area.height == 0 || hist.counts.is_empty() {
+            return;
+        }
+
+        let theme = &self.state.theme;
+        let max = hist.max_count().max(1);
+        let current = self.current_histogram_bucket(hist);
+        let selected = self.state.histogram_selected;
+
+        // Stretch buckets across the pane when there is room to spare.
+        let bar_w = (area.width as usize / hist.counts.len()).max(1);
+        let levels = area.height as usize * 8;
+
+        for (idx, &count) in hist.counts.iter().enumerate() {
+            let x0 = area.x as usize + idx * bar_w;
+            if x0 >= (area.x + area.width) as usize {
+                break;
+            }
+
+            // Any non-zero bucket gets at least a sliver so it stays visible.
+            let mut height = count * levels / max;
+            if count > 0 && height == 0 {
+                height = 1;
+            }
+
+            let style = styles::histogram_bar(theme, current == Some(idx), selected == Some(idx));
+
+            for row in 0..area.height as usize {
+                // row 0 is the bottom of the chart
+                let filled = height.saturating_sub(row * 8).min(8);
+                let y = area.y + area.height - 1 - row as u16;
+                for dx in 0..bar_w {
+                    let x = (x0 + dx) as u16;
+                    if x >= area.x + area.width {
+                        break;
+                    }
+                    if let Some(cell) = buf.cell_mut((x, y)) {
+                        cell.set_char(BAR_GLYPHS[filled]).set_style(style);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::buffer::Buffer;
+    use ratatui::layout::Rect;
+
+    use crate::app::histogram::Histogram;
+    use crate::app::state::AppState;
+    use crate::app::{App, Focus};
+
+    fn make_app() -> App {
+        let state = AppState {
+            profile: "test".to_string(),
+            focus: Focus::Results,
+            ..AppState::for_test()
+        };
+
+        App::for_test(state)
+    }
+
+    #[test]
+    fn tallest_bar_fills_the_chart_and_empty_buckets_stay_blank() {
+        let app = make_app();
+        let hist = Histogram {
+            start_ms: 0,
+            bucket_ms: 1_000,
+            counts: vec![4, 0, 2],
+        };
+
+        let area = Rect::new(0, 0, 3, 2);
+        let mut buf = Buffer::empty(area);
+        app.render_histogram(&hist, area, &mut buf);
+
+        // Peak bucket: full cells top and bottom
+        assert_eq!(buf.cell((0, 0)).unwrap().symbol(), "█");
+        assert_eq!(buf.cell((0, 1)).unwrap().symbol(), "█");
+        // Empty bucket
+        assert_eq!(buf.cell((1, 1)).unwrap().symbol(), " ");
+        // Half-height bucket: bottom full, top empty
+        assert_eq!(buf.cell((2, 1)).unwrap().symbol(), "█");
+        assert_eq!(buf.cell((2, 0)).unwrap().symbol(), " ");
+    }
+
+    #[test]
+    fn selected_bucket_uses_selected_style() {
+        let mut app = make_app();
+        app.state.histogram_selected = Some(1);
+        let hist = Histogram {
+            start_ms: 0,
+            bucket_ms: 1_000,
+            counts: vec![1, 1],
+        };
+
+        let area = Rect::new(0, 0, 2, 1);
+        let mut buf = Buffer::empty(area);
+        app.render_histogram(&hist, area, &mut buf);
+
+        assert_eq!(
+            buf.cell((1, 0)).unwrap().style().fg,
+            app.state.theme.histogram_selected.fg
+        );
+        assert_eq!(
+            buf.cell((0, 0)).unwrap().style().fg,
+            app.state.theme.histogram_bar.fg
+        );
+    }
+}
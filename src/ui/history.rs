@@ -0,0 +1,85 @@
+use chrono::{DateTime, Utc};
+use ratatui::prelude::{Buffer, Rect};
+use ratatui::style::Style;
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Widget};
+
+use crate::app::App;
+use crate::ui::styles;
+
+impl App {
+    /// Centered popup with past searches, newest first, filtered as you type.
+    pub fn render_history_popup(&self, area: Rect, buf: &mut Buffer) {
+        let theme = &self.state.theme;
+        let entries = self.history_popup_entries();
+
+        let popup_width = 90u16.min(area.width);
+        let needed_height = entries.len() as u16 + 4;
+        let popup_height = needed_height.min(20).min(area.height);
+        let popup_area = Rect {
+            x: area.x + (area.width.saturating_sub(popup_width)) / 2,
+            y: area.y + (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        let block = Block::bordered()
+            .title("Search history")
+            .style(styles::popup_block(theme))
+            .border_style(styles::popup_border(theme));
+        let inner = block.inner(popup_area);
+        block.render(popup_area, buf);
+
+        if inner.height < 3 {
+            return;
+        }
+
+        let row_rect = |row: u16| Rect {
+            x: inner.x,
+            y: inner.y + row,
+            width: inner.width,
+            height: 1,
+        };
+
+        Line::from(format!("Search: {}", self.state.history_popup_input)).render(row_rect(0), buf);
+
+        let list_h = (inner.height - 2) as usize;
+        let selected = self.state.history_popup_selected;
+        let offset = (selected + 1).saturating_sub(list_h);
+
+        for (row, (idx, entry)) in entries
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .take(list_h)
+            .enumerate()
+        {
+            let is_selected = idx == selected;
+            let marker = if is_selected { ">" } else { " " };
+            let style = if is_selected {
+                styles::popup_border(theme)
+            } else {
+                Style::default()
+            };
+            let when = DateTime::<Utc>::from_timestamp_millis(entry.timestamp_ms)
+                .map(|dt| dt.format("%m-%d %H:%M").to_string())
+                .unwrap_or_default();
+            // Tagged like the Groups pane when it lists several contexts
+            let group = if entry.profile.is_empty() && entry.region.is_empty() {
+                entry.group.clone()
+            } else {
+                self.pane_entry(&entry.profile, &entry.region, &entry.group)
+            };
+            Line::from(format!(
+                "{marker} {when} {group} {} ({})",
+                entry.query, entry.result_count
+            ))
+            .style(style)
+            .render(row_rect(row as u16 + 1), buf);
+        }
+
+        Line::from("Enter Load  Type to filter  Esc Cancel")
+            .style(styles::default_gray(theme))
+            .render(row_rect(inner.height - 1), buf);
+    }
+}
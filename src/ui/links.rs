@@ -0,0 +1,65 @@
+use crate::app::App;
+use crate::ui::styles;
+use ratatui::prelude::{Buffer, Rect};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Widget};
+
+impl App {
+    /// Centered popup to paste a CloudWatch console URL into.
+    pub fn render_import_url_popup(&self, area: Rect, buf: &mut Buffer) {
+        let theme = &self.state.theme;
+
+        let popup_width = 70u16.min(area.width);
+        let popup_height = 5u16.min(area.height);
+        let popup_area = Rect {
+            x: area.x + (area.width.saturating_sub(popup_width)) / 2,
+            y: area.y + (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        let block = Block::bordered()
+            .title("Import console URL")
+            .style(styles::popup_block(theme))
+            .border_style(styles::popup_border(theme));
+        let inner = block.inner(popup_area);
+        block.render(popup_area, buf);
+
+        if inner.height < 2 || inner.width == 0 {
+            return;
+        }
+
+        // Long URLs: keep the end (where the cursor is) visible.
+        let input = &self.state.import_url_input;
+        let width = inner.width as usize;
+        let chars = input.chars().count();
+        let shown: String = input
+            .chars()
+            .skip((chars + 1).saturating_sub(width))
+            .collect();
+
+        Line::from(format!("{shown}_"))
+            .style(styles::popup_border(theme))
+            .render(
+                Rect {
+                    x: inner.x,
+                    y: inner.y,
+                    width: inner.width,
+                    height: 1,
+                },
+                buf,
+            );
+
+        Line::from("Enter Import   Esc Cancel")
+            .style(styles::default_gray(theme))
+            .render(
+                Rect {
+                    x: inner.x,
+                    y: inner.y + inner.height - 1,
+                    width: inner.width,
+                    height: 1,
+                },
+                buf,
+            );
+    }
+}
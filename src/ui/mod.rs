@@ -1,9 +1,18 @@
+mod context;
+mod copy;
+mod export;
+mod fields;
+mod filters;
+mod histogram;
+mod history;
+mod links;
+mod pipes;
 mod results;
 pub mod styles;
 
 use ratatui::layout::{Constraint, Layout};
 use ratatui::prelude::Rect;
-use ratatui::style::{Color, Style, Stylize};
+use ratatui::style::Stylize;
 use ratatui::text::Line;
 use ratatui::widgets::{Block, Widget};
 
@@ -12,13 +21,17 @@ use crate::app::{App, FilterField, Focus};
 impl Widget for &App {
     fn render(self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer) {
         let theme = self.state.theme.clone();
-        let chunks = Layout::vertical([
+        // An auth banner takes a row under the header until it's dismissed
+        let banner_height = u16::from(self.state.auth_banner.is_some());
+        let rows = Layout::vertical([
             Constraint::Length(1),
+            Constraint::Length(banner_height),
             Constraint::Length(6),
             Constraint::Min(0),
             Constraint::Length(1),
         ])
         .split(area);
+        let chunks = [rows[0], rows[2], rows[3], rows[4]];
 
         let header_style = theme.header;
         let footer_style = theme.footer;
@@ -47,10 +60,21 @@ impl Widget for &App {
                 .split(chunks[1]);
 
         let header_right_text: String = format!(
-            "Profile: {} | Region: {}",
-            self.state.profile.as_str(),
+            "Profile: {} (P) | Region: {} (R)",
+            if self.state.profile.is_empty() {
+                "default"
+            } else {
+                self.state.profile.as_str()
+            },
             self.state.region.as_str(),
         );
+        if let Some(banner) = &self.state.auth_banner {
+            buf.set_style(rows[1], styles::popup_border(&theme).reversed());
+            Line::from(format!(" {}", banner.text()))
+                .bold()
+                .render(rows[1], buf);
+        }
+
         Line::from(self.state.app_title.as_str())
             .bold()
             .style(theme.header)
@@ -86,8 +110,21 @@ impl Widget for &App {
             .style(footer_style)
             .render(footer[1], buf);
 
+        // A loaded filter can bind several groups (and streams) at once
+        let groups_title = match (
+            self.state.filter_groups.len(),
+            self.state.filter_streams.is_empty(),
+        ) {
+            (0, true) => "Groups".to_string(),
+            (n, true) => format!("Groups ({n} from filter)"),
+            (n, false) => format!(
+                "Groups ({} from filter, streams {}*)",
+                n.max(1),
+                self.state.filter_streams.join("*, ")
+            ),
+        };
         let groups_block = Block::bordered()
-            .title("Groups")
+            .title(groups_title)
             .style(groups_block_style)
             .border_style(groups_border);
 
@@ -102,13 +139,65 @@ impl Widget for &App {
         let filter_inner = filter_block.inner(groups_row[1]);
         filter_block.render(groups_row[1], buf);
 
+        // Event-count timeline above the results, once there are events to bucket
+        let histogram = self.histogram();
+        let results_area = if let Some(hist) = &histogram {
+            let rows =
+                Layout::vertical([Constraint::Length(5), Constraint::Min(0)]).split(chunks[2]);
+
+            let mut title = format!(
+                "Timeline ({} buckets, peak {})",
+                hist.bucket_label(),
+                hist.max_count()
+            );
+            if let Some(idx) = self.state.histogram_selected {
+                let (from, to) = hist.bucket_range(idx);
+                let fmt = |ms: i64| {
+                    chrono::DateTime::<chrono::Utc>::from_timestamp_millis(ms)
+                        .map(|dt| dt.format("%H:%M:%S").to_string())
+                        .unwrap_or_default()
+                };
+                title.push_str(&format!(
+                    " | {}–{}: {} events  z Zoom",
+                    fmt(from),
+                    fmt(to),
+                    hist.counts[idx]
+                ));
+            }
+            let timeline_block = Block::bordered()
+                .title(title)
+                .style(results_block_style)
+                .border_style(results_border);
+            let timeline_inner = timeline_block.inner(rows[0]);
+            timeline_block.render(rows[0], buf);
+            self.render_histogram(hist, timeline_inner, buf);
+
+            rows[1]
+        } else {
+            chunks[2]
+        };
+
+        // Field discovery panel docks to the right of the results
+        let results_area = if self.state.field_panel_open {
+            let cols = Layout::horizontal([Constraint::Min(0), Constraint::Length(44)])
+                .split(results_area);
+            self.render_field_panel(cols[1], buf);
+            cols[0]
+        } else {
+            results_area
+        };
+
+        let results_title = match self.selected_entry_range() {
+            Some((from, to)) => format!("Results ({} selected)", to - from + 1),
+            None => "Results".to_string(),
+        };
         let results_block = Block::bordered()
-            .title("Results")
+            .title(results_title)
             .style(results_block_style)
             .border_style(results_border);
 
-        let results_inner = results_block.inner(chunks[2]);
-        results_block.render(chunks[2], buf);
+        let results_inner = results_block.inner(results_area);
+        results_block.render(results_area, buf);
 
         let visible_rows = inner.height as usize;
         let start = self.state.groups_scroll;
@@ -118,7 +207,13 @@ impl Widget for &App {
             let group = &self.state.groups[idx];
 
             let selected = idx == self.state.selected_group;
-            let marker = if selected { "(●) " } else { "( ) " };
+            let marker = if selected {
+                "(●) "
+            } else if self.state.filter_groups.contains(group) {
+                "(+) "
+            } else {
+                "( ) "
+            };
 
             let y = inner.y + row as u16;
             Line::from(format!("{marker}{group}"))
@@ -310,19 +405,17 @@ impl Widget for &App {
 
             // Label + current name on the next line
             let label = "Name:";
-            Line::from(label)
-                .style(Style::default().fg(Color::White))
-                .render(
-                    Rect {
-                        x: inner.x,
-                        y: inner.y,
-                        width: inner.width,
-                        height: 1,
-                    },
-                    buf,
-                );
+            Line::from(label).style(styles::popup_text(&theme)).render(
+                Rect {
+                    x: inner.x,
+                    y: inner.y,
+                    width: inner.width,
+                    height: 1,
+                },
+                buf,
+            );
 
-            let name_line = format!("{}", self.state.save_filter_name);
+            let name_line = self.state.save_filter_name.to_string();
             Line::from(name_line)
                 .style(styles::popup_border(&theme))
                 .render(
@@ -337,7 +430,7 @@ impl Widget for &App {
 
             // Hint line
             Line::from("Enter Save   Esc Cancel")
-                .style(Style::default().fg(Color::Gray))
+                .style(styles::popup_hint(&theme))
                 .render(
                     Rect {
                         x: inner.x,
@@ -349,73 +442,48 @@ impl Widget for &App {
                 );
         }
 
-        if self.state.load_filter_popup_open {
-            // Centered popup sized to number of filters (up to a max height)
-            let popup_width = 40u16.min(area.width);
-            let max_height = 10u16;
-            let needed_height = (self.state.saved_filters.len() as u16 + 3).max(3);
-            let popup_height = max_height.min(needed_height).min(area.height);
-            let popup_x = area.x + (area.width.saturating_sub(popup_width)) / 2;
-            let popup_y = area.y + (area.height.saturating_sub(popup_height)) / 2;
+        if self.state.value_picker_open {
+            self.render_value_picker(area, buf);
+        }
 
-            let popup_area = Rect {
-                x: popup_x,
-                y: popup_y,
-                width: popup_width,
-                height: popup_height,
-            };
+        if self.state.export_popup_open {
+            self.render_export_popup(area, buf);
+        }
 
-            let block = Block::bordered()
-                .title("Load filter")
-                .style(styles::popup_block(&theme))
-                .border_style(styles::popup_border(&theme));
-            let inner = block.inner(popup_area);
-            block.render(popup_area, buf);
+        if self.state.pipe_menu_open {
+            self.render_pipe_menu(area, buf);
+        }
 
-            // Render filter names with a simple highlight on the selected one
-            let mut y = inner.y;
-            for (idx, f) in self.state.saved_filters.iter().enumerate() {
-                if y >= inner.y + inner.height {
-                    break;
-                }
+        if self.state.copy_menu_open {
+            self.render_copy_menu(area, buf);
+        }
 
-                let marker = if idx == self.state.load_filter_selected {
-                    ">"
-                } else {
-                    " "
-                };
-                let line = format!("{marker} {}", f.name);
-                let style = if idx == self.state.load_filter_selected {
-                    styles::popup_border(&theme)
-                } else {
-                    Style::default().fg(Color::White)
-                };
+        if self.state.import_url_popup_open {
+            self.render_import_url_popup(area, buf);
+        }
 
-                Line::from(line).style(style).render(
-                    Rect {
-                        x: inner.x,
-                        y,
-                        width: inner.width,
-                        height: 1,
-                    },
-                    buf,
-                );
+        if self.state.history_popup_open {
+            self.render_history_popup(area, buf);
+        }
 
-                y += 1;
-            }
+        if self.state.context_picker.is_some() {
+            self.render_context_picker(area, buf);
+        }
 
-            // Hint line at the bottom of the popup
-            Line::from("Enter Load   Esc Cancel")
-                .style(styles::default_gray(&theme))
-                .render(
-                    Rect {
-                        x: inner.x,
-                        y: inner.y + inner.height.saturating_sub(1),
-                        width: inner.width,
-                        height: 1,
-                    },
-                    buf,
-                );
+        if self.state.load_filter_popup_open {
+            self.render_load_filter_popup(area, buf);
+        }
+
+        if self.state.filter_vars_prompt.is_some() {
+            self.render_filter_vars_prompt(area, buf);
+        }
+
+        if self.state.context_switch_prompt.is_some() {
+            self.render_context_switch_prompt(area, buf);
+        }
+
+        if self.state.mfa_prompt.is_some() {
+            self.render_mfa_prompt(area, buf);
         }
     }
 }
@@ -424,66 +492,22 @@ impl Widget for &App {
 mod ui_tests {
     use super::*;
     use crate::app::state::AppState;
-    use crate::ui::styles::Theme;
+
+    use ratatui::style::Color;
     use ratatui::{buffer::Buffer, layout::Rect};
-    use std::sync::atomic::AtomicBool;
-    use std::sync::{Arc, mpsc};
-    use std::time::Instant;
 
     fn make_app() -> App {
         let groups_owned = vec!["g1".to_string(), "g2".to_string()];
-        let (tx, rx) = mpsc::channel();
-
         let state = AppState {
             app_title: "lumberjack".to_string(),
-            theme: Theme::default_dark(),
-            theme_name: "dark".to_string(),
-            lines: vec![],
-            filter_cursor_pos: 0,
-
             all_groups: groups_owned.clone(),
             groups: groups_owned,
-            selected_group: 0,
-            groups_scroll: 0,
-
             profile: "test".to_string(),
-            region: "eu-west-1".to_string(),
             focus: Focus::Filter,
-
-            filter_start: "".to_string(),
-            filter_end: "".to_string(),
-            filter_query: "".to_string(),
-            filter_field: FilterField::Query,
-            editing: false,
-            cursor_on: true,
-            last_blink: Instant::now(),
-
-            group_search_active: false,
-            group_search_input: "".to_string(),
-
-            searching: false,
-            dots: 0,
-            last_dots: Instant::now(),
-            results_scroll: 0,
-
-            tail_mode: false,
-            status_message: None,
-            status_set_at: None,
-
-            saved_filters: Vec::new(),
-            save_filter_popup_open: false,
-            save_filter_name: String::new(),
-            load_filter_popup_open: false,
-            load_filter_selected: 0,
+            ..AppState::for_test()
         };
 
-        App {
-            state,
-            exit: false,
-            search_tx: tx,
-            search_rx: rx,
-            tail_stop: Arc::new(AtomicBool::new(false)),
-        }
+        App::for_test(state)
     }
 
     fn buffer_contains_symbol(buf: &Buffer, sym: &str) -> bool {
@@ -735,6 +759,30 @@ mod ui_tests {
         );
     }
 
+    #[test]
+    fn field_panel_lists_fields_and_expands_selected_values() {
+        let mut app = make_app();
+        let ev = crate::aws::LogEvent {
+            timestamp_ms: 0,
+            message: r#"{"level":"error","status":500}"#.to_string(),
+            log_stream_name: "s".to_string(),
+            event_id: "1".to_string(),
+        };
+        app.state.lines.push(ev.formatted());
+        app.state.line_events.push(Some(ev));
+        app.state.focus = Focus::Results;
+        app.open_field_panel();
+
+        let area = Rect::new(0, 0, 120, 30);
+        let mut buf = Buffer::empty(area);
+        (&app).render(area, &mut buf);
+
+        assert!(buffer_contains_text(&buf, "Fields"));
+        assert!(buffer_contains_text(&buf, "> level (1)"));
+        assert!(buffer_contains_text(&buf, "\"error\" ×1"));
+        assert!(buffer_contains_text(&buf, "status (1)"));
+    }
+
     #[test]
     fn header_and_footer_use_current_theme() {
         let mut app = make_app(); // starts with Theme::default_dark()
@@ -0,0 +1,86 @@
+use crate::app::App;
+use crate::ui::styles;
+use ratatui::prelude::{Buffer, Rect};
+use ratatui::style::Style;
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Widget};
+
+impl App {
+    /// Centered popup listing `$PAGER`, `$EDITOR` and the configured pipe commands.
+    pub fn render_pipe_menu(&self, area: Rect, buf: &mut Buffer) {
+        let theme = &self.state.theme;
+        let entries = self.pipe_menu_entries();
+
+        let popup_width = 60u16.min(area.width);
+        let needed_height = entries.len() as u16 + 3;
+        let popup_height = needed_height.min(16).min(area.height);
+        let popup_area = Rect {
+            x: area.x + (area.width.saturating_sub(popup_width)) / 2,
+            y: area.y + (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        let title = if self.state.pipe_menu_event {
+            "Pipe current event to"
+        } else {
+            "Pipe results to"
+        };
+        let block = Block::bordered()
+            .title(title)
+            .style(styles::popup_block(theme))
+            .border_style(styles::popup_border(theme));
+        let inner = block.inner(popup_area);
+        block.render(popup_area, buf);
+
+        if inner.height < 2 {
+            return;
+        }
+
+        let list_h = (inner.height - 1) as usize;
+        let selected = self.state.pipe_menu_selected;
+        let offset = (selected + 1).saturating_sub(list_h);
+
+        for (row, (idx, entry)) in entries
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .take(list_h)
+            .enumerate()
+        {
+            let is_selected = idx == selected;
+            let marker = if is_selected { ">" } else { " " };
+            let style = if is_selected {
+                styles::popup_border(theme)
+            } else {
+                Style::default()
+            };
+            Line::from(format!(
+                "{marker} {}: {}",
+                entry.command.name, entry.command.command
+            ))
+            .style(style)
+            .render(
+                Rect {
+                    x: inner.x,
+                    y: inner.y + row as u16,
+                    width: inner.width,
+                    height: 1,
+                },
+                buf,
+            );
+        }
+
+        Line::from("Enter Run  Tab Results/Event  Esc Cancel")
+            .style(styles::default_gray(theme))
+            .render(
+                Rect {
+                    x: inner.x,
+                    y: inner.y + inner.height - 1,
+                    width: inner.width,
+                    height: 1,
+                },
+                buf,
+            );
+    }
+}
@@ -22,13 +22,17 @@ impl App {
             return;
         }
 
-        // Flatten entries into raw lines (no manual wrapping).
+        // Flatten entries into raw lines (no manual wrapping), remembering
+        // which entry each line came from for selection highlighting.
         let mut raw_lines: Vec<String> = Vec::new();
-        for entry in &self.state.lines {
+        let mut line_entry: Vec<usize> = Vec::new();
+        for (entry_idx, entry) in self.state.lines.iter().enumerate() {
             for raw_line in entry.lines() {
                 raw_lines.push(raw_line.to_string());
+                line_entry.push(entry_idx);
             }
         }
+        let selection = self.selected_entry_range();
 
         let total = raw_lines.len();
         let visible_rows = text_area.height as usize;
@@ -36,6 +40,7 @@ impl App {
         if total == 0 {
             App::draw_scrollbar(
                 buf,
+                &theme,
                 results_inner,
                 0,
                 0,
@@ -50,6 +55,19 @@ impl App {
 
         for (i, line) in raw_lines[start..end].iter().enumerate() {
             let y = text_area.y + i as u16;
+            let entry_idx = line_entry[start + i];
+            let selected = selection.is_some_and(|(from, to)| (from..=to).contains(&entry_idx));
+            if selected {
+                buf.set_style(
+                    Rect {
+                        x: text_area.x,
+                        y,
+                        width: text_area.width,
+                        height: 1,
+                    },
+                    theme.results_selected,
+                );
+            }
 
             let expanded = if line.contains('\t') {
                 line.replace('\t', "    ")
@@ -113,6 +131,7 @@ impl App {
         // Draw scrollbar once per frame
         App::draw_scrollbar(
             buf,
+            &theme,
             results_inner,
             start, // first visible line index
             total, // total number of lines
@@ -125,66 +144,19 @@ impl App {
 mod tests {
     use ratatui::buffer::Buffer;
     use ratatui::layout::Rect;
-    use std::sync::atomic::AtomicBool;
-    use std::sync::{Arc, mpsc};
-    use std::time::Instant;
 
     use crate::app::state::AppState;
-    use crate::app::{App, FilterField, Focus};
-    use crate::ui::styles::Theme;
+    use crate::app::{App, Focus};
 
     fn make_results_app(lines: Vec<&str>) -> App {
-        let (tx, rx) = mpsc::channel();
         let state = AppState {
-            app_title: "Test".to_string(),
-            theme: Theme::default_dark(),
-            theme_name: "dark".to_string(),
             lines: lines.into_iter().map(|s| s.to_string()).collect(),
-            filter_cursor_pos: 0,
-
-            all_groups: vec![],
-            groups: vec![],
-            selected_group: 0,
-            groups_scroll: 0,
-
             profile: "test".to_string(),
-            region: "eu-west-1".to_string(),
             focus: Focus::Results,
-
-            filter_start: String::new(),
-            filter_end: String::new(),
-            filter_query: String::new(),
-            filter_field: FilterField::Query,
-            editing: false,
-            cursor_on: true,
-            last_blink: Instant::now(),
-
-            group_search_active: false,
-            group_search_input: String::new(),
-
-            searching: false,
-            dots: 0,
-            last_dots: Instant::now(),
-            results_scroll: 0,
-            tail_mode: false,
-
-            status_message: None,
-            status_set_at: None,
-
-            saved_filters: Vec::new(),
-            save_filter_popup_open: false,
-            save_filter_name: String::new(),
-            load_filter_popup_open: false,
-            load_filter_selected: 0,
+            ..AppState::for_test()
         };
 
-        App {
-            state,
-            exit: false,
-            search_tx: tx,
-            search_rx: rx,
-            tail_stop: Arc::new(AtomicBool::new(false)),
-        }
+        App::for_test(state)
     }
 
     fn buffer_to_string(buf: &Buffer, area: Rect) -> String {
@@ -304,4 +276,21 @@ mod tests {
             "expected scrollbar glyphs in rightmost column, but none were found"
         );
     }
+
+    #[test]
+    fn selected_entries_are_highlighted() {
+        let mut app = make_results_app(vec!["first", "second\nmore", "third"]);
+        app.state.selection_anchor = Some(1);
+        app.state.results_scroll = 1;
+
+        let area = Rect::new(0, 0, 20, 4);
+        let mut buf = Buffer::empty(area);
+        app.render_results(area, &mut buf);
+
+        let selected_bg = app.state.theme.results_selected.bg;
+        // Entry 1 spans rows 1 and 2 (scrolled to the top of the pane)
+        assert_eq!(buf.cell((0, 0)).unwrap().style().bg, selected_bg);
+        assert_eq!(buf.cell((0, 1)).unwrap().style().bg, selected_bg);
+        assert_ne!(buf.cell((0, 2)).unwrap().style().bg, selected_bg);
+    }
 }
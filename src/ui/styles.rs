@@ -1,4 +1,51 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
 use ratatui::style::{Color, Modifier, Style};
+use serde::Deserialize;
+
+/// Themes that ship with lumberjack, in `T` cycle order.
+pub const BUILTIN_THEMES: [&str; 3] = ["dark", "light", "green"];
+
+/// Colorless theme used when `NO_COLOR` is set.
+pub const NO_COLOR_THEME: &str = "no-color";
+
+/// Every `Theme` field, as named in `themes.json`.
+pub const THEME_FIELDS: [&str; 33] = [
+    "header",
+    "footer",
+    "groups_block_focused",
+    "groups_block_unfocused",
+    "groups_item_focused",
+    "groups_item_unfocused",
+    "groups_selected_focused",
+    "groups_selected_unfocused",
+    "filter_block_focused",
+    "filter_block_unfocused",
+    "results_block_focused",
+    "results_block_unfocused",
+    "pane_border_focused",
+    "pane_border_unfocused",
+    "default_gray",
+    "filter_field_active_editing",
+    "filter_field_active_idle",
+    "filter_field_inactive",
+    "popup_block",
+    "popup_border",
+    "popup_text",
+    "popup_hint",
+    "presets_hint",
+    "cursor",
+    "results_timestamp",
+    "results_selected",
+    "histogram_bar",
+    "histogram_current",
+    "histogram_selected",
+    "scrollbar_track_focused",
+    "scrollbar_track_unfocused",
+    "scrollbar_thumb_focused",
+    "scrollbar_thumb_unfocused",
+];
 
 #[derive(Clone, Debug)]
 pub struct Theme {
@@ -28,12 +75,116 @@ pub struct Theme {
 
     pub popup_block: Style,
     pub popup_border: Style,
+    pub popup_text: Style,
+    pub popup_hint: Style,
     pub presets_hint: Style,
     pub cursor: Style,
     pub results_timestamp: Style,
+    pub results_selected: Style,
+
+    pub histogram_bar: Style,
+    pub histogram_current: Style,
+    pub histogram_selected: Style,
+
+    pub scrollbar_track_focused: Style,
+    pub scrollbar_track_unfocused: Style,
+    pub scrollbar_thumb_focused: Style,
+    pub scrollbar_thumb_unfocused: Style,
 }
 
 impl Theme {
+    /// Built-in theme by name ("dark", "light" or "green").
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Theme::default_dark()),
+            "light" => Some(Theme::light()),
+            "green" => Some(Theme::green()),
+            _ => None,
+        }
+    }
+
+    fn style_mut(&mut self, field: &str) -> Option<&mut Style> {
+        Some(match field {
+            "header" => &mut self.header,
+            "footer" => &mut self.footer,
+            "groups_block_focused" => &mut self.groups_block_focused,
+            "groups_block_unfocused" => &mut self.groups_block_unfocused,
+            "groups_item_focused" => &mut self.groups_item_focused,
+            "groups_item_unfocused" => &mut self.groups_item_unfocused,
+            "groups_selected_focused" => &mut self.groups_selected_focused,
+            "groups_selected_unfocused" => &mut self.groups_selected_unfocused,
+            "filter_block_focused" => &mut self.filter_block_focused,
+            "filter_block_unfocused" => &mut self.filter_block_unfocused,
+            "results_block_focused" => &mut self.results_block_focused,
+            "results_block_unfocused" => &mut self.results_block_unfocused,
+            "pane_border_focused" => &mut self.pane_border_focused,
+            "pane_border_unfocused" => &mut self.pane_border_unfocused,
+            "default_gray" => &mut self.default_gray,
+            "filter_field_active_editing" => &mut self.filter_field_active_editing,
+            "filter_field_active_idle" => &mut self.filter_field_active_idle,
+            "filter_field_inactive" => &mut self.filter_field_inactive,
+            "popup_block" => &mut self.popup_block,
+            "popup_border" => &mut self.popup_border,
+            "popup_text" => &mut self.popup_text,
+            "popup_hint" => &mut self.popup_hint,
+            "presets_hint" => &mut self.presets_hint,
+            "cursor" => &mut self.cursor,
+            "results_timestamp" => &mut self.results_timestamp,
+            "results_selected" => &mut self.results_selected,
+            "histogram_bar" => &mut self.histogram_bar,
+            "histogram_current" => &mut self.histogram_current,
+            "histogram_selected" => &mut self.histogram_selected,
+            "scrollbar_track_focused" => &mut self.scrollbar_track_focused,
+            "scrollbar_track_unfocused" => &mut self.scrollbar_track_unfocused,
+            "scrollbar_thumb_focused" => &mut self.scrollbar_thumb_focused,
+            "scrollbar_thumb_unfocused" => &mut self.scrollbar_thumb_unfocused,
+            _ => return None,
+        })
+    }
+
+    /// Only bold/reversed/underline, for terminals where `NO_COLOR` is set.
+    pub fn no_color() -> Self {
+        let plain = Style::default();
+        let bold = plain.add_modifier(Modifier::BOLD);
+        let reversed = plain.add_modifier(Modifier::REVERSED);
+
+        Theme {
+            header: bold,
+            footer: plain,
+            groups_block_focused: plain,
+            groups_block_unfocused: plain,
+            groups_item_focused: plain,
+            groups_item_unfocused: plain,
+            groups_selected_focused: reversed,
+            groups_selected_unfocused: bold,
+            filter_block_focused: plain,
+            filter_block_unfocused: plain,
+            results_block_focused: plain,
+            results_block_unfocused: plain,
+            pane_border_focused: bold,
+            pane_border_unfocused: plain,
+            default_gray: plain,
+            filter_field_active_editing: reversed,
+            filter_field_active_idle: plain.add_modifier(Modifier::UNDERLINED),
+            filter_field_inactive: plain,
+            popup_block: plain,
+            popup_border: bold,
+            popup_text: plain,
+            popup_hint: plain.add_modifier(Modifier::DIM),
+            presets_hint: plain.add_modifier(Modifier::DIM),
+            cursor: reversed,
+            results_timestamp: bold,
+            results_selected: reversed,
+            histogram_bar: plain,
+            histogram_current: bold,
+            histogram_selected: reversed,
+            scrollbar_track_focused: plain,
+            scrollbar_track_unfocused: plain.add_modifier(Modifier::DIM),
+            scrollbar_thumb_focused: bold,
+            scrollbar_thumb_unfocused: plain,
+        }
+    }
+
     pub fn default_dark() -> Self {
         Theme {
             header: Style::default().bg(Color::Rgb(10, 10, 10)).fg(Color::White),
@@ -77,6 +228,8 @@ impl Theme {
 
             popup_block: Style::default().bg(Color::Rgb(30, 30, 30)).fg(Color::White),
             popup_border: Style::default().fg(Color::Yellow),
+            popup_text: Style::default().fg(Color::White),
+            popup_hint: Style::default().fg(Color::Gray),
 
             presets_hint: Style::default().fg(Color::Rgb(50, 50, 50)),
             cursor: Style::default().fg(Color::White).bg(Color::Rgb(20, 20, 20)),
@@ -84,6 +237,22 @@ impl Theme {
                 .fg(Color::Rgb(100, 180, 180))
                 .bg(Color::Rgb(5, 5, 5))
                 .add_modifier(Modifier::BOLD),
+            results_selected: Style::default().bg(Color::Rgb(45, 45, 70)),
+
+            histogram_bar: Style::default().fg(Color::Rgb(70, 130, 130)),
+            histogram_current: Style::default().fg(Color::Rgb(120, 230, 230)),
+            histogram_selected: Style::default().fg(Color::Yellow),
+
+            scrollbar_track_focused: Style::default()
+                .fg(Color::Rgb(130, 130, 130))
+                .bg(Color::Rgb(5, 5, 5)),
+            scrollbar_track_unfocused: Style::default()
+                .fg(Color::Rgb(80, 80, 80))
+                .bg(Color::Rgb(14, 14, 14)),
+            scrollbar_thumb_focused: Style::default().fg(Color::Cyan).bg(Color::Rgb(5, 5, 5)),
+            scrollbar_thumb_unfocused: Style::default()
+                .fg(Color::Rgb(200, 200, 200))
+                .bg(Color::Rgb(14, 14, 14)),
         }
     }
 
@@ -142,6 +311,8 @@ impl Theme {
         // Popup
         t.popup_block = Style::default().bg(Color::Rgb(245, 245, 245)).fg(text);
         t.popup_border = Style::default().fg(Color::Rgb(100, 100, 100));
+        t.popup_text = Style::default().fg(text);
+        t.popup_hint = Style::default().fg(Color::Rgb(100, 100, 100));
 
         // Presets hint, cursor
         t.presets_hint = Style::default().fg(Color::Rgb(100, 100, 100));
@@ -151,6 +322,16 @@ impl Theme {
             .fg(Color::Rgb(0, 100, 180))
             .bg(bg)
             .add_modifier(Modifier::BOLD);
+        t.results_selected = Style::default().bg(Color::Rgb(200, 215, 240));
+
+        t.histogram_bar = Style::default().fg(Color::Rgb(140, 170, 200));
+        t.histogram_current = Style::default().fg(Color::Rgb(0, 100, 180));
+        t.histogram_selected = Style::default().fg(Color::Rgb(200, 90, 0));
+
+        t.scrollbar_track_focused = Style::default().fg(Color::Rgb(160, 160, 160)).bg(bg);
+        t.scrollbar_track_unfocused = Style::default().fg(Color::Rgb(200, 200, 200)).bg(bg);
+        t.scrollbar_thumb_focused = Style::default().fg(Color::Rgb(0, 100, 180)).bg(bg);
+        t.scrollbar_thumb_unfocused = Style::default().fg(Color::Rgb(120, 120, 120)).bg(bg);
         t
     }
 
@@ -223,6 +404,8 @@ impl Theme {
         // Popups: same phosphor look
         t.popup_block = Style::default().bg(dark_bg).fg(green);
         t.popup_border = Style::default().fg(green);
+        t.popup_text = Style::default().fg(green);
+        t.popup_hint = Style::default().fg(green);
 
         t.presets_hint = Style::default().fg(green);
 
@@ -233,10 +416,118 @@ impl Theme {
             .fg(Color::Rgb(0, 180, 180))
             .bg(dark_bg)
             .add_modifier(Modifier::BOLD);
+        t.results_selected = Style::default().bg(Color::Rgb(0, 50, 0));
+
+        t.histogram_bar = Style::default().fg(Color::Rgb(0, 140, 0));
+        t.histogram_current = Style::default().fg(green);
+        t.histogram_selected = Style::default().fg(Color::Rgb(0, 180, 180));
+
+        t.scrollbar_track_focused = Style::default().fg(Color::Rgb(0, 140, 0)).bg(dark_bg);
+        t.scrollbar_track_unfocused = Style::default().fg(band_bg).bg(dark_bg);
+        t.scrollbar_thumb_focused = Style::default().fg(green).bg(dark_bg);
+        t.scrollbar_thumb_unfocused = Style::default().fg(Color::Rgb(0, 140, 0)).bg(dark_bg);
         t
     }
 }
 
+/// One style in `themes.json`. Missing parts keep the base theme's value;
+/// `modifiers` replaces the base modifiers when given (`[]` clears them).
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct StyleSpec {
+    pub fg: Option<String>,
+    pub bg: Option<String>,
+    pub modifiers: Option<Vec<String>>,
+}
+
+/// A user theme: a base theme plus the fields it changes.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ThemeSpec {
+    pub name: String,
+    #[serde(default = "default_base_theme")]
+    pub base: String,
+    #[serde(default)]
+    pub styles: BTreeMap<String, StyleSpec>,
+}
+
+fn default_base_theme() -> String {
+    "dark".to_string()
+}
+
+/// Named (`red`, `dark gray`), indexed (`236`) or hex (`#1e1e2e`) colors.
+pub fn parse_color(value: &str) -> Result<Color, String> {
+    Color::from_str(value.trim()).map_err(|_| format!("unknown color '{value}'"))
+}
+
+pub fn parse_modifier(value: &str) -> Result<Modifier, String> {
+    Ok(
+        match value.trim().to_ascii_lowercase().replace('-', "_").as_str() {
+            "bold" => Modifier::BOLD,
+            "dim" => Modifier::DIM,
+            "italic" => Modifier::ITALIC,
+            "underlined" | "underline" => Modifier::UNDERLINED,
+            "slow_blink" => Modifier::SLOW_BLINK,
+            "rapid_blink" => Modifier::RAPID_BLINK,
+            "reversed" => Modifier::REVERSED,
+            "hidden" => Modifier::HIDDEN,
+            "crossed_out" => Modifier::CROSSED_OUT,
+            _ => return Err(format!("unknown modifier '{value}'")),
+        },
+    )
+}
+
+impl StyleSpec {
+    fn apply(&self, base: Style) -> Result<Style, String> {
+        let mut style = base;
+        if let Some(fg) = &self.fg {
+            style.fg = Some(parse_color(fg)?);
+        }
+        if let Some(bg) = &self.bg {
+            style.bg = Some(parse_color(bg)?);
+        }
+        if let Some(modifiers) = &self.modifiers {
+            style.add_modifier = Modifier::empty();
+            style.sub_modifier = Modifier::empty();
+            for m in modifiers {
+                style.add_modifier |= parse_modifier(m)?;
+            }
+        }
+        Ok(style)
+    }
+}
+
+impl ThemeSpec {
+    pub fn build(&self) -> Result<Theme, String> {
+        let mut theme = Theme::from_name(&self.base).ok_or_else(|| {
+            format!(
+                "theme '{}': unknown base '{}' (expected {})",
+                self.name,
+                self.base,
+                BUILTIN_THEMES.join(", ")
+            )
+        })?;
+        for (field, spec) in &self.styles {
+            let style = theme.style_mut(field).ok_or_else(|| {
+                format!(
+                    "theme '{}': unknown style '{field}' (expected one of {})",
+                    self.name,
+                    THEME_FIELDS.join(", ")
+                )
+            })?;
+            *style = spec
+                .apply(*style)
+                .map_err(|e| format!("theme '{}', {field}: {e}", self.name))?;
+        }
+        Ok(theme)
+    }
+}
+
+/// Built-in theme or one of `user` by name.
+pub fn theme_by_name(name: &str, user: &[(String, Theme)]) -> Option<Theme> {
+    Theme::from_name(name).or_else(|| user.iter().find(|(n, _)| n == name).map(|(_, t)| t.clone()))
+}
+
 pub fn groups_block(theme: &Theme, focus: bool) -> Style {
     if focus {
         theme.groups_block_focused
@@ -309,6 +600,14 @@ pub fn popup_border(theme: &Theme) -> Style {
     theme.popup_border
 }
 
+pub fn popup_text(theme: &Theme) -> Style {
+    theme.popup_text
+}
+
+pub fn popup_hint(theme: &Theme) -> Style {
+    theme.popup_hint
+}
+
 pub fn presets_hint(theme: &Theme) -> Style {
     theme.presets_hint
 }
@@ -316,3 +615,106 @@ pub fn presets_hint(theme: &Theme) -> Style {
 pub fn cursor(theme: &Theme) -> Style {
     theme.cursor
 }
+
+pub fn scrollbar_track(theme: &Theme, focus: bool) -> Style {
+    if focus {
+        theme.scrollbar_track_focused
+    } else {
+        theme.scrollbar_track_unfocused
+    }
+}
+
+pub fn scrollbar_thumb(theme: &Theme, focus: bool) -> Style {
+    if focus {
+        theme.scrollbar_thumb_focused
+    } else {
+        theme.scrollbar_thumb_unfocused
+    }
+}
+
+pub fn histogram_bar(theme: &Theme, current: bool, selected: bool) -> Style {
+    if selected {
+        theme.histogram_selected
+    } else if current {
+        theme.histogram_current
+    } else {
+        theme.histogram_bar
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(json: &str) -> ThemeSpec {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn every_listed_field_is_settable() {
+        let mut theme = Theme::default_dark();
+        for field in THEME_FIELDS {
+            assert!(theme.style_mut(field).is_some(), "{field}");
+        }
+    }
+
+    #[test]
+    fn no_color_theme_sets_no_colors() {
+        let mut theme = Theme::no_color();
+        for field in THEME_FIELDS {
+            let style = *theme.style_mut(field).unwrap();
+            assert_eq!((style.fg, style.bg), (None, None), "{field}");
+        }
+    }
+
+    #[test]
+    fn user_theme_patches_its_base() {
+        let theme = spec(
+            r##"{
+                "name": "solarized",
+                "base": "light",
+                "styles": {
+                    "header": { "fg": "#839496", "bg": "dark gray", "modifiers": ["bold", "italic"] },
+                    "groups_selected_focused": { "modifiers": [] },
+                    "cursor": { "bg": "236" }
+                }
+            }"##,
+        )
+        .build()
+        .unwrap();
+        let light = Theme::light();
+
+        assert_eq!(theme.header.fg, Some(Color::Rgb(0x83, 0x94, 0x96)));
+        assert_eq!(theme.header.bg, Some(Color::DarkGray));
+        assert_eq!(theme.header.add_modifier, Modifier::BOLD | Modifier::ITALIC);
+        // Colors kept from the base, modifiers cleared
+        assert_eq!(
+            theme.groups_selected_focused.bg,
+            light.groups_selected_focused.bg
+        );
+        assert_eq!(
+            theme.groups_selected_focused.add_modifier,
+            Modifier::empty()
+        );
+        assert_eq!(theme.cursor.bg, Some(Color::Indexed(236)));
+        assert_eq!(theme.cursor.fg, light.cursor.fg);
+        // Untouched fields come straight from the base
+        assert_eq!(theme.footer, light.footer);
+    }
+
+    #[test]
+    fn rejects_unknown_fields_colors_and_bases() {
+        let err = spec(r#"{ "name": "x", "styles": { "headr": {} } }"#)
+            .build()
+            .unwrap_err();
+        assert!(err.contains("unknown style 'headr'"), "{err}");
+
+        let err = spec(r#"{ "name": "x", "styles": { "header": { "fg": "chartreuse" } } }"#)
+            .build()
+            .unwrap_err();
+        assert!(err.contains("unknown color 'chartreuse'"), "{err}");
+
+        assert!(spec(r#"{ "name": "x", "base": "sepia" }"#).build().is_err());
+        assert!(parse_modifier("sparkly").is_err());
+    }
+}
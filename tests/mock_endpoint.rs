@@ -0,0 +1,381 @@
+//! Runs the binary against a local mock of the CloudWatch Logs JSON API,
+//! reached through `--endpoint-url`, `LUMBERJACK_ENDPOINT_URL` or the
+//! config file.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::process::{Command, Output};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+const GROUPS: &str = r#"{
+    "logGroups": [
+        {"logGroupName": "/app/api", "creationTime": 1700000000000, "retentionInDays": 14, "storedBytes": 2048},
+        {"logGroupName": "/app/worker", "creationTime": 1700000000000, "storedBytes": 0}
+    ]
+}"#;
+
+const STREAMS: &str = r#"{
+    "logStreams": [
+        {"logStreamName": "api/2", "creationTime": 1700000000000, "lastEventTimestamp": 1700000060000}
+    ]
+}"#;
+
+const EVENTS: &str = r#"{
+    "events": [
+        {"logStreamName": "api/1", "timestamp": 1700000000000, "message": "ERROR payment declined", "eventId": "1"},
+        {"logStreamName": "api/2", "timestamp": 1700000001000, "message": "ERROR card expired", "eventId": "2"}
+    ],
+    "searchedLogStreams": []
+}"#;
+
+const EXPIRED: &str = r#"{
+    "__type": "ExpiredTokenException",
+    "message": "The security token included in the request is expired"
+}"#;
+
+/// One request as the mock saw it: the `X-Amz-Target` action and the body.
+#[derive(Clone, Debug)]
+struct Request {
+    action: String,
+    body: String,
+}
+
+/// Answers every CloudWatch Logs action with canned JSON (or with expired
+/// credentials when `expired`) and records what it was asked.
+struct MockCloudWatch {
+    url: String,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl MockCloudWatch {
+    fn start(expired: bool) -> MockCloudWatch {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let seen = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                serve(stream, expired, &seen);
+            }
+        });
+        MockCloudWatch { url, requests }
+    }
+
+    fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+fn serve(mut stream: TcpStream, expired: bool, seen: &Mutex<Vec<Request>>) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut action = String::new();
+    let mut length = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).unwrap_or(0) == 0 {
+            return;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            match name.trim().to_ascii_lowercase().as_str() {
+                "x-amz-target" => action = value.trim().rsplit('.').next().unwrap().to_string(),
+                "content-length" => length = value.trim().parse().unwrap_or(0),
+                _ => {}
+            }
+        }
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).unwrap();
+
+    let (status, reply) = match action.as_str() {
+        _ if expired => ("400 Bad Request", EXPIRED),
+        "DescribeLogGroups" => ("200 OK", GROUPS),
+        "DescribeLogStreams" => ("200 OK", STREAMS),
+        "FilterLogEvents" => ("200 OK", EVENTS),
+        _ => ("200 OK", "{}"),
+    };
+    seen.lock().unwrap().push(Request {
+        action,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    });
+    let _ = write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: application/x-amz-json-1.1\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{reply}",
+        reply.len()
+    );
+}
+
+/// A HOME of its own, so no real AWS or lumberjack config is read; removed
+/// again when the test ends, pass or fail.
+struct TempHome(PathBuf);
+
+impl TempHome {
+    fn new(name: &str) -> TempHome {
+        let home =
+            std::env::temp_dir().join(format!("lumberjack-it-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&home);
+        std::fs::create_dir_all(&home).unwrap();
+        TempHome(home)
+    }
+
+    fn config_dir(&self) -> PathBuf {
+        let dir = self.0.join(".config").join("lumberjack");
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+}
+
+impl Drop for TempHome {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Run the binary with fake static credentials and nothing else from the
+/// environment that could point it at real AWS.
+fn lumberjack(home: &TempHome, args: &[&str], env: &[(&str, &str)]) -> Output {
+    let home = &home.0;
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_lumberjack"));
+    cmd.args(args)
+        .env("HOME", home)
+        .env("AWS_ACCESS_KEY_ID", "AKIDTEST")
+        .env("AWS_SECRET_ACCESS_KEY", "secret")
+        .env("AWS_CONFIG_FILE", home.join("aws-config"))
+        .env("AWS_SHARED_CREDENTIALS_FILE", home.join("aws-credentials"))
+        .env("AWS_EC2_METADATA_DISABLED", "true")
+        .env_remove("AWS_PROFILE")
+        .env_remove("AWS_SESSION_TOKEN")
+        .env_remove("AWS_ENDPOINT_URL")
+        .env_remove("LUMBERJACK_ENDPOINT_URL");
+    for (key, value) in env {
+        cmd.env(key, value);
+    }
+    cmd.output().unwrap()
+}
+
+#[test]
+fn groups_are_listed_from_the_endpoint_flag() {
+    let mock = MockCloudWatch::start(false);
+    let home = TempHome::new("groups");
+
+    let out = lumberjack(
+        &home,
+        &[
+            "groups",
+            "--region",
+            "us-east-1",
+            "--endpoint-url",
+            &mock.url,
+            "--prefix",
+            "/app",
+        ],
+        &[],
+    );
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 2, "{stdout}");
+    assert!(lines[0].starts_with("/app/api\t"), "{stdout}");
+    assert!(lines[0].contains("\t14\t"), "{stdout}");
+    assert!(lines[0].ends_with("\t2023-11-14T22:14:20Z"), "{stdout}");
+    assert!(lines[1].starts_with("/app/worker\t"), "{stdout}");
+
+    let requests = mock.requests();
+    assert_eq!(requests[0].action, "DescribeLogGroups");
+    assert!(
+        requests[0].body.contains(r#""logGroupNamePrefix":"/app""#),
+        "{:?}",
+        requests
+    );
+    // The newest stream of each group, for the last event column
+    let lookups: Vec<&Request> = requests
+        .iter()
+        .filter(|r| r.action == "DescribeLogStreams")
+        .collect();
+    assert_eq!(lookups.len(), 2, "{requests:?}");
+    assert!(
+        lookups
+            .iter()
+            .all(|r| r.body.contains(r#""orderBy":"LastEventTime""#)
+                && r.body.contains(r#""limit":1"#)),
+        "{lookups:?}"
+    );
+}
+
+#[test]
+fn groups_skip_the_last_event_lookup_when_asked() {
+    let mock = MockCloudWatch::start(false);
+    let home = TempHome::new("no-last-event");
+
+    let out = lumberjack(
+        &home,
+        &[
+            "groups",
+            "--region",
+            "us-east-1",
+            "--endpoint-url",
+            &mock.url,
+            "--no-last-event",
+        ],
+        &[],
+    );
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    assert!(stdout.lines().all(|l| l.ends_with("\t-")), "{stdout}");
+    assert!(
+        mock.requests()
+            .iter()
+            .all(|r| r.action == "DescribeLogGroups"),
+        "{:?}",
+        mock.requests()
+    );
+}
+
+#[test]
+fn query_fetches_events_through_the_env_var() {
+    let mock = MockCloudWatch::start(false);
+    let home = TempHome::new("query");
+
+    let out = lumberjack(
+        &home,
+        &[
+            "query",
+            "--region",
+            "us-east-1",
+            "--group",
+            "/app/api",
+            "--query",
+            "ERROR",
+            "--start",
+            "-1h",
+            "--format",
+            "raw",
+        ],
+        &[("LUMBERJACK_ENDPOINT_URL", &mock.url)],
+    );
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&out.stdout),
+        "ERROR payment declined\nERROR card expired\n"
+    );
+
+    let requests = mock.requests();
+    assert_eq!(requests[0].action, "FilterLogEvents");
+    assert!(
+        requests[0].body.contains(r#""logGroupName":"/app/api""#),
+        "{:?}",
+        requests
+    );
+    assert!(requests[0].body.contains("ERROR"), "{:?}", requests);
+}
+
+#[test]
+fn limit_is_sent_to_every_group_of_a_filter() {
+    let mock = MockCloudWatch::start(false);
+    let home = TempHome::new("limit");
+    let config_dir = home.config_dir();
+    std::fs::write(
+        config_dir.join("filters.json"),
+        r#"[{ "name": "errors", "group": "/app/api", "groups": ["/app/worker"],
+              "start": "-1h", "end": "", "query": "ERROR" }]"#,
+    )
+    .unwrap();
+
+    let out = lumberjack(
+        &home,
+        &[
+            "query",
+            "--region",
+            "us-east-1",
+            "--endpoint-url",
+            &mock.url,
+            "--filter",
+            "errors",
+            "--limit",
+            "1",
+            "--format",
+            "raw",
+        ],
+        &[],
+    );
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&out.stdout),
+        "ERROR payment declined\n"
+    );
+
+    let fetches: Vec<Request> = mock
+        .requests()
+        .into_iter()
+        .filter(|r| r.action == "FilterLogEvents")
+        .collect();
+    assert_eq!(fetches.len(), 2, "{fetches:?}");
+    assert!(
+        fetches.iter().all(|r| r.body.contains(r#""limit":1"#)),
+        "{fetches:?}"
+    );
+}
+
+#[test]
+fn config_endpoint_reports_expired_credentials() {
+    let mock = MockCloudWatch::start(true);
+    let home = TempHome::new("expired");
+    let config_dir = home.config_dir();
+    std::fs::write(
+        config_dir.join("config.json"),
+        format!(r#"{{ "defaults": {{ "endpoint_url": "{}" }} }}"#, mock.url),
+    )
+    .unwrap();
+
+    let out = lumberjack(&home, &["groups", "--region", "us-east-1"], &[]);
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert_eq!(out.status.code(), Some(2), "{stderr}");
+    // Classified as expired credentials, not passed through as a raw error;
+    // static keys get no `aws sso login` hint
+    assert_eq!(
+        stderr.lines().next(),
+        Some(
+            "error: Credentials for profile 'default' have expired or were rejected; \
+             refresh them and retry"
+        ),
+        "{stderr}"
+    );
+    assert_eq!(mock.requests()[0].action, "DescribeLogGroups");
+}
+
+#[test]
+fn bad_endpoint_urls_are_rejected_before_any_call() {
+    let home = TempHome::new("bad-url");
+
+    let out = lumberjack(&home, &["groups", "--endpoint-url", "localhost:4566"], &[]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("--endpoint-url"));
+
+    let out = lumberjack(&home, &["groups"], &[("LUMBERJACK_ENDPOINT_URL", "nope")]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(
+        String::from_utf8_lossy(&out.stderr)
+            .starts_with("error: invalid LUMBERJACK_ENDPOINT_URL 'nope'")
+    );
+}